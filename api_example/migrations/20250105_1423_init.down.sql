@@ -0,0 +1 @@
+DROP TABLE IF EXISTS todo
\ No newline at end of file
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS todo_workspace_id_idx;
+ALTER TABLE todo DROP COLUMN IF EXISTS workspace_id;
+
+DROP TABLE IF EXISTS membership;
+DROP TABLE IF EXISTS workspace;
+DROP TYPE IF EXISTS workspace_role
\ No newline at end of file
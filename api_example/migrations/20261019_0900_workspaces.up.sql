@@ -0,0 +1,26 @@
+CREATE TYPE workspace_role AS ENUM ('viewer', 'editor', 'owner');
+
+CREATE TABLE IF NOT EXISTS workspace (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS membership (
+    workspace_id BIGINT NOT NULL REFERENCES workspace (id) ON DELETE CASCADE,
+    user_id BIGINT NOT NULL,
+    role workspace_role NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (workspace_id, user_id)
+);
+
+CREATE INDEX IF NOT EXISTS membership_user_id_idx ON membership (user_id);
+
+-- существующие todo переезжают в общий workspace без участников
+INSERT INTO workspace (name) SELECT 'default' WHERE EXISTS (SELECT 1 FROM todo);
+
+ALTER TABLE todo ADD COLUMN workspace_id BIGINT REFERENCES workspace (id) ON DELETE CASCADE;
+UPDATE todo SET workspace_id = (SELECT min(id) FROM workspace);
+ALTER TABLE todo ALTER COLUMN workspace_id SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS todo_workspace_id_idx ON todo (workspace_id, id)
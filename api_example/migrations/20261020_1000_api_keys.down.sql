@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS api_key;
+DROP TYPE IF EXISTS api_key_scope;
+DROP TYPE IF EXISTS api_key_kind
\ No newline at end of file
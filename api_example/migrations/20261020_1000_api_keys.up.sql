@@ -0,0 +1,20 @@
+CREATE TYPE api_key_kind AS ENUM ('personal', 'service');
+CREATE TYPE api_key_scope AS ENUM ('todos:read', 'todos:write');
+
+CREATE TABLE IF NOT EXISTS api_key (
+    id BIGSERIAL PRIMARY KEY,
+    kind api_key_kind NOT NULL,
+    user_id BIGINT NOT NULL,
+    workspace_id BIGINT REFERENCES workspace (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    prefix TEXT NOT NULL,
+    secret_hash BYTEA NOT NULL UNIQUE,
+    scopes api_key_scope[] NOT NULL,
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK ((kind = 'service') = (workspace_id IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS api_key_user_id_idx ON api_key (user_id)
@@ -0,0 +1,2 @@
+DROP TRIGGER IF EXISTS todo_changed ON todo;
+DROP FUNCTION IF EXISTS notify_todo_changed()
\ No newline at end of file
@@ -0,0 +1,10 @@
+CREATE OR REPLACE FUNCTION notify_todo_changed() RETURNS trigger AS $$
+BEGIN
+    PERFORM pg_notify('todo_changed', COALESCE(NEW.workspace_id, OLD.workspace_id)::text);
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER todo_changed
+    AFTER INSERT OR UPDATE OR DELETE ON todo
+    FOR EACH ROW EXECUTE FUNCTION notify_todo_changed()
\ No newline at end of file
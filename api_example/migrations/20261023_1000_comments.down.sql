@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS todo_comment_count ON todo_comment;
+DROP FUNCTION IF EXISTS update_todo_comment_count();
+ALTER TABLE todo DROP COLUMN IF EXISTS comment_count;
+DROP TABLE IF EXISTS todo_comment
\ No newline at end of file
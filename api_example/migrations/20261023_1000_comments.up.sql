@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS todo_comment (
+    id BIGSERIAL PRIMARY KEY,
+    todo_id BIGINT NOT NULL REFERENCES todo (id) ON DELETE CASCADE,
+    author_id BIGINT NOT NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS todo_comment_todo_id_idx ON todo_comment (todo_id, id);
+
+ALTER TABLE todo ADD COLUMN comment_count BIGINT NOT NULL DEFAULT 0;
+
+-- счётчик меняет updated_at, чтобы ETag списка и кэш видели новые комментарии
+CREATE OR REPLACE FUNCTION update_todo_comment_count() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        UPDATE todo SET comment_count = comment_count + 1, updated_at = now() WHERE id = NEW.todo_id;
+    ELSE
+        UPDATE todo SET comment_count = comment_count - 1, updated_at = now() WHERE id = OLD.todo_id;
+    END IF;
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER todo_comment_count
+    AFTER INSERT OR DELETE ON todo_comment
+    FOR EACH ROW EXECUTE FUNCTION update_todo_comment_count();
\ No newline at end of file
@@ -0,0 +1,6 @@
+ALTER TABLE todo
+    DROP CONSTRAINT IF EXISTS todo_recurrence_due_at,
+    DROP COLUMN IF EXISTS next_todo_id,
+    DROP COLUMN IF EXISTS time_zone,
+    DROP COLUMN IF EXISTS recurrence,
+    DROP COLUMN IF EXISTS due_at
\ No newline at end of file
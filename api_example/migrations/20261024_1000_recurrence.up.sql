@@ -0,0 +1,6 @@
+ALTER TABLE todo
+    ADD COLUMN due_at TIMESTAMPTZ,
+    ADD COLUMN recurrence TEXT,
+    ADD COLUMN time_zone TEXT,
+    ADD COLUMN next_todo_id BIGINT REFERENCES todo (id) ON DELETE SET NULL,
+    ADD CONSTRAINT todo_recurrence_due_at CHECK (recurrence IS NULL OR due_at IS NOT NULL)
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE todo DROP CONSTRAINT IF EXISTS todo_workspace_position_key;
+ALTER TABLE todo DROP COLUMN IF EXISTS position
\ No newline at end of file
@@ -0,0 +1,12 @@
+ALTER TABLE todo ADD COLUMN position TEXT COLLATE "C";
+
+-- ранги существующих todo по порядку id; суффикс 'i', потому что ранг не должен кончаться на '0'
+UPDATE todo t
+SET position = lpad(ranked.n::text, 10, '0') || 'i'
+FROM (SELECT id, row_number() OVER (PARTITION BY workspace_id ORDER BY id) AS n FROM todo) ranked
+WHERE ranked.id = t.id;
+
+ALTER TABLE todo ALTER COLUMN position SET NOT NULL;
+
+-- проверяется при commit, чтобы пересчёт рангов мог переставлять их одним UPDATE
+ALTER TABLE todo ADD CONSTRAINT todo_workspace_position_key UNIQUE (workspace_id, position) DEFERRABLE INITIALLY DEFERRED
\ No newline at end of file
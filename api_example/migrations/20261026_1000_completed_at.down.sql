@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS todo_workspace_completed_at_idx;
+DROP INDEX IF EXISTS todo_workspace_created_at_idx;
+ALTER TABLE todo DROP CONSTRAINT IF EXISTS todo_completed_at_done;
+ALTER TABLE todo DROP COLUMN IF EXISTS completed_at
\ No newline at end of file
@@ -0,0 +1,9 @@
+ALTER TABLE todo ADD COLUMN completed_at TIMESTAMPTZ;
+
+-- точное время выполнения старых todo неизвестно, лучшее приближение - последнее изменение
+UPDATE todo SET completed_at = updated_at WHERE done;
+
+ALTER TABLE todo ADD CONSTRAINT todo_completed_at_done CHECK (done = (completed_at IS NOT NULL));
+
+CREATE INDEX IF NOT EXISTS todo_workspace_created_at_idx ON todo (workspace_id, created_at);
+CREATE INDEX IF NOT EXISTS todo_workspace_completed_at_idx ON todo (workspace_id, completed_at) WHERE completed_at IS NOT NULL
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE todo DROP CONSTRAINT IF EXISTS todo_workspace_ical_uid_key;
+ALTER TABLE todo DROP COLUMN IF EXISTS ical_uid
\ No newline at end of file
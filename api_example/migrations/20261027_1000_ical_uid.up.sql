@@ -0,0 +1,3 @@
+-- UID, с которым todo пришёл из CalDAV-клиента; у остальных UID выводится из id
+ALTER TABLE todo ADD COLUMN ical_uid TEXT;
+ALTER TABLE todo ADD CONSTRAINT todo_workspace_ical_uid_key UNIQUE (workspace_id, ical_uid)
\ No newline at end of file
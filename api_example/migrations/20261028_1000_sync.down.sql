@@ -0,0 +1,8 @@
+DROP TRIGGER IF EXISTS todo_tombstone ON todo;
+DROP FUNCTION IF EXISTS bury_todo();
+DROP TRIGGER IF EXISTS todo_track_change ON todo;
+DROP FUNCTION IF EXISTS track_todo_change();
+DROP TABLE IF EXISTS todo_tombstone;
+DROP INDEX IF EXISTS todo_workspace_change_xid_idx;
+ALTER TABLE todo DROP COLUMN IF EXISTS change_xid;
+ALTER TABLE todo DROP COLUMN IF EXISTS version
\ No newline at end of file
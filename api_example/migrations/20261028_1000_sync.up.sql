@@ -0,0 +1,39 @@
+ALTER TABLE todo ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
+
+-- id транзакции (xid8) растёт монотонно и не переполняется; токен синхронизации - xmin снимка,
+-- поэтому изменения транзакций, которые ещё не были видны, не теряются
+ALTER TABLE todo ADD COLUMN change_xid xid8 NOT NULL DEFAULT pg_current_xact_id();
+
+CREATE INDEX IF NOT EXISTS todo_workspace_change_xid_idx ON todo (workspace_id, change_xid);
+
+CREATE TABLE IF NOT EXISTS todo_tombstone (
+    todo_id BIGINT PRIMARY KEY,
+    workspace_id BIGINT NOT NULL,
+    change_xid xid8 NOT NULL DEFAULT pg_current_xact_id(),
+    deleted_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS todo_tombstone_workspace_change_xid_idx ON todo_tombstone (workspace_id, change_xid);
+
+CREATE OR REPLACE FUNCTION track_todo_change() RETURNS trigger AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    NEW.change_xid := pg_current_xact_id();
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER todo_track_change
+    BEFORE UPDATE ON todo
+    FOR EACH ROW EXECUTE FUNCTION track_todo_change();
+
+CREATE OR REPLACE FUNCTION bury_todo() RETURNS trigger AS $$
+BEGIN
+    INSERT INTO todo_tombstone (todo_id, workspace_id) VALUES (OLD.id, OLD.workspace_id);
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER todo_tombstone
+    AFTER DELETE ON todo
+    FOR EACH ROW EXECUTE FUNCTION bury_todo();
\ No newline at end of file
@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS todo_tags_idx;
+
+ALTER TABLE todo
+    DROP COLUMN IF EXISTS priority,
+    DROP COLUMN IF EXISTS tags;
+
+DROP TYPE IF EXISTS todo_priority
\ No newline at end of file
@@ -0,0 +1,7 @@
+CREATE TYPE todo_priority AS ENUM ('low', 'medium', 'high');
+
+ALTER TABLE todo
+    ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN priority todo_priority;
+
+CREATE INDEX IF NOT EXISTS todo_tags_idx ON todo USING gin (tags)
\ No newline at end of file
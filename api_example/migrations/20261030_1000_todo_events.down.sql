@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS todo_snapshot;
+DROP TABLE IF EXISTS todo_event
\ No newline at end of file
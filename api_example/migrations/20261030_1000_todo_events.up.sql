@@ -0,0 +1,23 @@
+-- история todo для режима TODO_STORE=events, таблица todo в нём - проекция событий
+CREATE TABLE IF NOT EXISTS todo_event (
+    id BIGSERIAL PRIMARY KEY,
+    workspace_id BIGINT NOT NULL REFERENCES workspace (id) ON DELETE CASCADE,
+    todo_id BIGINT NOT NULL,
+    -- номер события в истории todo, начиная с 1
+    seq BIGINT NOT NULL,
+    data JSONB NOT NULL,
+    kind TEXT GENERATED ALWAYS AS (data ->> 'type') STORED,
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (todo_id, seq)
+);
+
+CREATE INDEX IF NOT EXISTS todo_event_workspace_idx ON todo_event (workspace_id, todo_id);
+
+-- состояние todo после события seq: пересборка начинает с него, а не с начала истории
+CREATE TABLE IF NOT EXISTS todo_snapshot (
+    todo_id BIGINT PRIMARY KEY,
+    workspace_id BIGINT NOT NULL REFERENCES workspace (id) ON DELETE CASCADE,
+    seq BIGINT NOT NULL,
+    state JSONB NOT NULL,
+    taken_at TIMESTAMPTZ NOT NULL DEFAULT now()
+)
\ No newline at end of file
@@ -0,0 +1,18 @@
+CREATE OR REPLACE FUNCTION update_todo_comment_count() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        UPDATE todo SET comment_count = comment_count + 1, updated_at = now() WHERE id = NEW.todo_id;
+    ELSE
+        UPDATE todo SET comment_count = comment_count - 1, updated_at = now() WHERE id = OLD.todo_id;
+    END IF;
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION track_todo_change() RETURNS trigger AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    NEW.change_xid := pg_current_xact_id();
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
\ No newline at end of file
@@ -0,0 +1,25 @@
+-- счётчик комментариев не часть todo, которую синхронизируют клиенты: его изменение не двигает
+-- updated_at, version и change_xid, иначе push после чужого комментария считался конфликтом.
+-- ETag списка видит комментарии через sum(comment_count)
+CREATE OR REPLACE FUNCTION update_todo_comment_count() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        UPDATE todo SET comment_count = comment_count + 1 WHERE id = NEW.todo_id;
+    ELSE
+        UPDATE todo SET comment_count = comment_count - 1 WHERE id = OLD.todo_id;
+    END IF;
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION track_todo_change() RETURNS trigger AS $$
+BEGIN
+    IF NEW.comment_count IS DISTINCT FROM OLD.comment_count
+        AND to_jsonb(NEW) - 'comment_count' = to_jsonb(OLD) - 'comment_count' THEN
+        RETURN NEW;
+    END IF;
+    NEW.version := OLD.version + 1;
+    NEW.change_xid := pg_current_xact_id();
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
\ No newline at end of file
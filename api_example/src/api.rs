@@ -1,2 +1,9 @@
-pub(crate) mod router;
-pub(crate) mod handlers;
\ No newline at end of file
+pub mod router;
+pub(crate) mod handlers;
+pub(crate) mod auth;
+pub(crate) mod dav;
+pub(crate) mod extract;
+pub mod limits;
+pub(crate) mod locale;
+pub(crate) mod replica;
+pub mod state;
\ No newline at end of file
@@ -0,0 +1,117 @@
+use axum::extract::{FromRequestParts, Request, State};
+use axum::http::request::Parts;
+use axum::http::{header, HeaderMap};
+use axum::middleware::Next;
+use axum::response::Response;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use sqlx::PgPool;
+use crate::dto::api_key::Scope;
+use crate::error::Error;
+use crate::repo;
+
+pub const USER_ID_HEADER: &str = "x-user-id";
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Кто выполняет запрос: пользователь за аутентифицирующим прокси (`X-User-Id`)
+/// или владелец API-ключа (`Authorization: Bearer` / `X-API-Key`, либо пароль в `Authorization: Basic`
+/// для календарных клиентов, которые умеют только его).
+#[derive(Clone, Debug)]
+pub struct Principal {
+    pub user_id: i64,
+    /// Сервисный ключ работает только в своём workspace
+    pub workspace_id: Option<i64>,
+    pub scopes: Vec<Scope>,
+    pub api_key_id: Option<i64>,
+}
+
+impl Principal {
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    pub fn is_api_key(&self) -> bool {
+        self.api_key_id.is_some()
+    }
+}
+
+impl<S> FromRequestParts<S> for Principal
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts.extensions.get::<Principal>().cloned().ok_or(Error::Unauthorized)
+    }
+}
+
+/// Определяет `Principal` и кладёт его в extensions запроса.
+/// Запрос без учётных данных пропускается дальше: 401 вернёт тот, кому `Principal` нужен.
+pub async fn authenticate(
+    State(dbpool): State<PgPool>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, Error> {
+    let principal = match api_token(request.headers()) {
+        Some(token) => {
+            let grant = repo::api_key::authenticate(&dbpool, &token)
+                .await?
+                .ok_or(Error::Unauthorized)?;
+            Some(Principal {
+                user_id: grant.user_id,
+                workspace_id: grant.workspace_id,
+                scopes: grant.scopes,
+                api_key_id: Some(grant.id),
+            })
+        }
+        None => header_i64(request.headers(), USER_ID_HEADER).map(|user_id| Principal {
+            user_id,
+            workspace_id: None,
+            scopes: Scope::ALL.to_vec(),
+            api_key_id: None,
+        }),
+    };
+
+    if let Some(principal) = principal {
+        request.extensions_mut().insert(principal);
+    }
+    Ok(next.run(request).await)
+}
+
+/// Middleware для `route_layer`: `from_fn_with_state(Scope::TodosRead, require_scope)`.
+pub async fn require_scope(
+    State(scope): State<Scope>,
+    request: Request,
+    next: Next,
+) -> Result<Response, Error> {
+    let principal = request.extensions().get::<Principal>().ok_or(Error::Unauthorized)?;
+    if !principal.has_scope(scope) {
+        return Err(Error::Forbidden);
+    }
+    Ok(next.run(request).await)
+}
+
+fn api_token(headers: &HeaderMap) -> Option<String> {
+    let authorization = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_once(' '))
+        .and_then(|(scheme, credentials)| {
+            if scheme.eq_ignore_ascii_case("bearer") {
+                return Some(credentials.trim().to_string());
+            }
+            if !scheme.eq_ignore_ascii_case("basic") {
+                return None;
+            }
+            // имя пользователя не важно, ключ сам знает своего владельца
+            let decoded = String::from_utf8(STANDARD.decode(credentials.trim()).ok()?).ok()?;
+            decoded.split_once(':').map(|(_, password)| password.to_string())
+        });
+
+    authorization.or_else(|| headers.get(API_KEY_HEADER)?.to_str().ok().map(|token| token.trim().to_string()))
+}
+
+pub(crate) fn header_i64(headers: &HeaderMap, name: &str) -> Option<i64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
\ No newline at end of file
@@ -0,0 +1,523 @@
+//! Минимальный CalDAV (RFC 4791): каждый workspace - календарь с задачами VTODO.
+//!
+//! - `/dav/` - principal текущего пользователя, `calendar-home-set` указывает на `/dav/workspaces/`
+//! - `/dav/workspaces/` - календари пользователя
+//! - `/dav/workspaces/{workspace_id}/` - PROPFIND и REPORT `calendar-query` / `calendar-multiget`
+//! - `/dav/workspaces/{workspace_id}/{name}.ics` - GET, PUT и DELETE одного todo
+//!
+//! Из фильтров `calendar-query` учитывается только тип компонента. Календарные клиенты
+//! передают API-ключ паролем в `Authorization: Basic`.
+
+use axum::body::Bytes;
+use axum::extract::{Path, Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::Deserialize;
+use crate::api::auth::Principal;
+use crate::api::extract::WorkspaceAccess;
+use crate::api::state::AppState;
+use crate::api::handlers::delete_blobs;
+use crate::dto::api_key::Scope;
+use crate::dto::todo::{CreateTodo, Todo, TodoPatch, UpdateTodo};
+use crate::dto::workspace::{Role, Workspace};
+use crate::error::Error;
+use crate::i18n::{Key, Message};
+use crate::ical::{self, VTodo};
+use crate::repo;
+use crate::repo::pg::Db;
+
+pub const ROOT: &str = "/dav/";
+const HOME: &str = "/dav/workspaces/";
+const DAV: &str = "DAV:";
+const CALDAV: &str = "urn:ietf:params:xml:ns:caldav";
+const CALENDARSERVER: &str = "http://calendarserver.org/ns/";
+const ALLOW: &str = "OPTIONS, GET, HEAD, PUT, DELETE, PROPFIND, REPORT";
+const TODO_CONTENT_TYPE: &str = "text/calendar; charset=utf-8; component=vtodo";
+/// UID из клиента попадает в путь и может содержать что угодно
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~').remove(b'@');
+
+/// Свойства для `allprop` и PROPFIND без тела.
+const ALLPROP: [(&str, &str); 6] = [
+    (DAV, "resourcetype"),
+    (DAV, "displayname"),
+    (DAV, "getetag"),
+    (DAV, "getcontenttype"),
+    (DAV, "getlastmodified"),
+    (CALENDARSERVER, "getctag"),
+];
+
+#[derive(Deserialize)]
+pub struct ResourcePath {
+    resource: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct PropName {
+    namespace: String,
+    name: String,
+}
+
+impl PropName {
+    fn new(namespace: &str, name: &str) -> Self {
+        PropName { namespace: namespace.to_string(), name: name.to_string() }
+    }
+
+    fn element(&self, inner: &str) -> String {
+        let (tag, declaration) = match self.namespace.as_str() {
+            DAV => (format!("d:{}", self.name), String::new()),
+            CALDAV => (format!("c:{}", self.name), String::new()),
+            CALENDARSERVER => (format!("cs:{}", self.name), String::new()),
+            "" => (self.name.clone(), " xmlns=\"\"".to_string()),
+            namespace => (format!("x:{}", self.name), format!(" xmlns:x=\"{}\"", escape(namespace))),
+        };
+        if inner.is_empty() {
+            format!("<{tag}{declaration}/>")
+        } else {
+            format!("<{tag}{declaration}>{inner}</{tag}>")
+        }
+    }
+}
+
+enum Props {
+    All,
+    Names(Vec<PropName>),
+}
+
+enum Resource<'a> {
+    Principal,
+    Home,
+    Calendar { workspace: &'a Workspace, ctag: String },
+    Todo { workspace_id: i64, role: Role, todo: &'a Todo },
+}
+
+impl<'a> Resource<'a> {
+    fn todo(access: &WorkspaceAccess, todo: &'a Todo) -> Self {
+        Resource::Todo { workspace_id: access.workspace_id, role: access.role, todo }
+    }
+
+    fn href(&self) -> String {
+        match self {
+            Resource::Principal => ROOT.to_string(),
+            Resource::Home => HOME.to_string(),
+            Resource::Calendar { workspace, .. } => calendar_href(workspace.id()),
+            Resource::Todo { workspace_id, todo, .. } => format!("{}{}", calendar_href(*workspace_id), resource_name(todo)),
+        }
+    }
+
+    /// Содержимое свойства в XML, `None` - у ресурса такого свойства нет.
+    fn value(&self, prop: &PropName) -> Option<String> {
+        let value = match (prop.namespace.as_str(), prop.name.as_str(), self) {
+            (DAV, "resourcetype", Resource::Principal) => "<d:collection/><d:principal/>".to_string(),
+            (DAV, "resourcetype", Resource::Home) => "<d:collection/>".to_string(),
+            (DAV, "resourcetype", Resource::Calendar { .. }) => "<d:collection/><c:calendar/>".to_string(),
+            (DAV, "resourcetype", Resource::Todo { .. }) => String::new(),
+            (DAV, "displayname", Resource::Home) => "Workspaces".to_string(),
+            (DAV, "displayname", Resource::Calendar { workspace, .. }) => escape(workspace.name()),
+            (DAV, "current-user-principal" | "principal-URL", _) => format!("<d:href>{ROOT}</d:href>"),
+            (CALDAV, "calendar-home-set", Resource::Principal) => format!("<d:href>{HOME}</d:href>"),
+            (CALDAV, "supported-calendar-component-set", Resource::Calendar { .. }) => "<c:comp name=\"VTODO\"/>".to_string(),
+            (DAV, "supported-report-set", Resource::Calendar { .. }) => ["calendar-query", "calendar-multiget"]
+                .map(|report| format!("<d:supported-report><d:report><c:{report}/></d:report></d:supported-report>"))
+                .concat(),
+            (DAV, "getetag", Resource::Calendar { ctag, .. }) | (CALENDARSERVER, "getctag", Resource::Calendar { ctag, .. }) => escape(ctag),
+            (DAV, "getetag", Resource::Todo { todo, .. }) => escape(&etag(todo)),
+            (DAV, "getcontenttype", Resource::Todo { .. }) => TODO_CONTENT_TYPE.to_string(),
+            (DAV, "getlastmodified", Resource::Todo { todo, .. }) => todo.updated_at().format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+            (DAV, "current-user-privilege-set", resource) => {
+                let role = match resource {
+                    Resource::Calendar { workspace, .. } => workspace.role(),
+                    Resource::Todo { role, .. } => *role,
+                    _ => Role::Viewer,
+                };
+                let mut privileges = vec!["read"];
+                if role >= Role::Editor {
+                    privileges.extend(["write", "write-content", "bind", "unbind"]);
+                }
+                privileges.iter().map(|privilege| format!("<d:privilege><d:{privilege}/></d:privilege>")).collect()
+            }
+            (CALDAV, "calendar-data", Resource::Todo { todo, .. }) => escape(&ical::calendar(None, &[VTodo::from(*todo)])),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+/// Ответ 207 Multi-Status.
+struct Multistatus(String);
+
+impl Multistatus {
+    fn new() -> Self {
+        Multistatus(format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<d:multistatus xmlns:d=\"{DAV}\" xmlns:c=\"{CALDAV}\" xmlns:cs=\"{CALENDARSERVER}\">"
+        ))
+    }
+
+    fn add(&mut self, resource: &Resource, props: &Props) {
+        let requested = match props {
+            Props::All => ALLPROP.iter().map(|(namespace, name)| PropName::new(namespace, name)).collect(),
+            Props::Names(names) => names.clone(),
+        };
+        let mut found = String::new();
+        let mut missing = String::new();
+        for prop in &requested {
+            match resource.value(prop) {
+                Some(value) => found.push_str(&prop.element(&value)),
+                // в allprop отсутствующие свойства просто не перечисляются
+                None if matches!(props, Props::Names(_)) => missing.push_str(&prop.element("")),
+                None => {}
+            }
+        }
+
+        self.0.push_str(&format!("<d:response><d:href>{}</d:href>", escape(&resource.href())));
+        for (props, status) in [(found, "200 OK"), (missing, "404 Not Found")] {
+            if !props.is_empty() {
+                self.0.push_str(&format!("<d:propstat><d:prop>{props}</d:prop><d:status>HTTP/1.1 {status}</d:status></d:propstat>"));
+            }
+        }
+        self.0.push_str("</d:response>");
+    }
+
+    fn add_missing(&mut self, href: &str) {
+        self.0.push_str(&format!("<d:response><d:href>{}</d:href><d:status>HTTP/1.1 404 Not Found</d:status></d:response>", escape(href)));
+    }
+}
+
+impl IntoResponse for Multistatus {
+    fn into_response(mut self) -> Response {
+        self.0.push_str("</d:multistatus>");
+        (
+            StatusCode::MULTI_STATUS,
+            [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+            self.0,
+        )
+            .into_response()
+    }
+}
+
+/// Календарные клиенты спрашивают пароль только после 401 с `WWW-Authenticate`.
+pub async fn challenge(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    if response.status() == StatusCode::UNAUTHORIZED {
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Basic realm=\"todo\", charset=\"UTF-8\""));
+    }
+    response
+}
+
+/// OPTIONS отвечает без аутентификации: по нему клиент узнаёт, что здесь CalDAV.
+pub async fn options() -> impl IntoResponse {
+    [(header::ALLOW, ALLOW), (header::HeaderName::from_static("dav"), "1, 3, calendar-access")]
+}
+
+pub async fn principal(method: Method, principal: Principal, body: Bytes) -> Result<Response, Error> {
+    if method.as_str() != "PROPFIND" {
+        return Ok(method_not_allowed());
+    }
+    require_scope(&principal, Scope::TodosRead)?;
+    let mut multistatus = Multistatus::new();
+    multistatus.add(&Resource::Principal, &propfind(&body)?);
+    Ok(multistatus.into_response())
+}
+
+pub async fn home(
+    State(db): State<Db>,
+    method: Method,
+    principal: Principal,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, Error> {
+    if method.as_str() != "PROPFIND" {
+        return Ok(method_not_allowed());
+    }
+    require_scope(&principal, Scope::TodosRead)?;
+    let props = propfind(&body)?;
+    let mut multistatus = Multistatus::new();
+    multistatus.add(&Resource::Home, &props);
+    if depth(&headers) > 0 {
+        let workspaces = repo::workspace::list_for_user(db.primary(), principal.user_id).await?;
+        // сервисный ключ видит только свой workspace
+        for workspace in workspaces.iter().filter(|workspace| principal.workspace_id.is_none_or(|id| id == workspace.id())) {
+            let ctag = repo::todo::list_version(&db, workspace.id()).await?.etag(workspace.id());
+            multistatus.add(&Resource::Calendar { workspace, ctag }, &props);
+        }
+    }
+    Ok(multistatus.into_response())
+}
+
+pub async fn calendar(
+    State(db): State<Db>,
+    method: Method,
+    principal: Principal,
+    access: WorkspaceAccess,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, Error> {
+    require_scope(&principal, Scope::TodosRead)?;
+    let workspace = repo::workspace::list_for_user(db.primary(), access.user_id)
+        .await?
+        .into_iter()
+        .find(|workspace| workspace.id() == access.workspace_id)
+        .ok_or(Error::NotFound)?;
+
+    let mut multistatus = Multistatus::new();
+    match method.as_str() {
+        "PROPFIND" => {
+            let props = propfind(&body)?;
+            let ctag = repo::todo::list_version(&db, access.workspace_id).await?.etag(access.workspace_id);
+            multistatus.add(&Resource::Calendar { workspace: &workspace, ctag }, &props);
+            if depth(&headers) > 0 {
+                for todo in &repo::todo::list(&db, access.workspace_id).await? {
+                    multistatus.add(&Resource::todo(&access, todo), &props);
+                }
+            }
+        }
+        "REPORT" => {
+            let text = utf8(&body)?;
+            let document = parse_xml(text)?;
+            let report = document.root_element();
+            let props = requested_props(report);
+            match (report.tag_name().namespace(), report.tag_name().name()) {
+                (Some(CALDAV), "calendar-multiget") => {
+                    let hrefs = report.children().filter(|node| is_element(node, DAV, "href")).filter_map(|node| node.text());
+                    for href in hrefs {
+                        let name = href.trim().rsplit('/').next().unwrap_or_default();
+                        let name = percent_decode_str(name).decode_utf8_lossy();
+                        match find(&db, access.workspace_id, &name).await? {
+                            Some(todo) => multistatus.add(&Resource::todo(&access, &todo), &props),
+                            None => multistatus.add_missing(href.trim()),
+                        }
+                    }
+                }
+                (Some(CALDAV), "calendar-query") => {
+                    // фильтр по другим компонентам (VEVENT) в календаре задач ничего не находит
+                    let components: Vec<_> = report
+                        .descendants()
+                        .filter(|node| is_element(node, CALDAV, "comp-filter"))
+                        .filter_map(|node| node.attribute("name"))
+                        .filter(|name| !name.eq_ignore_ascii_case("VCALENDAR"))
+                        .collect();
+                    if components.is_empty() || components.iter().any(|name| name.eq_ignore_ascii_case("VTODO")) {
+                        for todo in &repo::todo::list(&db, access.workspace_id).await? {
+                            multistatus.add(&Resource::todo(&access, todo), &props);
+                        }
+                    }
+                }
+                (_, name) => {
+                    let message = Message::new(Key::ReportUnsupported).arg("name", name);
+                    return Err(Error::Validation(StatusCode::FORBIDDEN, message));
+                }
+            }
+        }
+        _ => return Ok(method_not_allowed()),
+    }
+    Ok(multistatus.into_response())
+}
+
+pub async fn resource(
+    State(AppState { db, blobs, .. }): State<AppState>,
+    method: Method,
+    principal: Principal,
+    access: WorkspaceAccess,
+    Path(ResourcePath { resource }): Path<ResourcePath>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, Error> {
+    let current = find(&db, access.workspace_id, &resource).await?;
+    match method {
+        Method::GET | Method::HEAD => {
+            require_scope(&principal, Scope::TodosRead)?;
+            let todo = current.ok_or(Error::NotFound)?;
+            Ok((
+                [(header::CONTENT_TYPE, ical::CONTENT_TYPE.to_string()), (header::ETAG, etag(&todo))],
+                ical::calendar(None, &[VTodo::from(&todo)]),
+            )
+                .into_response())
+        }
+        Method::PUT => {
+            require_scope(&principal, Scope::TodosWrite)?;
+            access.require(Role::Editor)?;
+            let vtodo = single_vtodo(utf8(&body)?)?;
+            if !preconditions_hold(&headers, current.as_ref()) {
+                return Ok(StatusCode::PRECONDITION_FAILED.into_response());
+            }
+
+            let (status, todo) = match current {
+                Some(current) => {
+                    let update_todo = UpdateTodo {
+                        body: vtodo.summary.clone(),
+                        done: Some(vtodo.done()),
+                        due_at: Some(vtodo.due),
+                        recurrence: None,
+                        time_zone: None,
+                        tags: None,
+                        priority: None,
+                    };
+                    (StatusCode::NO_CONTENT, repo::todo::update(&db, access.workspace_id, current.id(), TodoPatch::Update(update_todo)).await?)
+                }
+                None => {
+                    // числовые имена заняты todo, созданными через API
+                    if resource != format!("{}.ics", vtodo.uid) || vtodo.uid.parse::<i64>().is_ok() {
+                        return Err(Error::Validation(
+                            StatusCode::UNPROCESSABLE_ENTITY,
+                            Key::ResourceNameInvalid.into(),
+                        ));
+                    }
+                    let new_todo = CreateTodo {
+                        body: vtodo.summary.clone().unwrap_or_default(),
+                        due_at: vtodo.due,
+                        recurrence: None,
+                        time_zone: None,
+                        tags: Vec::new(),
+                        priority: None,
+                        completed_at: vtodo.done().then(|| vtodo.completed.unwrap_or_else(Utc::now)),
+                        ical_uid: Some(vtodo.uid),
+                    };
+                    (StatusCode::CREATED, repo::todo::create(&db, access.workspace_id, new_todo).await?)
+                }
+            };
+            Ok((status, [(header::ETAG, etag(&todo))]).into_response())
+        }
+        Method::DELETE => {
+            require_scope(&principal, Scope::TodosWrite)?;
+            access.require(Role::Editor)?;
+            let todo = current.ok_or(Error::NotFound)?;
+            if !preconditions_hold(&headers, Some(&todo)) {
+                return Ok(StatusCode::PRECONDITION_FAILED.into_response());
+            }
+            let storage_keys = repo::todo::delete(&db, access.workspace_id, todo.id()).await?;
+            delete_blobs(blobs.as_ref(), &storage_keys).await;
+            Ok(StatusCode::NO_CONTENT.into_response())
+        }
+        _ => Ok(method_not_allowed()),
+    }
+}
+
+fn calendar_href(workspace_id: i64) -> String {
+    format!("{HOME}{workspace_id}/")
+}
+
+/// Todo из CalDAV лежат под своим UID, остальные - под id.
+fn resource_name(todo: &Todo) -> String {
+    match todo.ical_uid() {
+        Some(uid) => format!("{}.ics", utf8_percent_encode(uid, SEGMENT)),
+        None => format!("{}.ics", todo.id()),
+    }
+}
+
+async fn find(db: &Db, workspace_id: i64, resource: &str) -> Result<Option<Todo>, Error> {
+    let Some(name) = resource.strip_suffix(".ics") else {
+        return Ok(None);
+    };
+    if let Some(todo) = repo::todo::read_by_ical_uid(db, workspace_id, name).await? {
+        return Ok(Some(todo));
+    }
+    let Ok(id) = name.parse() else {
+        return Ok(None);
+    };
+    match repo::todo::read(db, workspace_id, id).await {
+        Ok(todo) => Ok(Some(todo)),
+        Err(Error::NotFound) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Сильный ETag одного todo, меняется с каждым `updated_at`.
+fn etag(todo: &Todo) -> String {
+    format!("\"{}-{}\"", todo.id(), todo.updated_at().timestamp_micros())
+}
+
+/// `If-Match` и `If-None-Match: *`, которыми клиенты защищаются от потерянных обновлений.
+fn preconditions_hold(headers: &HeaderMap, current: Option<&Todo>) -> bool {
+    let current_etag = current.map(etag);
+    let listed = |name| {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|candidate| candidate.trim().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let if_match = listed(header::IF_MATCH);
+    if !if_match.is_empty() && !if_match.iter().any(|candidate| current_etag.as_ref().is_some_and(|etag| candidate == "*" || candidate == etag)) {
+        return false;
+    }
+    let if_none_match = listed(header::IF_NONE_MATCH);
+    !if_none_match.iter().any(|candidate| (candidate == "*" && current.is_some()) || current_etag.as_ref() == Some(candidate))
+}
+
+fn single_vtodo(text: &str) -> Result<VTodo, Error> {
+    let mut todos = ical::parse(text).map_err(|message| {
+        Error::Validation(StatusCode::UNPROCESSABLE_ENTITY, Message::new(Key::InvalidCalendar).arg("details", message))
+    })?;
+    // исключения из повторений (RECURRENCE-ID) приходят с тем же UID, берём основной компонент
+    if todos.is_empty() || todos.iter().any(|todo| todo.uid != todos[0].uid) {
+        return Err(Error::Validation(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Key::CalendarUidMismatch.into(),
+        ));
+    }
+    Ok(todos.swap_remove(0))
+}
+
+fn require_scope(principal: &Principal, scope: Scope) -> Result<(), Error> {
+    if !principal.has_scope(scope) {
+        return Err(Error::Forbidden);
+    }
+    Ok(())
+}
+
+/// `Depth: 0` или 1; `infinity` обрабатывается как 1.
+fn depth(headers: &HeaderMap) -> u8 {
+    match headers.get("depth").and_then(|value| value.to_str().ok()) {
+        Some(depth) if depth.trim() == "0" => 0,
+        _ => 1,
+    }
+}
+
+fn propfind(body: &Bytes) -> Result<Props, Error> {
+    let text = utf8(body)?;
+    if text.trim().is_empty() {
+        return Ok(Props::All);
+    }
+    Ok(requested_props(parse_xml(text)?.root_element()))
+}
+
+/// Свойства из `<d:prop>`; `allprop` и `propname` дают набор по умолчанию.
+fn requested_props(request: roxmltree::Node) -> Props {
+    match request.children().find(|node| is_element(node, DAV, "prop")) {
+        Some(prop) => Props::Names(
+            prop.children()
+                .filter(|node| node.is_element())
+                .map(|node| PropName::new(node.tag_name().namespace().unwrap_or_default(), node.tag_name().name()))
+                .collect(),
+        ),
+        None => Props::All,
+    }
+}
+
+fn is_element(node: &roxmltree::Node, namespace: &str, name: &str) -> bool {
+    node.is_element() && node.tag_name().namespace() == Some(namespace) && node.tag_name().name() == name
+}
+
+fn parse_xml(text: &str) -> Result<roxmltree::Document<'_>, Error> {
+    roxmltree::Document::parse(text)
+        .map_err(|e| Error::Validation(StatusCode::BAD_REQUEST, Message::new(Key::InvalidXml).arg("details", e)))
+}
+
+fn utf8(body: &Bytes) -> Result<&str, Error> {
+    std::str::from_utf8(body).map_err(|_| Error::Validation(StatusCode::BAD_REQUEST, Key::BodyNotUtf8.into()))
+}
+
+fn method_not_allowed() -> Response {
+    (StatusCode::METHOD_NOT_ALLOWED, [(header::ALLOW, ALLOW)]).into_response()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
\ No newline at end of file
@@ -0,0 +1,89 @@
+use axum::extract::{FromRef, FromRequestParts, RawPathParams};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use sqlx::PgPool;
+use crate::api::auth::{header_i64, Principal};
+use crate::dto::workspace::Role;
+use crate::error::Error;
+use crate::i18n::Key;
+use crate::repo;
+
+pub const WORKSPACE_ID_HEADER: &str = "x-workspace-id";
+
+/// Id текущего пользователя, см. `auth::Principal`.
+pub struct CurrentUser(pub i64);
+
+impl<S> FromRequestParts<S> for CurrentUser
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Principal::from_request_parts(parts, state)
+            .await
+            .map(|principal| CurrentUser(principal.user_id))
+    }
+}
+
+/// Workspace из пути (`/workspaces/{workspace_id}/...`) или заголовка `X-Workspace-Id`
+/// вместе с ролью текущего пользователя в нём.
+pub struct WorkspaceAccess {
+    pub workspace_id: i64,
+    pub user_id: i64,
+    pub role: Role,
+}
+
+impl WorkspaceAccess {
+    pub fn require(&self, role: Role) -> Result<(), Error> {
+        if self.role < role {
+            return Err(Error::Forbidden);
+        }
+        Ok(())
+    }
+}
+
+impl<S> FromRequestParts<S> for WorkspaceAccess
+where
+    PgPool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let principal = Principal::from_request_parts(parts, state).await?;
+
+        let from_path = RawPathParams::from_request_parts(parts, state)
+            .await
+            .ok()
+            .and_then(|params| {
+                params
+                    .iter()
+                    .find(|(key, _)| *key == "workspace_id")
+                    .map(|(_, value)| value.parse::<i64>().ok())
+            });
+
+        let workspace_id = match from_path {
+            Some(id) => id,
+            None => header_i64(&parts.headers, WORKSPACE_ID_HEADER),
+        }
+        .ok_or_else(|| {
+            Error::Validation(
+                StatusCode::BAD_REQUEST,
+                Key::WorkspaceMissing.into(),
+            )
+        })?;
+
+        if principal.workspace_id.is_some_and(|allowed| allowed != workspace_id) {
+            return Err(Error::NotFound);
+        }
+
+        let dbpool = PgPool::from_ref(state);
+        // для не-участников workspace как будто не существует
+        let role = repo::workspace::role_of(&dbpool, workspace_id, principal.user_id)
+            .await?
+            .ok_or(Error::NotFound)?;
+
+        Ok(WorkspaceAccess { workspace_id, user_id: principal.user_id, role })
+    }
+}
\ No newline at end of file
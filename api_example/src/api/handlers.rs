@@ -1,94 +1,985 @@
-use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use axum::body::{Body, Bytes};
+use axum::extract::{Multipart, Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
-use crate::dto::todo::{CreateTodo, Todo, UpdateTodo};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use serde::Deserialize;
+use std::sync::Arc;
+use crate::api::auth::Principal;
+use crate::api::extract::{CurrentUser, WorkspaceAccess};
+use crate::api::state::AppState;
+use crate::blob::{self, BlobStore};
+use crate::dto::api_key::{ApiKey, CreateApiKey, CreatedApiKey};
+use crate::dto::attachment::{Attachment, AttachmentUpload, NewAttachment};
+use crate::dto::comment::{Comment, CommentPage, CommentQuery, CreateComment, UpdateComment};
+use crate::dto::fields::FieldsQuery;
+use crate::dto::health::{Check, Failure, Health, HealthReport};
+use crate::dto::stats::{Stats, StatsQuery};
+use crate::dto::sync::{PushChanges, PushResult, SyncChanges, SyncQuery};
+use crate::dto::todo::{CreateTodo, MoveTodo, Occurrences, OccurrencesQuery, QuickAdd, QuickAdded, Todo, TodoPatch, UpdateTodo};
+use crate::dto::workspace::{CreateWorkspace, Member, Role, SetMember, Workspace};
 use crate::error::Error;
+use crate::i18n::{Key, Message};
+use crate::ical::{self, VTodo};
 use crate::repo;
+use crate::repo::pg::Db;
+
+#[derive(Deserialize)]
+pub struct TodoPath {
+    id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct CommentPath {
+    id: i64,
+    comment_id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct AttachmentPath {
+    id: i64,
+    attachment_id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct ApiKeyPath {
+    id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct MemberPath {
+    user_id: i64,
+}
+
+/// Слабое сравнение ETag из `If-None-Match` (RFC 9110, 13.1.2).
+pub(crate) fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == opaque(etag))
+}
 
 pub async fn ping(State(dbpool): State<sqlx::PgPool>) -> Result<String, Error> {
     repo::system::ping(&dbpool).await
 }
 
+/// Отчёт по зависимостям для мониторинга и людей. Оркестратору нужны `/health` (процесс жив,
+/// зависимости не трогает) и `/ready`; здесь 503 только когда сервис работать не может.
+pub async fn health_details(State(state): State<AppState>) -> Response {
+    let thresholds = &state.health;
+    let primary = state.db.primary();
+    let latency = repo::system::round_trip(primary, thresholds.unhealthy).await;
+    let mut checks = vec![Check::database("database", thresholds, primary, latency)];
+    if let Some(replica) = state.db.replica() {
+        let latency = repo::system::round_trip(replica, thresholds.unhealthy).await;
+        checks.push(Check::database("replica", thresholds, replica, latency).optional());
+    }
+    let statuses = match tokio::time::timeout(thresholds.unhealthy, repo::migrations::status(primary)).await {
+        Ok(statuses) => statuses.map_err(|err| Failure::Error(err.to_string())),
+        Err(_) => Err(Failure::Timeout(thresholds.unhealthy)),
+    };
+    checks.push(Check::migrations(statuses));
+
+    let report = HealthReport::new(checks);
+    let status = match report.status() {
+        Health::Unhealthy => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::OK,
+    };
+    (status, Json(report)).into_response()
+}
+
+/// Счётчики в текстовом формате Prometheus.
+pub async fn metrics(State(db): State<Db>) -> impl IntoResponse {
+    let cache = db.cache().stats();
+    let body = format!(
+        "# TYPE todo_cache_hits_total counter\n\
+         todo_cache_hits_total {}\n\
+         # TYPE todo_cache_misses_total counter\n\
+         todo_cache_misses_total {}\n\
+         # TYPE todo_cache_entries gauge\n\
+         todo_cache_entries {}\n\
+         # TYPE todo_cache_capacity gauge\n\
+         todo_cache_capacity {}\n",
+        cache.hits, cache.misses, cache.entries, cache.capacity,
+    );
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
+}
+
 #[utoipa::path(
     get,
     path = "/v1/todos",
+    params(
+        ("X-Workspace-Id" = i64, Header, description = "Workspace id"),
+        FieldsQuery
+    ),
+    responses(
+        (status = 200, description = "List todos", body = [Todo],
+            headers(("ETag" = String, description = "Weak validator for If-None-Match, not sent with 'include'"))),
+        (status = 304, description = "List has not changed since the given ETag"),
+        (status = 400, description = "Unknown field or include")
+    )
+)]
+pub async fn todo_list(
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    Query(query): Query<FieldsQuery>,
+    headers: HeaderMap,
+) -> Result<Response, Error> {
+    let projection = query.projection()?;
+    // изменения комментариев и вложений не видны в версии списка
+    if let Some(projection) = projection.as_ref().filter(|projection| !projection.include().is_empty()) {
+        let todos = repo::todo::list_projected(&db, access.workspace_id, projection).await?;
+        return Ok(Json(todos).into_response());
+    }
+
+    let etag = repo::todo::list_version(&db, access.workspace_id)
+        .await?
+        .etag(access.workspace_id);
+    let etag = match &projection {
+        Some(projection) => projection.etag(&etag),
+        None => etag,
+    };
+
+    if etag_matches(&headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
+    match projection {
+        Some(projection) => {
+            let todos = repo::todo::list_projected(&db, access.workspace_id, &projection).await?;
+            Ok(([(header::ETAG, etag)], Json(todos)).into_response())
+        }
+        None => {
+            let todos = repo::todo::list(&db, access.workspace_id).await?;
+            Ok(([(header::ETAG, etag)], Json(todos)).into_response())
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/todos.ics",
+    params(
+        ("X-Workspace-Id" = i64, Header)
+    ),
+    responses(
+        (status = 200, description = "Todos of the workspace as iCalendar VTODO components", body = String, content_type = "text/calendar",
+            headers(("ETag" = String, description = "Weak validator for If-None-Match"))),
+        (status = 304, description = "Feed has not changed since the given ETag")
+    )
+)]
+pub async fn todo_feed(
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    headers: HeaderMap,
+) -> Result<Response, Error> {
+    let etag = repo::todo::list_version(&db, access.workspace_id)
+        .await?
+        .etag(access.workspace_id);
+
+    if etag_matches(&headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
+    let workspaces = repo::workspace::list_for_user(db.primary(), access.user_id).await?;
+    let name = workspaces.iter().find(|workspace| workspace.id() == access.workspace_id).map(|workspace| workspace.name());
+    let todos: Vec<VTodo> = repo::todo::list(&db, access.workspace_id).await?.iter().map(VTodo::from).collect();
+    Ok((
+        [(header::CONTENT_TYPE, ical::CONTENT_TYPE.to_string()), (header::ETAG, etag)],
+        ical::calendar(name, &todos),
+    )
+        .into_response())
+}
+
+/// Все todo пользователя из всех его workspace, для подписки в календаре.
+#[utoipa::path(
+    get,
+    path = "/v1/calendar.ics",
     responses(
-        (status = 200, description = "List todos", body = [Todo])
+        (status = 200, description = "Todos of every workspace of the user as iCalendar VTODO components", body = String, content_type = "text/calendar")
     )
 )]
-pub async fn todo_list(State(dbpool): State<sqlx::PgPool>) -> Result<Json<Vec<Todo>>, Error> {
-    repo::todo::list(&dbpool).await.map(Json::from)
+pub async fn calendar_feed(
+    State(db): State<Db>,
+    principal: Principal,
+) -> Result<Response, Error> {
+    let workspaces = repo::workspace::list_for_user(db.primary(), principal.user_id).await?;
+    let mut todos = Vec::new();
+    // сервисный ключ видит только свой workspace
+    for workspace in workspaces.iter().filter(|workspace| principal.workspace_id.is_none_or(|id| id == workspace.id())) {
+        todos.extend(repo::todo::list(&db, workspace.id()).await?.iter().map(VTodo::from));
+    }
+    Ok(([(header::CONTENT_TYPE, ical::CONTENT_TYPE)], ical::calendar(Some("Todos"), &todos)).into_response())
 }
 
 #[utoipa::path(
     get,
     path = "/v1/todos/{id}",
     params(
-        ("id" = i64, Path, description = "Todo id")
+        ("id" = i64, Path, description = "Todo id"),
+        ("X-Workspace-Id" = i64, Header, description = "Workspace id"),
+        FieldsQuery
     ),
     responses(
         (status = 200, body = Todo),
+        (status = 400, description = "Unknown field or include"),
         (status = 404, description = "Not found")
     )
 )]
 pub async fn todo_read(
-    State(dbpool): State<sqlx::PgPool>,
-    Path(id): Path<i64>,
-) -> Result<Json<Todo>, Error> {
-    repo::todo::read(&dbpool, id).await.map(Json::from)
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    Path(TodoPath { id }): Path<TodoPath>,
+    Query(query): Query<FieldsQuery>,
+) -> Result<Response, Error> {
+    match query.projection()? {
+        Some(projection) => {
+            let todo = repo::todo::read_projected(&db, access.workspace_id, id, &projection).await?;
+            Ok(Json(todo).into_response())
+        }
+        None => {
+            let todo = repo::todo::read(&db, access.workspace_id, id).await?;
+            Ok(Json(todo).into_response())
+        }
+    }
 }
 
 #[utoipa::path(
     patch,
     path = "/v1/todos/{id}",
     params(
-        ("id" = i64, Path)
+        ("id" = i64, Path),
+        ("X-Workspace-Id" = i64, Header)
+    ),
+    request_body(
+        description = "Поля для изменения, JSON Merge Patch (RFC 7396) или JSON Patch (RFC 6902)",
+        content(
+            (UpdateTodo = "application/json"),
+            (serde_json::Value = "application/merge-patch+json"),
+            (serde_json::Value = "application/json-patch+json")
+        )
     ),
-    request_body = UpdateTodo,
     responses(
         (status = 200, body = Todo),
-        (status = 404)
+        (status = 403),
+        (status = 404),
+        (status = 409, description = "JSON Patch 'test' operation failed"),
+        (status = 415)
     )
 )]
 pub async fn todo_update(
-    State(dbpool): State<sqlx::PgPool>,
-    Path(id): Path<i64>,
-    Json(update_todo): Json<UpdateTodo>,
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    Path(TodoPath { id }): Path<TodoPath>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> Result<Json<Todo>, Error> {
-    update_todo.validate()?;
-    repo::todo::update(&dbpool, id, update_todo).await.map(Json::from)
+    access.require(Role::Editor)?;
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok());
+    let patch = TodoPatch::parse(content_type, &body)?;
+    repo::todo::update(&db, access.workspace_id, id, patch).await.map(Json::from)
 }
 
 #[utoipa::path(
     post,
     path = "/v1/todos",
+    params(
+        ("X-Workspace-Id" = i64, Header)
+    ),
     request_body = CreateTodo,
     responses(
-        (status = 200, body = Todo)
+        (status = 200, body = Todo),
+        (status = 403)
     )
 )]
 pub async fn todo_create(
-    State(dbpool): State<sqlx::PgPool>,
+    State(db): State<Db>,
+    access: WorkspaceAccess,
     Json(new_todo): Json<CreateTodo>,
 ) -> Result<Json<Todo>, Error> {
-    repo::todo::create(&dbpool, new_todo).await.map(Json::from)
+    access.require(Role::Editor)?;
+    repo::todo::create(&db, access.workspace_id, new_todo).await.map(Json::from)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/todos/quick",
+    params(
+        ("X-Workspace-Id" = i64, Header)
+    ),
+    request_body = QuickAdd,
+    responses(
+        (status = 200, body = QuickAdded),
+        (status = 403),
+        (status = 422, description = "Nothing is left for the body after parsing")
+    )
+)]
+pub async fn todo_quick_add(
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    Json(quick_add): Json<QuickAdd>,
+) -> Result<Json<QuickAdded>, Error> {
+    access.require(Role::Editor)?;
+    let (new_todo, recognised) = quick_add.parse(chrono::Utc::now())?;
+    let todo = repo::todo::create(&db, access.workspace_id, new_todo).await?;
+    Ok(Json(QuickAdded::new(todo, recognised)))
 }
 
 #[utoipa::path(
     delete,
     path = "/v1/todos/{id}",
     params(
-        ("id" = i64, Path)
+        ("id" = i64, Path),
+        ("X-Workspace-Id" = i64, Header)
     ),
     responses(
         (status = 204),
+        (status = 403),
         (status = 404)
     )
 )]
 pub async fn todo_delete(
+    State(db): State<Db>,
+    State(blobs): State<Arc<dyn BlobStore>>,
+    access: WorkspaceAccess,
+    Path(TodoPath { id }): Path<TodoPath>,
+) -> Result<StatusCode, Error> {
+    access.require(Role::Editor)?;
+    let storage_keys = repo::todo::delete(&db, access.workspace_id, id).await?;
+    delete_blobs(blobs.as_ref(), &storage_keys).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/todos/{id}/move",
+    params(
+        ("id" = i64, Path),
+        ("X-Workspace-Id" = i64, Header)
+    ),
+    request_body = MoveTodo,
+    responses(
+        (status = 200, body = Todo),
+        (status = 403),
+        (status = 404),
+        (status = 422, description = "Neither or both of 'before'/'after', or the target does not exist")
+    )
+)]
+pub async fn todo_move(
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    Path(TodoPath { id }): Path<TodoPath>,
+    Json(move_todo): Json<MoveTodo>,
+) -> Result<Json<Todo>, Error> {
+    access.require(Role::Editor)?;
+    let placement = move_todo.placement(id)?;
+    repo::todo::move_to(&db, access.workspace_id, id, placement).await.map(Json::from)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/todos/{id}/occurrences",
+    params(
+        ("id" = i64, Path),
+        ("X-Workspace-Id" = i64, Header),
+        OccurrencesQuery
+    ),
+    responses(
+        (status = 200, body = Occurrences),
+        (status = 404),
+        (status = 422, description = "Todo is not recurring or 'limit' is invalid")
+    )
+)]
+pub async fn todo_occurrences(
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    Path(TodoPath { id }): Path<TodoPath>,
+    Query(query): Query<OccurrencesQuery>,
+) -> Result<Json<Occurrences>, Error> {
+    let limit = query.limit()?;
+    let todo = repo::todo::read(&db, access.workspace_id, id).await?;
+    let schedule = todo.schedule().ok_or_else(|| {
+        Error::Validation(StatusCode::UNPROCESSABLE_ENTITY, Key::NoRecurrence.into())
+    })?;
+    Ok(Json(Occurrences::new(&schedule, chrono::Utc::now(), limit)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/sync",
+    params(
+        ("X-Workspace-Id" = i64, Header),
+        SyncQuery
+    ),
+    responses(
+        (status = 200, body = SyncChanges),
+        (status = 422, description = "Invalid 'since' token")
+    )
+)]
+pub async fn sync_changes(
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    Query(query): Query<SyncQuery>,
+) -> Result<Json<SyncChanges>, Error> {
+    let since = query.since()?;
+    repo::sync::changes(&db, access.workspace_id, since).await.map(Json::from)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/sync",
+    params(
+        ("X-Workspace-Id" = i64, Header)
+    ),
+    request_body = PushChanges,
+    responses(
+        (status = 200, body = PushResult),
+        (status = 403),
+        (status = 422, description = "Too many changes or a new todo without 'body'")
+    )
+)]
+pub async fn sync_push(
+    State(db): State<Db>,
+    State(blobs): State<Arc<dyn BlobStore>>,
+    access: WorkspaceAccess,
+    Json(push): Json<PushChanges>,
+) -> Result<Json<PushResult>, Error> {
+    access.require(Role::Editor)?;
+    push.validate()?;
+
+    let (results, storage_keys) = repo::sync::push(&db, access.workspace_id, push.changes).await?;
+    delete_blobs(blobs.as_ref(), &storage_keys).await;
+    Ok(Json(PushResult { results }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/stats",
+    params(
+        ("X-Workspace-Id" = i64, Header),
+        StatsQuery
+    ),
+    responses(
+        (status = 200, body = Stats),
+        (status = 403),
+        (status = 422, description = "Invalid date range")
+    )
+)]
+pub async fn stats(
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    Query(query): Query<StatsQuery>,
+) -> Result<Json<Stats>, Error> {
+    access.require(Role::Owner)?;
+    let range = query.range()?;
+    repo::stats::stats(&db, access.workspace_id, range).await.map(Json::from)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/todos/{id}/comments",
+    params(
+        ("id" = i64, Path),
+        ("X-Workspace-Id" = i64, Header),
+        CommentQuery
+    ),
+    responses(
+        (status = 200, body = CommentPage),
+        (status = 404),
+        (status = 422, description = "Invalid 'limit'")
+    )
+)]
+pub async fn comment_list(
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    Path(TodoPath { id }): Path<TodoPath>,
+    Query(page): Query<CommentQuery>,
+) -> Result<Json<CommentPage>, Error> {
+    let limit = page.limit()?;
+    repo::todo::read(&db, access.workspace_id, id).await?;
+    let comments = repo::comment::list(&db, access.workspace_id, id, page.after, limit).await?;
+    Ok(Json(CommentPage::new(comments, limit)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/todos/{id}/comments",
+    params(
+        ("id" = i64, Path),
+        ("X-Workspace-Id" = i64, Header)
+    ),
+    request_body = CreateComment,
+    responses(
+        (status = 200, body = Comment),
+        (status = 403),
+        (status = 404),
+        (status = 422, description = "Empty or too long 'body'")
+    )
+)]
+pub async fn comment_create(
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    Path(TodoPath { id }): Path<TodoPath>,
+    Json(new_comment): Json<CreateComment>,
+) -> Result<Json<Comment>, Error> {
+    access.require(Role::Editor)?;
+    new_comment.validate()?;
+    repo::comment::create(&db, access.workspace_id, id, access.user_id, new_comment)
+        .await
+        .map(Json::from)
+}
+
+#[utoipa::path(
+    patch,
+    path = "/v1/todos/{id}/comments/{comment_id}",
+    params(
+        ("id" = i64, Path),
+        ("comment_id" = i64, Path),
+        ("X-Workspace-Id" = i64, Header)
+    ),
+    request_body = UpdateComment,
+    responses(
+        (status = 200, body = Comment),
+        (status = 403, description = "Only the author can edit a comment"),
+        (status = 404),
+        (status = 422, description = "Empty or too long 'body'")
+    )
+)]
+pub async fn comment_update(
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    Path(CommentPath { id, comment_id }): Path<CommentPath>,
+    Json(update_comment): Json<UpdateComment>,
+) -> Result<Json<Comment>, Error> {
+    access.require(Role::Editor)?;
+    update_comment.validate()?;
+    let comment = repo::comment::read(&db, access.workspace_id, id, comment_id).await?;
+    if comment.author_id() != access.user_id {
+        return Err(Error::Forbidden);
+    }
+    repo::comment::update(&db, access.workspace_id, id, comment_id, update_comment)
+        .await
+        .map(Json::from)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/todos/{id}/comments/{comment_id}",
+    params(
+        ("id" = i64, Path),
+        ("comment_id" = i64, Path),
+        ("X-Workspace-Id" = i64, Header)
+    ),
+    responses(
+        (status = 204),
+        (status = 403, description = "Only the author or a workspace owner can delete a comment"),
+        (status = 404)
+    )
+)]
+pub async fn comment_delete(
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    Path(CommentPath { id, comment_id }): Path<CommentPath>,
+) -> Result<StatusCode, Error> {
+    access.require(Role::Editor)?;
+    let comment = repo::comment::read(&db, access.workspace_id, id, comment_id).await?;
+    if comment.author_id() != access.user_id {
+        access.require(Role::Owner)?;
+    }
+    repo::comment::delete(&db, access.workspace_id, id, comment_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/todos/{id}/attachments",
+    params(
+        ("id" = i64, Path),
+        ("X-Workspace-Id" = i64, Header)
+    ),
+    responses(
+        (status = 200, body = [Attachment]),
+        (status = 404)
+    )
+)]
+pub async fn attachment_list(
+    State(db): State<Db>,
+    access: WorkspaceAccess,
+    Path(TodoPath { id }): Path<TodoPath>,
+) -> Result<Json<Vec<Attachment>>, Error> {
+    repo::todo::read(&db, access.workspace_id, id).await?;
+    repo::attachment::list(&db, access.workspace_id, id).await.map(Json::from)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/todos/{id}/attachments",
+    params(
+        ("id" = i64, Path),
+        ("X-Workspace-Id" = i64, Header)
+    ),
+    request_body(content = AttachmentUpload, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, body = Attachment),
+        (status = 403),
+        (status = 404),
+        (status = 413, description = "File is larger than ATTACHMENT_MAX_BYTES"),
+        (status = 422, description = "No 'file' field or the file is empty")
+    )
+)]
+pub async fn attachment_upload(
+    State(state): State<AppState>,
+    access: WorkspaceAccess,
+    Path(TodoPath { id }): Path<TodoPath>,
+    mut multipart: Multipart,
+) -> Result<Json<Attachment>, Error> {
+    access.require(Role::Editor)?;
+    repo::todo::read(&state.db, access.workspace_id, id).await?;
+
+    let multipart_error = |e: axum::extract::multipart::MultipartError| {
+        Error::Validation(e.status(), Message::new(Key::InvalidMultipart).arg("details", e.body_text()))
+    };
+    let mut field = loop {
+        match multipart.next_field().await.map_err(multipart_error)? {
+            Some(field) if field.name() == Some("file") => break field,
+            Some(_) => continue,
+            None => {
+                return Err(Error::Validation(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    Key::AttachmentMissing.into(),
+                ));
+            }
+        }
+    };
+
+    let file_name = field
+        .file_name()
+        .map(sanitize_file_name)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "attachment".to_string());
+
+    let mut data = bytes::BytesMut::new();
+    while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+        if data.len() + chunk.len() > state.attachment_max_bytes {
+            return Err(Error::Validation(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Message::new(Key::AttachmentTooLarge).arg("max", state.attachment_max_bytes),
+            ));
+        }
+        data.extend_from_slice(&chunk);
+    }
+    if data.is_empty() {
+        return Err(Error::Validation(StatusCode::UNPROCESSABLE_ENTITY, Key::AttachmentEmpty.into()));
+    }
+
+    let content_type = blob::sniff_content_type(&data[..data.len().min(512)]);
+    let storage_key = format!("{}/{}/{}", access.workspace_id, id, hex::encode(rand::random::<[u8; 16]>()));
+    let size_bytes = data.len() as i64;
+    state.blobs.put(&storage_key, content_type, data.freeze()).await?;
+
+    let new_attachment = NewAttachment {
+        file_name,
+        content_type: content_type.to_string(),
+        size_bytes,
+        storage_key: storage_key.clone(),
+    };
+    match repo::attachment::create(&state.db, access.workspace_id, id, new_attachment).await {
+        Ok(attachment) => Ok(Json(attachment)),
+        Err(e) => {
+            delete_blobs(state.blobs.as_ref(), &[storage_key]).await;
+            Err(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/todos/{id}/attachments/{attachment_id}",
+    params(
+        ("id" = i64, Path),
+        ("attachment_id" = i64, Path),
+        ("X-Workspace-Id" = i64, Header)
+    ),
+    responses(
+        (status = 200, description = "Attachment contents", content_type = "application/octet-stream"),
+        (status = 404)
+    )
+)]
+pub async fn attachment_download(
+    State(db): State<Db>,
+    State(blobs): State<Arc<dyn BlobStore>>,
+    access: WorkspaceAccess,
+    Path(AttachmentPath { id, attachment_id }): Path<AttachmentPath>,
+) -> Result<Response, Error> {
+    let attachment = repo::attachment::read(&db, access.workspace_id, id, attachment_id).await?;
+    let stream = blobs.get(&attachment.storage_key).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, attachment.content_type().to_string()),
+            (header::CONTENT_LENGTH, attachment.size_bytes().to_string()),
+            (header::CONTENT_DISPOSITION, content_disposition(attachment.file_name())),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/todos/{id}/attachments/{attachment_id}",
+    params(
+        ("id" = i64, Path),
+        ("attachment_id" = i64, Path),
+        ("X-Workspace-Id" = i64, Header)
+    ),
+    responses(
+        (status = 204),
+        (status = 403),
+        (status = 404)
+    )
+)]
+pub async fn attachment_delete(
+    State(db): State<Db>,
+    State(blobs): State<Arc<dyn BlobStore>>,
+    access: WorkspaceAccess,
+    Path(AttachmentPath { id, attachment_id }): Path<AttachmentPath>,
+) -> Result<StatusCode, Error> {
+    access.require(Role::Editor)?;
+    let storage_key = repo::attachment::delete(&db, access.workspace_id, id, attachment_id).await?;
+    delete_blobs(blobs.as_ref(), &[storage_key]).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// метаданные уже удалены, так что недоудалённый blob - только мусор в хранилище
+pub(crate) async fn delete_blobs(blobs: &dyn BlobStore, storage_keys: &[String]) {
+    for storage_key in storage_keys {
+        if let Err(e) = blobs.delete(storage_key).await {
+            tracing::warn!("couldn't delete blob {}: {}", storage_key, e);
+        }
+    }
+}
+
+/// Имя файла для `Content-Disposition`: без пути, кавычек и управляющих символов.
+fn sanitize_file_name(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    base.chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(255)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// `attr-char` из RFC 5987 остаются как есть, остальное кодируется.
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!').remove(b'#').remove(b'$').remove(b'&').remove(b'+').remove(b'-').remove(b'.')
+    .remove(b'^').remove(b'_').remove(b'`').remove(b'|').remove(b'~');
+
+/// `Content-Disposition` по RFC 6266: в `filename` только ASCII, для старых клиентов, а полное
+/// имя в UTF-8 - в `filename*`.
+fn content_disposition(file_name: &str) -> String {
+    if file_name.is_ascii() {
+        return format!("attachment; filename=\"{file_name}\"");
+    }
+    let fallback: String = file_name.chars().map(|c| if c.is_ascii() { c } else { '_' }).collect();
+    format!(
+        "attachment; filename=\"{fallback}\"; filename*=UTF-8''{}",
+        utf8_percent_encode(file_name, ATTR_CHAR),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/workspaces",
+    responses(
+        (status = 200, description = "Workspaces of the current user", body = [Workspace])
+    )
+)]
+pub async fn workspace_list(
+    State(dbpool): State<sqlx::PgPool>,
+    CurrentUser(user_id): CurrentUser,
+) -> Result<Json<Vec<Workspace>>, Error> {
+    repo::workspace::list_for_user(&dbpool, user_id).await.map(Json::from)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/workspaces",
+    request_body = CreateWorkspace,
+    responses(
+        (status = 200, body = Workspace),
+        (status = 403, description = "Workspaces can not be created with an API key"),
+        (status = 422)
+    )
+)]
+pub async fn workspace_create(
+    State(dbpool): State<sqlx::PgPool>,
+    principal: Principal,
+    Json(new_workspace): Json<CreateWorkspace>,
+) -> Result<Json<Workspace>, Error> {
+    if principal.is_api_key() {
+        return Err(Error::Forbidden);
+    }
+    new_workspace.validate()?;
+    repo::workspace::create(&dbpool, principal.user_id, new_workspace).await.map(Json::from)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/workspaces/{workspace_id}/members",
+    params(
+        ("workspace_id" = i64, Path)
+    ),
+    responses(
+        (status = 200, body = [Member]),
+        (status = 404)
+    )
+)]
+pub async fn member_list(
+    State(dbpool): State<sqlx::PgPool>,
+    access: WorkspaceAccess,
+) -> Result<Json<Vec<Member>>, Error> {
+    repo::workspace::members(&dbpool, access.workspace_id).await.map(Json::from)
+}
+
+#[utoipa::path(
+    put,
+    path = "/v1/workspaces/{workspace_id}/members/{user_id}",
+    params(
+        ("workspace_id" = i64, Path),
+        ("user_id" = i64, Path)
+    ),
+    request_body = SetMember,
+    responses(
+        (status = 200, body = Member),
+        (status = 403, description = "Not an owner, or an API key"),
+        (status = 422, description = "Workspace would be left without an owner")
+    )
+)]
+pub async fn member_set(
+    State(dbpool): State<sqlx::PgPool>,
+    principal: Principal,
+    access: WorkspaceAccess,
+    Path(MemberPath { user_id }): Path<MemberPath>,
+    Json(set_member): Json<SetMember>,
+) -> Result<Json<Member>, Error> {
+    if principal.is_api_key() {
+        return Err(Error::Forbidden);
+    }
+    access.require(Role::Owner)?;
+    repo::workspace::set_member(&dbpool, access.workspace_id, user_id, set_member.role)
+        .await
+        .map(Json::from)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/workspaces/{workspace_id}/members/{user_id}",
+    params(
+        ("workspace_id" = i64, Path),
+        ("user_id" = i64, Path)
+    ),
+    responses(
+        (status = 204),
+        (status = 403, description = "Not an owner, or an API key"),
+        (status = 404),
+        (status = 422, description = "Workspace would be left without an owner")
+    )
+)]
+pub async fn member_delete(
+    State(dbpool): State<sqlx::PgPool>,
+    principal: Principal,
+    access: WorkspaceAccess,
+    Path(MemberPath { user_id }): Path<MemberPath>,
+) -> Result<StatusCode, Error> {
+    if principal.is_api_key() {
+        return Err(Error::Forbidden);
+    }
+    // покинуть workspace может любой участник, исключать других - только owner
+    if user_id != access.user_id {
+        access.require(Role::Owner)?;
+    }
+    repo::workspace::remove_member(&dbpool, access.workspace_id, user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/api-keys",
+    responses(
+        (status = 200, description = "API keys of the current user", body = [ApiKey]),
+        (status = 403, description = "Keys can not be managed with an API key")
+    )
+)]
+pub async fn api_key_list(
+    State(dbpool): State<sqlx::PgPool>,
+    principal: Principal,
+) -> Result<Json<Vec<ApiKey>>, Error> {
+    if principal.is_api_key() {
+        return Err(Error::Forbidden);
+    }
+    repo::api_key::list_for_user(&dbpool, principal.user_id).await.map(Json::from)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/api-keys",
+    request_body = CreateApiKey,
+    responses(
+        (status = 200, description = "The token is returned only once", body = CreatedApiKey),
+        (status = 403),
+        (status = 422)
+    )
+)]
+pub async fn api_key_create(
+    State(dbpool): State<sqlx::PgPool>,
+    principal: Principal,
+    Json(new_key): Json<CreateApiKey>,
+) -> Result<Json<CreatedApiKey>, Error> {
+    if principal.is_api_key() {
+        return Err(Error::Forbidden);
+    }
+    new_key.validate()?;
+
+    // сервисные ключи workspace выпускает только его owner
+    if let Some(workspace_id) = new_key.workspace_id {
+        let role = repo::workspace::role_of(&dbpool, workspace_id, principal.user_id)
+            .await?
+            .ok_or(Error::NotFound)?;
+        if role < Role::Owner {
+            return Err(Error::Forbidden);
+        }
+    }
+
+    repo::api_key::create(&dbpool, principal.user_id, new_key).await.map(Json::from)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/api-keys/{id}",
+    params(
+        ("id" = i64, Path)
+    ),
+    responses(
+        (status = 204),
+        (status = 403),
+        (status = 404)
+    )
+)]
+pub async fn api_key_revoke(
     State(dbpool): State<sqlx::PgPool>,
-    Path(id): Path<i64>,
+    principal: Principal,
+    Path(ApiKeyPath { id }): Path<ApiKeyPath>,
 ) -> Result<StatusCode, Error> {
-    repo::todo::delete(&dbpool, id).await?;
+    if principal.is_api_key() {
+        return Err(Error::Forbidden);
+    }
+    repo::api_key::revoke(&dbpool, principal.user_id, id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
\ No newline at end of file
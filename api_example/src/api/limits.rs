@@ -0,0 +1,137 @@
+use axum::body::{Body, HttpBody};
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use sqlx::PgPool;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use crate::error::Error;
+
+/// Пределы нагрузки, из окружения:
+/// - `REQUEST_TIMEOUT_SECS` (30) - время на запрос, после него 504;
+/// - `TRANSFER_TIMEOUT_SECS` (300) - то же для вложений, календарей и CalDAV;
+/// - `BODY_MAX_BYTES` (1 МиБ) - тело запроса, у загрузки вложений свой предел `ATTACHMENT_MAX_BYTES`;
+/// - `MAX_CONCURRENT_REQUESTS` (1024) - запросы сверх него сразу получают 503;
+/// - `DB_QUEUE_MAX` (32) - сколько запросов может ждать соединение, когда пул занят целиком.
+#[derive(Clone, Debug)]
+pub struct Limits {
+    pub request_timeout: Duration,
+    pub transfer_timeout: Duration,
+    pub body_max_bytes: usize,
+    pub max_concurrent_requests: usize,
+    pub db_queue_max: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            request_timeout: Duration::from_secs(30),
+            transfer_timeout: Duration::from_secs(300),
+            body_max_bytes: 1024 * 1024,
+            max_concurrent_requests: 1024,
+            db_queue_max: 32,
+        }
+    }
+}
+
+impl Limits {
+    pub fn from_env() -> Self {
+        fn var<T: std::str::FromStr>(name: &str) -> Option<T> {
+            std::env::var(name).ok().and_then(|value| value.parse().ok())
+        }
+
+        let default = Limits::default();
+        Limits {
+            request_timeout: var("REQUEST_TIMEOUT_SECS").map_or(default.request_timeout, Duration::from_secs),
+            transfer_timeout: var("TRANSFER_TIMEOUT_SECS").map_or(default.transfer_timeout, Duration::from_secs),
+            body_max_bytes: var("BODY_MAX_BYTES").unwrap_or(default.body_max_bytes),
+            max_concurrent_requests: var("MAX_CONCURRENT_REQUESTS").unwrap_or(default.max_concurrent_requests),
+            db_queue_max: var("DB_QUEUE_MAX").unwrap_or(default.db_queue_max),
+        }
+    }
+
+    /// Пределы обычного маршрута API.
+    pub fn requests(&self) -> RouteLimits {
+        RouteLimits { timeout: self.request_timeout, body_max_bytes: self.body_max_bytes }
+    }
+
+    /// Вложения и календари передаются дольше обычных запросов, тело у них может быть больше.
+    pub fn transfers(&self, body_max_bytes: usize) -> RouteLimits {
+        RouteLimits { timeout: self.transfer_timeout, body_max_bytes }
+    }
+}
+
+/// Состояние `guard`: общий на все запросы счётчик и пул, по которому видна очередь к БД.
+#[derive(Clone)]
+pub struct Guard {
+    limits: Arc<Limits>,
+    permits: Arc<Semaphore>,
+    pool: PgPool,
+}
+
+impl Guard {
+    pub fn new(limits: Limits, pool: PgPool) -> Self {
+        let permits = Arc::new(Semaphore::new(limits.max_concurrent_requests));
+        Guard { limits: Arc::new(limits), permits, pool }
+    }
+
+    /// Оценка очереди к пулу: когда свободных соединений нет, запросы в обработке
+    /// сверх занятых соединений ждут своего.
+    fn db_queue(&self) -> Option<usize> {
+        let size = self.pool.size() as usize;
+        if self.pool.num_idle() > 0 || size < self.pool.options().get_max_connections() as usize {
+            return None;
+        }
+        let in_flight = self.limits.max_concurrent_requests - self.permits.available_permits();
+        Some(in_flight.saturating_sub(size))
+    }
+}
+
+/// Сбрасывает нагрузку до того, как запрос займёт соединение.
+pub async fn guard(State(guard): State<Guard>, request: Request, next: Next) -> Response {
+    // при длинной очереди к пулу запрос скорее всего не дождётся соединения до таймаута
+    if guard.db_queue().is_some_and(|queue| queue >= guard.limits.db_queue_max) {
+        return Error::Overloaded.into_response();
+    }
+    let Ok(_permit) = guard.permits.clone().try_acquire_owned() else {
+        return Error::Overloaded.into_response();
+    };
+    next.run(request).await
+}
+
+/// Время на запрос и предел тела для группы маршрутов, см. `enforce`.
+#[derive(Clone, Copy, Debug)]
+pub struct RouteLimits {
+    pub timeout: Duration,
+    pub body_max_bytes: usize,
+}
+
+/// Вешается `route_layer`-ом на группу маршрутов с её `RouteLimits`.
+/// Тело без `Content-Length` считается при чтении: если обработчик упёрся в предел,
+/// его ответ (отказ экстрактора, ошибка multipart) заменяется на `PayloadTooLarge`.
+pub async fn enforce(State(route): State<RouteLimits>, request: Request, next: Next) -> Response {
+    let max = route.body_max_bytes;
+    if request.body().size_hint().exact().is_some_and(|length| length > max as u64) {
+        return Error::PayloadTooLarge(max).into_response();
+    }
+
+    let exceeded = Arc::new(AtomicBool::new(false));
+    let request = request.map(|body| {
+        let exceeded = exceeded.clone();
+        Body::new(Limited::new(body, max).map_err(move |err| {
+            if err.is::<LengthLimitError>() {
+                exceeded.store(true, Ordering::Relaxed);
+            }
+            err
+        }))
+    });
+
+    match tokio::time::timeout(route.timeout, next.run(request)).await {
+        Ok(_) if exceeded.load(Ordering::Relaxed) => Error::PayloadTooLarge(max).into_response(),
+        Ok(response) => response,
+        Err(_) => Error::Timeout.into_response(),
+    }
+}
\ No newline at end of file
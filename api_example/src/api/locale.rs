@@ -0,0 +1,16 @@
+use axum::extract::Request;
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::Response;
+use crate::i18n::Locale;
+
+/// Выбирает язык сообщений об ошибках по `Accept-Language` на время запроса.
+pub async fn negotiate(request: Request, next: Next) -> Response {
+    let locale = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(Locale::negotiate)
+        .unwrap_or_default();
+    locale.scope(next.run(request)).await
+}
\ No newline at end of file
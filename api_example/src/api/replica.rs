@@ -0,0 +1,24 @@
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use crate::api::auth::Principal;
+use crate::repo::pg::Db;
+
+/// Read-your-writes для реплики: запись и чтения клиента в течение короткого окна
+/// после его успешной записи выполняются на primary.
+pub async fn read_your_writes(State(db): State<Db>, request: Request, next: Next) -> Response {
+    if !db.has_replica() {
+        return next.run(request).await;
+    }
+
+    let client = request.extensions().get::<Principal>().map(|principal| principal.user_id);
+    let writes = !request.method().is_safe();
+    let pinned = writes || client.is_some_and(|client| db.is_sticky(client));
+
+    let response = Db::read_from_primary(pinned, next.run(request)).await;
+
+    if let Some(client) = client.filter(|_| writes && response.status().is_success()) {
+        db.wrote(client);
+    }
+    response
+}
\ No newline at end of file
@@ -1,7 +1,18 @@
-use crate::api::handlers;
+use crate::api::state::AppState;
+use crate::api::{auth, dav, handlers, limits, locale, replica};
+use crate::dto::api_key::{ApiKey, ApiKeyKind, CreateApiKey, CreatedApiKey, Scope};
+use crate::dto::attachment::{Attachment, AttachmentUpload};
+use crate::dto::comment::{Comment, CommentPage, CreateComment, UpdateComment};
+use crate::dto::stats::{Bucket, HistogramBucket, Stats, Totals};
+use crate::dto::sync::{Change, ChangeError, ChangeResult, Outcome, PushChanges, PushResult, SyncChanges, Tombstone};
 use crate::dto::todo::CreateTodo;
+use crate::dto::todo::{Priority, QuickAdd, QuickAdded};
+use crate::dto::todo::MoveTodo;
+use crate::dto::todo::Occurrences;
 use crate::dto::todo::Todo;
 use crate::dto::todo::UpdateTodo;
+use crate::dto::workspace::{CreateWorkspace, Member, Role, SetMember, Workspace};
+use crate::quick_add::{Kind, Recognised};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
@@ -9,13 +20,39 @@ use utoipa_swagger_ui::SwaggerUi;
 #[openapi(
     paths(
         handlers::todo_list,
+        handlers::todo_feed,
+        handlers::calendar_feed,
         handlers::todo_read,
         handlers::todo_create,
+        handlers::todo_quick_add,
         handlers::todo_update,
-        handlers::todo_delete
+        handlers::todo_delete,
+        handlers::todo_move,
+        handlers::todo_occurrences,
+        handlers::stats,
+        handlers::sync_changes,
+        handlers::sync_push,
+        handlers::comment_list,
+        handlers::comment_create,
+        handlers::comment_update,
+        handlers::comment_delete,
+        handlers::attachment_list,
+        handlers::attachment_upload,
+        handlers::attachment_download,
+        handlers::attachment_delete,
+        handlers::workspace_list,
+        handlers::workspace_create,
+        handlers::member_list,
+        handlers::member_set,
+        handlers::member_delete,
+        handlers::api_key_list,
+        handlers::api_key_create,
+        handlers::api_key_revoke
     ),
     components(
-        schemas(Todo, CreateTodo, UpdateTodo)
+        schemas(Todo, CreateTodo, UpdateTodo, Priority, QuickAdd, QuickAdded, Recognised, Kind, MoveTodo, Occurrences, Comment, CommentPage, CreateComment, UpdateComment, Attachment, AttachmentUpload, Stats, Totals, HistogramBucket, Bucket,
+            SyncChanges, Tombstone, PushChanges, Change, ChangeResult, ChangeError, Outcome, PushResult, Workspace, CreateWorkspace, Member, SetMember, Role,
+            ApiKey, ApiKeyKind, CreateApiKey, CreatedApiKey, Scope)
     ),
     tags(
         (name = "todo", description = "Todo API")
@@ -23,25 +60,159 @@ use utoipa_swagger_ui::SwaggerUi;
 )]
 struct ApiDoc;
 
-pub fn create_router(dbpool: sqlx::PgPool) -> axum::Router {
-    use axum::{Router, routing::get};
+pub fn create_router(state: AppState) -> axum::Router {
+    use axum::extract::DefaultBodyLimit;
+    use axum::response::Redirect;
+    use axum::{Router, middleware, routing::{any, delete, get, options, patch, post, put}};
+    use axum::http::{header, HeaderValue};
+    use tower_http::compression::CompressionLayer;
+    use tower_http::compression::predicate::{DefaultPredicate, Predicate, SizeAbove};
     use tower_http::cors::{Any, CorsLayer};
+    use tower_http::set_header::SetResponseHeaderLayer;
     use tower_http::trace::TraceLayer;
 
+    let cache_control = |value: &'static str| {
+        SetResponseHeaderLayer::if_not_present(header::CACHE_CONTROL, HeaderValue::from_static(value))
+    };
+
+    let compression_min_bytes = std::env::var("COMPRESSION_MIN_BYTES")
+        .ok()
+        .and_then(|bytes| bytes.parse().ok())
+        .unwrap_or(1024);
+
+    // общий на /v1 и CalDAV: они делят пул и предел одновременных запросов
+    let guard = limits::Guard::new(state.limits.clone(), state.db.primary().clone());
+    // время на запрос и предел тела задаёт группа маршрутов, внутри авторизации
+    let requests = middleware::from_fn_with_state(state.limits.requests(), limits::enforce);
+    let transfers = middleware::from_fn_with_state(state.limits.transfers(state.limits.body_max_bytes), limits::enforce);
+
+    let todos = Router::new()
+        .route("/todos", get(handlers::todo_list))
+        .route("/todos/{id}", get(handlers::todo_read))
+        .route("/todos/{id}/occurrences", get(handlers::todo_occurrences))
+        .route("/todos/{id}/comments", get(handlers::comment_list))
+        .route("/todos/{id}/attachments", get(handlers::attachment_list))
+        .route("/stats", get(handlers::stats))
+        .route("/sync", get(handlers::sync_changes))
+        .route_layer(middleware::from_fn_with_state(Scope::TodosRead, auth::require_scope))
+        // ответ зависит от пользователя, поэтому только private; list отдаёт ETag для ревалидации
+        .route_layer(cache_control("private, no-cache"))
+        .route_layer(requests.clone())
+        .merge(
+            Router::new()
+                .route("/todos", post(handlers::todo_create))
+                .route("/todos/quick", post(handlers::todo_quick_add))
+                .route("/todos/{id}", patch(handlers::todo_update).delete(handlers::todo_delete))
+                .route("/todos/{id}/move", post(handlers::todo_move))
+                .route("/sync", post(handlers::sync_push))
+                .route("/todos/{id}/comments", post(handlers::comment_create))
+                .route(
+                    "/todos/{id}/comments/{comment_id}",
+                    patch(handlers::comment_update).delete(handlers::comment_delete),
+                )
+                .route("/todos/{id}/attachments/{attachment_id}", delete(handlers::attachment_delete))
+                .route_layer(middleware::from_fn_with_state(Scope::TodosWrite, auth::require_scope))
+                .route_layer(cache_control("no-store"))
+                .route_layer(requests.clone()),
+        )
+        .merge(
+            Router::new()
+                .route("/todos/{id}/attachments/{attachment_id}", get(handlers::attachment_download))
+                .route_layer(middleware::from_fn_with_state(Scope::TodosRead, auth::require_scope))
+                .route_layer(cache_control("private, no-cache"))
+                .route_layer(transfers.clone()),
+        )
+        .merge(
+            Router::new()
+                .route("/todos/{id}/attachments", post(handlers::attachment_upload))
+                .route_layer(middleware::from_fn_with_state(Scope::TodosWrite, auth::require_scope))
+                .route_layer(cache_control("no-store"))
+                // запас на заголовки multipart поверх самого файла
+                .route_layer(middleware::from_fn_with_state(
+                    state.limits.transfers(state.attachment_max_bytes + 64 * 1024),
+                    limits::enforce,
+                )),
+        )
+        .merge(
+            Router::new()
+                .route("/todos.ics", get(handlers::todo_feed))
+                .route_layer(middleware::from_fn_with_state(Scope::TodosRead, auth::require_scope))
+                .route_layer(cache_control("private, no-cache"))
+                .route_layer(middleware::from_fn(dav::challenge))
+                .route_layer(transfers.clone()),
+        );
+
+    // подписка на все todo пользователя, без привязки к workspace
+    let calendar = Router::new()
+        .route("/calendar.ics", get(handlers::calendar_feed))
+        .route_layer(middleware::from_fn_with_state(Scope::TodosRead, auth::require_scope))
+        .route_layer(cache_control("private, no-cache"))
+        .route_layer(middleware::from_fn(dav::challenge))
+        .route_layer(transfers.clone());
+
+    // WebDAV-методы (PROPFIND, REPORT) axum не различает, их разбирают сами обработчики
+    let dav = Router::new()
+        .route("/dav", options(dav::options).fallback(dav::principal))
+        .route("/dav/", options(dav::options).fallback(dav::principal))
+        .route("/dav/workspaces", options(dav::options).fallback(dav::home))
+        .route("/dav/workspaces/", options(dav::options).fallback(dav::home))
+        .route("/dav/workspaces/{workspace_id}", options(dav::options).fallback(dav::calendar))
+        .route("/dav/workspaces/{workspace_id}/", options(dav::options).fallback(dav::calendar))
+        .route("/dav/workspaces/{workspace_id}/{resource}", options(dav::options).fallback(dav::resource))
+        .route_layer(cache_control("private, no-cache"))
+        .route_layer(transfers)
+        .layer(middleware::from_fn_with_state(state.db.clone(), replica::read_your_writes))
+        .layer(middleware::from_fn_with_state(state.db.primary().clone(), auth::authenticate))
+        .layer(middleware::from_fn(dav::challenge))
+        .layer(middleware::from_fn_with_state(guard.clone(), limits::guard));
+
+    let workspaces = Router::new()
+        .route("/workspaces", get(handlers::workspace_list).post(handlers::workspace_create))
+        .route("/workspaces/{workspace_id}/members", get(handlers::member_list))
+        .route(
+            "/workspaces/{workspace_id}/members/{user_id}",
+            put(handlers::member_set).delete(handlers::member_delete),
+        )
+        // изменения workspace и участников ключам запрещены в самих обработчиках
+        .route_layer(middleware::from_fn_with_state(Scope::TodosRead, auth::require_scope))
+        .route_layer(cache_control("private, no-cache"))
+        .route_layer(requests.clone());
+
+    // в ответе на создание ключа лежит секрет
+    let api_keys = Router::new()
+        .route("/api-keys", get(handlers::api_key_list).post(handlers::api_key_create))
+        .route("/api-keys/{id}", delete(handlers::api_key_revoke))
+        .route_layer(cache_control("no-store"))
+        .route_layer(requests);
+
     Router::new()
-        .merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .route("/health", get(|| async { "Ok" }))
         .route("/ready", get(handlers::ping))
+        .route("/health/details", get(handlers::health_details))
+        .route("/metrics", get(handlers::metrics))
+        .route_layer(cache_control("no-store"))
+        .route("/.well-known/caldav", any(|| async { Redirect::temporary(dav::ROOT) }))
+        .merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .nest(
             "/v1",
             Router::new()
-                .route("/todos", get(handlers::todo_list).post(handlers::todo_create))
-                .route(
-                    "/todos/{id}",
-                    get(handlers::todo_read).patch(handlers::todo_update).delete(handlers::todo_delete),
-                ),
+                .merge(todos.clone())
+                .merge(calendar)
+                .merge(workspaces)
+                .nest("/workspaces/{workspace_id}", todos)
+                .merge(api_keys)
+                .layer(middleware::from_fn_with_state(state.db.clone(), replica::read_your_writes))
+                .layer(middleware::from_fn_with_state(state.db.primary().clone(), auth::authenticate))
+                .layer(middleware::from_fn_with_state(guard, limits::guard)),
         )
-        .with_state(dbpool)
-        .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any))
-        .layer(TraceLayer::new_for_http())
+        .with_state(state.clone())
+        .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any))
+        // CORS считает любой OPTIONS preflight-запросом, а CalDAV-клиентам нужен ответ с заголовком DAV
+        .merge(dav.with_state(state.clone()))
+        // тело ограничивает limits::enforce, у каждой группы маршрутов свой предел
+        .layer(DefaultBodyLimit::disable())
+        .layer(CompressionLayer::new().compress_when(DefaultPredicate::new().and(SizeAbove::new(compression_min_bytes))))
+        // снаружи guard и таймаутов, чтобы их ответы тоже были на языке клиента
+        .layer(middleware::from_fn(locale::negotiate))
+        .layer(TraceLayer::new_for_http().make_span_with(crate::logger::http_span))
 }
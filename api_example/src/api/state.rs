@@ -0,0 +1,47 @@
+use axum::extract::FromRef;
+use sqlx::PgPool;
+use std::sync::Arc;
+use crate::api::limits::Limits;
+use crate::blob::BlobStore;
+use crate::dto::health::Thresholds;
+use crate::repo::pg::Db;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub db: Db,
+    pub blobs: Arc<dyn BlobStore>,
+    pub attachment_max_bytes: usize,
+    pub limits: Limits,
+    pub health: Thresholds,
+}
+
+impl AppState {
+    /// `ATTACHMENT_MAX_BYTES` - предельный размер вложения (по умолчанию 10 МиБ),
+    /// остальные пределы см. `Limits`, пороги `/health/details` - `Thresholds`.
+    pub fn from_env(db: Db) -> Self {
+        let attachment_max_bytes = std::env::var("ATTACHMENT_MAX_BYTES")
+            .ok()
+            .and_then(|bytes| bytes.parse().ok())
+            .unwrap_or(10 * 1024 * 1024);
+
+        AppState { db, blobs: crate::blob::from_env(), attachment_max_bytes, limits: Limits::from_env(), health: Thresholds::from_env() }
+    }
+}
+
+impl FromRef<AppState> for Db {
+    fn from_ref(state: &AppState) -> Self {
+        state.db.clone()
+    }
+}
+
+impl FromRef<AppState> for PgPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.db.primary().clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn BlobStore> {
+    fn from_ref(state: &AppState) -> Self {
+        state.blobs.clone()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,15 @@
+use api_example::client::{self, args::Cli};
+use clap::Parser;
+use std::process::ExitCode;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match client::run(cli, &mut std::io::stdout().lock()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("todo: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
\ No newline at end of file
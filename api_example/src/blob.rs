@@ -0,0 +1,74 @@
+pub mod fs;
+pub mod s3;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::stream::BoxStream;
+use std::sync::Arc;
+
+pub type BlobStream = BoxStream<'static, std::io::Result<Bytes>>;
+
+#[derive(Debug)]
+pub enum BlobError {
+    NotFound,
+    Io(String),
+}
+
+impl std::fmt::Display for BlobError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlobError::NotFound => write!(f, "blob not found"),
+            BlobError::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Хранилище содержимого вложений. Метаданные лежат в Postgres, здесь - только байты по ключу.
+#[async_trait]
+pub trait BlobStore: Send + Sync {
+    async fn put(&self, key: &str, content_type: &str, data: Bytes) -> Result<(), BlobError>;
+    async fn get(&self, key: &str) -> Result<BlobStream, BlobError>;
+    async fn delete(&self, key: &str) -> Result<(), BlobError>;
+}
+
+/// `BLOB_STORE=fs` (по умолчанию, `BLOB_FS_ROOT`) или `BLOB_STORE=s3` (см. `s3::S3Store::from_env`).
+pub(crate) fn from_env() -> Arc<dyn BlobStore> {
+    match std::env::var("BLOB_STORE").as_deref() {
+        Ok("s3") => Arc::new(s3::S3Store::from_env()),
+        Ok("fs") | Err(_) => {
+            let root = std::env::var("BLOB_FS_ROOT").unwrap_or_else(|_| "./data/attachments".to_string());
+            Arc::new(fs::FsStore::new(root))
+        }
+        Ok(other) => panic!("Unknown BLOB_STORE: {}", other),
+    }
+}
+
+/// Определяет тип по содержимому, а не по заголовку клиента.
+pub fn sniff_content_type(head: &[u8]) -> &'static str {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+    ];
+
+    if let Some((_, content_type)) = SIGNATURES.iter().find(|(magic, _)| head.starts_with(magic)) {
+        return content_type;
+    }
+    if head.len() >= 12 && &head[..4] == b"RIFF" && &head[8..12] == b"WEBP" {
+        return "image/webp";
+    }
+
+    // UTF-8, возможно обрезанный на границе символа
+    let text = match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    };
+    if text && !head.contains(&0) {
+        return "text/plain; charset=utf-8";
+    }
+    "application/octet-stream"
+}
\ No newline at end of file
@@ -0,0 +1,69 @@
+use crate::blob::{BlobError, BlobStore, BlobStream};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_util::StreamExt;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use tokio_util::io::ReaderStream;
+
+/// Файлы в локальном каталоге, ключ - относительный путь.
+pub struct FsStore {
+    root: PathBuf,
+}
+
+impl FsStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FsStore { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, BlobError> {
+        let relative = Path::new(key);
+        if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+            return Err(BlobError::Io(format!("invalid blob key: {}", key)));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl BlobStore for FsStore {
+    async fn put(&self, key: &str, _content_type: &str, data: Bytes) -> Result<(), BlobError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(io_error)?;
+        }
+
+        // пишем во временный файл, чтобы читатели не увидели половину; имя у каждой записи
+        // своё, иначе параллельные put одного ключа пишут в один файл вперемешку
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(format!(".{}.partial", hex::encode(rand::random::<[u8; 8]>())));
+        let tmp = PathBuf::from(tmp);
+        let written = match tokio::fs::write(&tmp, &data).await {
+            Ok(()) => tokio::fs::rename(&tmp, &path).await,
+            Err(e) => Err(e),
+        };
+        if written.is_err() {
+            let _ = tokio::fs::remove_file(&tmp).await;
+        }
+        written.map_err(io_error)
+    }
+
+    async fn get(&self, key: &str) -> Result<BlobStream, BlobError> {
+        let file = tokio::fs::File::open(self.path(key)?).await.map_err(io_error)?;
+        Ok(ReaderStream::new(file).boxed())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), BlobError> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(io_error(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn io_error(e: std::io::Error) -> BlobError {
+    match e.kind() {
+        ErrorKind::NotFound => BlobError::NotFound,
+        _ => BlobError::Io(e.to_string()),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,176 @@
+use crate::blob::{BlobError, BlobStore, BlobStream};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::Utc;
+use futures_util::StreamExt;
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::{Method, StatusCode, Url};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+// RFC 3986 unreserved остаются как есть, как требует SigV4
+const URI_ENCODE: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+/// Без таймаутов зависшее хранилище держало бы запрос к API и соединение из пула бесконечно.
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// На весь запрос вместе с телом; вложения ограничены `ATTACHMENT_MAX_BYTES`.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Запрос в каноническом виде SigV4: путь и query уже закодированы, query отсортирован,
+/// имена заголовков в нижнем регистре и по алфавиту.
+pub struct Canonical<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    pub query: &'a str,
+    pub headers: &'a [(&'a str, &'a str)],
+    pub payload_hash: &'a str,
+}
+
+/// S3-совместимое хранилище (AWS, MinIO, ...) с path-style адресами и подписью SigV4.
+pub struct S3Store {
+    client: reqwest::Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+}
+
+impl S3Store {
+    pub fn new(endpoint: Url, bucket: String, region: String, access_key_id: String, secret_access_key: String) -> Self {
+        let client = http_client(CONNECT_TIMEOUT, REQUEST_TIMEOUT);
+        S3Store { client, endpoint, bucket, region, access_key_id, secret_access_key }
+    }
+
+    pub fn with_timeouts(mut self, connect: Duration, request: Duration) -> Self {
+        self.client = http_client(connect, request);
+        self
+    }
+
+    /// `S3_ENDPOINT`, `S3_BUCKET`, `S3_REGION` (по умолчанию us-east-1),
+    /// `S3_ACCESS_KEY_ID`, `S3_SECRET_ACCESS_KEY`, `S3_CONNECT_TIMEOUT_MS` и `S3_TIMEOUT_MS`.
+    pub(crate) fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).unwrap_or_else(|_| panic!("{} must be set for BLOB_STORE=s3", name));
+        let millis = |name: &str| std::env::var(name).ok().and_then(|ms| ms.parse().ok()).map(Duration::from_millis);
+
+        let endpoint = Url::parse(&var("S3_ENDPOINT")).expect("S3_ENDPOINT must be a URL");
+        S3Store::new(
+            endpoint,
+            var("S3_BUCKET"),
+            std::env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+            var("S3_ACCESS_KEY_ID"),
+            var("S3_SECRET_ACCESS_KEY"),
+        )
+        .with_timeouts(
+            millis("S3_CONNECT_TIMEOUT_MS").unwrap_or(CONNECT_TIMEOUT),
+            millis("S3_TIMEOUT_MS").unwrap_or(REQUEST_TIMEOUT),
+        )
+    }
+
+    /// Заголовок `Authorization` для запроса, сделанного в `amz_date` (`20130524T000000Z`).
+    pub fn authorization(&self, amz_date: &str, request: &Canonical) -> String {
+        let date = &amz_date[..8];
+        let signed_headers = request.headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+        let canonical_headers: String =
+            request.headers.iter().map(|(name, value)| format!("{name}:{}\n", value.trim())).collect();
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{}",
+            request.method, request.path, request.query, request.payload_hash,
+        );
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes())),
+        );
+
+        let signing_key = [self.region.as_bytes(), b"s3", b"aws4_request"]
+            .into_iter()
+            .fold(hmac(format!("AWS4{}", self.secret_access_key).as_bytes(), date.as_bytes()), |key, part| {
+                hmac(&key, part)
+            });
+        let signature = hex::encode(hmac(&signing_key, string_to_sign.as_bytes()));
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            self.access_key_id,
+        )
+    }
+
+    async fn send(&self, method: Method, key: &str, body: Bytes, content_type: Option<&str>) -> Result<reqwest::Response, BlobError> {
+        let path = format!(
+            "/{}/{}",
+            utf8_percent_encode(&self.bucket, URI_ENCODE),
+            key.split('/').map(|segment| utf8_percent_encode(segment, URI_ENCODE).to_string()).collect::<Vec<_>>().join("/"),
+        );
+        let mut url = self.endpoint.clone();
+        url.set_path(&path);
+
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let authorization = self.authorization(
+            &amz_date,
+            &Canonical {
+                method: method.as_str(),
+                path: &path,
+                query: "",
+                headers: &[("host", &host), ("x-amz-content-sha256", &payload_hash), ("x-amz-date", &amz_date)],
+                payload_hash: &payload_hash,
+            },
+        );
+
+        let mut request = self
+            .client
+            .request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", authorization);
+        if let Some(content_type) = content_type {
+            request = request.header("content-type", content_type);
+        }
+
+        let response = request.body(body).send().await.map_err(|e| BlobError::Io(e.to_string()))?;
+        match response.status() {
+            status if status.is_success() => Ok(response),
+            StatusCode::NOT_FOUND => Err(BlobError::NotFound),
+            status => Err(BlobError::Io(format!("S3 responded with {}", status))),
+        }
+    }
+}
+
+#[async_trait]
+impl BlobStore for S3Store {
+    async fn put(&self, key: &str, content_type: &str, data: Bytes) -> Result<(), BlobError> {
+        self.send(Method::PUT, key, data, Some(content_type)).await.map(|_| ())
+    }
+
+    async fn get(&self, key: &str) -> Result<BlobStream, BlobError> {
+        let response = self.send(Method::GET, key, Bytes::new(), None).await?;
+        Ok(response.bytes_stream().map(|chunk| chunk.map_err(std::io::Error::other)).boxed())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), BlobError> {
+        match self.send(Method::DELETE, key, Bytes::new(), None).await {
+            Err(BlobError::NotFound) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+}
+
+fn http_client(connect: Duration, request: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(connect)
+        .timeout(request)
+        .build()
+        .expect("HTTP client with default TLS settings")
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
\ No newline at end of file
@@ -0,0 +1,79 @@
+use clap::{Parser, Subcommand, ValueEnum};
+
+#[derive(Parser)]
+#[command(version, about = "Todo API server", args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Без подкоманды работает как `serve`
+    #[command(flatten)]
+    pub serve: ServeArgs,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Запустить HTTP-сервер (по умолчанию)
+    Serve(ServeArgs),
+    /// Управление миграциями схемы
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+    /// История todo для TODO_STORE=events
+    #[command(subcommand)]
+    Events(EventsCommand),
+    /// Обслуживание workspace
+    #[command(subcommand)]
+    Workspaces(WorkspacesCommand),
+}
+
+#[derive(clap::Args)]
+pub struct ServeArgs {
+    /// Что делать с миграциями при старте
+    #[arg(long, value_enum, env = "MIGRATE", default_value_t = MigrateMode::Apply)]
+    pub migrate: MigrateMode,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrateMode {
+    /// Не трогать схему
+    Never,
+    /// Не запускаться, если схема отстаёт от сборки
+    Check,
+    /// Накатить недостающие миграции под advisory lock
+    Apply,
+}
+
+#[derive(Subcommand)]
+pub enum MigrateCommand {
+    /// Применить все новые миграции
+    Up,
+    /// Показать применённые и ожидающие миграции
+    Status,
+    /// Откатить миграции
+    Down {
+        /// Откатить всё новее этой версии (по умолчанию - одну последнюю миграцию)
+        #[arg(long)]
+        to: Option<i64>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum EventsCommand {
+    /// Начать историю todo, у которых её ещё нет, с их текущего состояния
+    Seed,
+    /// Пересобрать таблицу todo из снимков и событий
+    Rebuild {
+        /// Только этот workspace
+        #[arg(long)]
+        workspace: Option<i64>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum WorkspacesCommand {
+    /// Назначить владельца workspace без участников (после миграции на workspace там лежат старые todo)
+    Adopt {
+        /// Пользователь, который станет owner
+        #[arg(long, env = "DEFAULT_WORKSPACE_OWNER")]
+        owner: i64,
+    },
+}
\ No newline at end of file
@@ -0,0 +1,125 @@
+//! Клиент HTTP API для бинарника `todo`: разбор аргументов, профили, вывод.
+
+pub mod args;
+pub mod config;
+pub mod output;
+mod commands;
+
+pub use commands::run;
+
+use reqwest::header::{self, HeaderValue};
+use reqwest::{Method, StatusCode};
+use serde_json::Value;
+use std::fmt;
+
+const JSON_PATCH: &str = "application/json-patch+json";
+
+#[derive(Debug)]
+pub enum ClientError {
+    /// Профиль или файл конфигурации не годятся
+    Config(String),
+    /// Сервер недоступен или ответил не по протоколу
+    Http(String),
+    /// Ответ API с ошибкой
+    Api { status: StatusCode, code: Option<String>, message: String },
+    Io(std::io::Error),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Config(message) | ClientError::Http(message) => write!(f, "{message}"),
+            ClientError::Api { status, message, .. } => write!(f, "{message} ({status})"),
+            ClientError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        // у reqwest причина вроде "connection refused" лежит в source
+        let mut message = err.to_string();
+        let mut source = std::error::Error::source(&err);
+        while let Some(cause) = source {
+            message = format!("{message}: {cause}");
+            source = cause.source();
+        }
+        ClientError::Http(message)
+    }
+}
+
+impl From<std::io::Error> for ClientError {
+    fn from(err: std::io::Error) -> Self {
+        ClientError::Io(err)
+    }
+}
+
+/// Тело запроса: обычный JSON или JSON Patch (RFC 6902).
+pub enum Body {
+    Json(Value),
+    Patch(Value),
+}
+
+pub struct Client {
+    http: reqwest::Client,
+    server: String,
+    token: Option<String>,
+    language: Option<String>,
+}
+
+impl Client {
+    pub fn new(server: &str, token: Option<String>) -> Self {
+        Client {
+            http: reqwest::Client::new(),
+            server: server.trim_end_matches('/').to_string(),
+            token,
+            language: None,
+        }
+    }
+
+    /// Язык сообщений об ошибках сервера, уходит в `Accept-Language`.
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+
+    async fn send(&self, method: Method, path: &str, body: Option<Body>) -> Result<reqwest::Response, ClientError> {
+        let mut request = self.http.request(method, format!("{}{path}", self.server));
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(language) = self.language.as_deref().and_then(|language| HeaderValue::from_str(language).ok()) {
+            request = request.header(header::ACCEPT_LANGUAGE, language);
+        }
+        request = match body {
+            Some(Body::Json(body)) => request.header(header::CONTENT_TYPE, "application/json").body(body.to_string()),
+            Some(Body::Patch(body)) => request.header(header::CONTENT_TYPE, JSON_PATCH).body(body.to_string()),
+            None => request,
+        };
+
+        let response = request.send().await?;
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        let status = response.status();
+        let error: Value = serde_json::from_slice(&response.bytes().await?).unwrap_or_default();
+        Err(ClientError::Api {
+            status,
+            code: error["code"].as_str().map(str::to_string),
+            message: error["message"].as_str().unwrap_or(status.canonical_reason().unwrap_or("Request failed")).to_string(),
+        })
+    }
+
+    /// Ответ в JSON; пустой ответ (204) - `null`.
+    pub async fn json(&self, method: Method, path: &str, body: Option<Body>) -> Result<Value, ClientError> {
+        let bytes = self.send(method, path, body).await?.bytes().await?;
+        if bytes.is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_slice(&bytes).map_err(|err| ClientError::Http(format!("Invalid JSON from server: {err}")))
+    }
+
+    pub async fn text(&self, path: &str) -> Result<String, ClientError> {
+        Ok(self.send(Method::GET, path, None).await?.text().await?)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,153 @@
+use crate::client::output::Format;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(name = "todo", version, about = "Manage todos from the terminal")]
+pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Args)]
+pub struct GlobalArgs {
+    /// Файл профилей, см. `client::config`
+    #[arg(long, global = true, env = "TODO_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Профиль из файла конфигурации
+    #[arg(long, short, global = true, env = "TODO_PROFILE")]
+    pub profile: Option<String>,
+    /// Адрес API, например https://todo.example.com
+    #[arg(long, global = true, env = "TODO_SERVER")]
+    pub server: Option<String>,
+    /// API-ключ
+    #[arg(long, global = true, env = "TODO_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+    /// Workspace; если не задан, а workspace у пользователя один - он
+    #[arg(long, short, global = true, env = "TODO_WORKSPACE")]
+    pub workspace: Option<i64>,
+    #[arg(long, short, global = true, value_enum, env = "TODO_OUTPUT")]
+    pub output: Option<Format>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Показать todo, по умолчанию невыполненные
+    #[command(visible_alias = "ls")]
+    List {
+        /// Вместе с выполненными
+        #[arg(long, short)]
+        all: bool,
+        /// Только выполненные
+        #[arg(long, conflicts_with = "all")]
+        done: bool,
+        /// Только с этим тегом, можно повторять
+        #[arg(long, short)]
+        tag: Vec<String>,
+        #[arg(long, value_enum)]
+        priority: Option<PriorityArg>,
+    },
+    /// Добавить todo; срок, #теги и !приоритет разбираются из текста
+    Add {
+        #[arg(required = true, trailing_var_arg = true)]
+        text: Vec<String>,
+        /// Сохранить текст как есть, без разбора
+        #[arg(long)]
+        raw: bool,
+        /// Пояс для `tomorrow 5pm`, по умолчанию из профиля
+        #[arg(long)]
+        time_zone: Option<String>,
+    },
+    /// Отметить todo выполненными
+    Done {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+        /// Снова открыть
+        #[arg(long)]
+        undo: bool,
+    },
+    /// Изменить поля todo
+    Edit(EditArgs),
+    /// Удалить todo
+    #[command(visible_alias = "delete")]
+    Rm {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// Добавить или снять теги
+    Tag {
+        id: i64,
+        /// Теги, `#` в начале можно опустить
+        #[arg(required = true)]
+        tags: Vec<String>,
+        #[arg(long, short)]
+        remove: bool,
+    },
+    /// Выгрузить todo workspace
+    Export {
+        #[arg(long, short, value_enum, default_value_t = ExportFormat::Ics)]
+        format: ExportFormat,
+        /// Записать в файл вместо stdout
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Скрипт автодополнения для shell
+    Completions {
+        shell: Shell,
+    },
+}
+
+#[derive(Args)]
+pub struct EditArgs {
+    pub id: i64,
+    #[arg(long)]
+    pub body: Option<String>,
+    /// Срок в RFC 3339, например 2026-10-22T17:00:00+02:00
+    #[arg(long, conflicts_with = "no_due")]
+    pub due: Option<String>,
+    /// Снять срок
+    #[arg(long)]
+    pub no_due: bool,
+    #[arg(long, value_enum, conflicts_with = "no_priority")]
+    pub priority: Option<PriorityArg>,
+    /// Снять приоритет
+    #[arg(long)]
+    pub no_priority: bool,
+    /// RRULE, например FREQ=WEEKLY;BYDAY=MO
+    #[arg(long, conflicts_with = "no_recurrence")]
+    pub recurrence: Option<String>,
+    /// Снять повторение
+    #[arg(long)]
+    pub no_recurrence: bool,
+    /// Часовой пояс IANA
+    #[arg(long)]
+    pub time_zone: Option<String>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriorityArg {
+    Low,
+    Medium,
+    High,
+}
+
+impl PriorityArg {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PriorityArg::Low => "low",
+            PriorityArg::Medium => "medium",
+            PriorityArg::High => "high",
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// iCalendar, как `/todos.ics`
+    Ics,
+    /// Все поля todo из API
+    Json,
+}
\ No newline at end of file
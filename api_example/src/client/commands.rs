@@ -0,0 +1,183 @@
+use crate::client::args::{Cli, Command, EditArgs, ExportFormat};
+use crate::client::config::{Config, Settings};
+use crate::client::output;
+use crate::client::{Body, Client, ClientError};
+use clap::CommandFactory;
+use reqwest::Method;
+use serde_json::{json, Map, Value};
+use std::io::Write;
+
+/// Выполняет команду и пишет результат в `out`.
+pub async fn run(cli: Cli, out: &mut impl Write) -> Result<(), ClientError> {
+    if let Command::Completions { shell } = cli.command {
+        // clap_complete паникует на ошибке записи, поэтому пишем сами
+        let mut script = Vec::new();
+        clap_complete::generate(shell, &mut Cli::command(), "todo", &mut script);
+        return Ok(out.write_all(&script)?);
+    }
+
+    let settings = Config::load(cli.global.config.as_deref())?.settings(&cli.global)?;
+    let client = Client::new(&settings.server, settings.token.clone()).with_language(language());
+    let todos = format!("/v1/workspaces/{}/todos", workspace(&client, &settings).await?);
+    let format = settings.output;
+
+    match cli.command {
+        Command::List { all, done, tag, priority } => {
+            let list = client.json(Method::GET, &todos, None).await?;
+            let list: Vec<Value> = list
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|todo| all || todo["done"].as_bool() == Some(done))
+                .filter(|todo| tag.iter().all(|tag| has_tag(todo, tag)))
+                .filter(|todo| priority.is_none_or(|priority| todo["priority"] == priority.as_str()))
+                .cloned()
+                .collect();
+            output::todos(out, format, &list)?;
+        }
+        Command::Add { text, raw, time_zone } => {
+            let text = text.join(" ");
+            let todo = if raw {
+                client.json(Method::POST, &todos, Some(Body::Json(json!({ "body": text })))).await?
+            } else {
+                let time_zone = time_zone.or(settings.time_zone);
+                let body = json!({ "text": text, "time_zone": time_zone });
+                client.json(Method::POST, &format!("{todos}/quick"), Some(Body::Json(body))).await?["todo"].take()
+            };
+            output::todo(out, format, &todo)?;
+        }
+        Command::Done { ids, undo } => {
+            let mut updated = Vec::with_capacity(ids.len());
+            for id in ids {
+                let body = Body::Json(json!({ "done": !undo }));
+                updated.push(client.json(Method::PATCH, &format!("{todos}/{id}"), Some(body)).await?);
+            }
+            output::todos(out, format, &updated)?;
+        }
+        Command::Edit(args) => {
+            let id = args.id;
+            let body = Body::Json(Value::Object(changes(args)?));
+            let todo = client.json(Method::PATCH, &format!("{todos}/{id}"), Some(body)).await?;
+            output::todo(out, format, &todo)?;
+        }
+        Command::Rm { ids } => {
+            let mut deleted = Vec::with_capacity(ids.len());
+            for id in ids {
+                client.json(Method::DELETE, &format!("{todos}/{id}"), None).await?;
+                deleted.push(id);
+            }
+            output::deleted(out, format, &deleted)?;
+        }
+        Command::Tag { id, tags, remove } => {
+            let path = format!("{todos}/{id}");
+            let current = client.json(Method::GET, &path, None).await?;
+            let mut updated: Vec<String> = current["tags"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|tag| tag.as_str().map(str::to_string))
+                .collect();
+            for tag in tags.iter().map(|tag| normalize_tag(tag)) {
+                if remove {
+                    updated.retain(|known| *known != tag);
+                } else if !updated.contains(&tag) {
+                    updated.push(tag);
+                }
+            }
+            // `test` по версии: если todo успели изменить, сервер ответит 409, а не затрёт чужие теги
+            let patch = json!([
+                { "op": "test", "path": "/version", "value": current["version"] },
+                { "op": "replace", "path": "/tags", "value": updated },
+            ]);
+            let todo = client.json(Method::PATCH, &path, Some(Body::Patch(patch))).await?;
+            output::todo(out, format, &todo)?;
+        }
+        Command::Export { format: export, file } => {
+            let exported = match export {
+                ExportFormat::Ics => client.text(&format!("{todos}.ics")).await?,
+                ExportFormat::Json => {
+                    let list = client.json(Method::GET, &todos, None).await?;
+                    serde_json::to_string_pretty(&list).expect("JSON value is serializable") + "\n"
+                }
+            };
+            match file {
+                Some(file) => std::fs::write(file, exported)?,
+                None => out.write_all(exported.as_bytes())?,
+            }
+        }
+        Command::Completions { .. } => unreachable!("completions are generated before loading the config"),
+    }
+    Ok(())
+}
+
+/// Workspace из настроек, а если он не задан - единственный workspace пользователя.
+async fn workspace(client: &Client, settings: &Settings) -> Result<i64, ClientError> {
+    if let Some(workspace) = settings.workspace {
+        return Ok(workspace);
+    }
+    let workspaces = client.json(Method::GET, "/v1/workspaces", None).await?;
+    match workspaces.as_array().map(Vec::as_slice).unwrap_or_default() {
+        [workspace] => workspace["id"]
+            .as_i64()
+            .ok_or_else(|| ClientError::Http("Workspace without 'id' from server".to_string())),
+        [] => Err(ClientError::Config("No workspaces yet, create one first".to_string())),
+        several => {
+            let known: Vec<String> = several
+                .iter()
+                .map(|workspace| format!("{} ({})", workspace["id"], workspace["name"].as_str().unwrap_or_default()))
+                .collect();
+            Err(ClientError::Config(format!(
+                "Choose a workspace with --workspace or in the profile: {}",
+                known.join(", ")
+            )))
+        }
+    }
+}
+
+fn changes(args: EditArgs) -> Result<Map<String, Value>, ClientError> {
+    let mut changes = Map::new();
+    if let Some(body) = args.body {
+        changes.insert("body".to_string(), body.into());
+    }
+    if let Some(due) = args.due {
+        changes.insert("due_at".to_string(), due.into());
+    } else if args.no_due {
+        changes.insert("due_at".to_string(), Value::Null);
+    }
+    if let Some(priority) = args.priority {
+        changes.insert("priority".to_string(), priority.as_str().into());
+    } else if args.no_priority {
+        changes.insert("priority".to_string(), Value::Null);
+    }
+    if let Some(recurrence) = args.recurrence {
+        changes.insert("recurrence".to_string(), recurrence.into());
+    } else if args.no_recurrence {
+        changes.insert("recurrence".to_string(), Value::Null);
+    }
+    if let Some(time_zone) = args.time_zone {
+        changes.insert("time_zone".to_string(), time_zone.into());
+    }
+    if changes.is_empty() {
+        return Err(ClientError::Config("Nothing to change, see 'todo edit --help'".to_string()));
+    }
+    Ok(changes)
+}
+
+fn normalize_tag(tag: &str) -> String {
+    tag.trim_start_matches('#').to_lowercase()
+}
+
+fn has_tag(todo: &Value, tag: &str) -> bool {
+    let tag = normalize_tag(tag);
+    todo["tags"].as_array().is_some_and(|tags| tags.iter().any(|known| known.as_str() == Some(tag.as_str())))
+}
+
+/// Язык из локали терминала: `ru_RU.UTF-8` -> `ru`.
+fn language() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|locale| !locale.is_empty())
+        .map(|locale| locale.split(['_', '.', '@']).next().unwrap_or_default().to_string())
+        .filter(|language| !language.is_empty() && language != "C" && language != "POSIX")
+}
\ No newline at end of file
@@ -0,0 +1,102 @@
+//! Профили `todo` в TOML:
+//!
+//! ```toml
+//! default = "work"
+//!
+//! [profiles.work]
+//! server = "https://todo.example.com"
+//! token = "tk_..."
+//! workspace = 3
+//! time_zone = "Europe/Berlin"
+//! output = "table"
+//! ```
+//!
+//! Файл берётся из `--config`/`TODO_CONFIG`, иначе `$XDG_CONFIG_HOME/todo/config.toml`
+//! или `~/.config/todo/config.toml`. Флаги и переменные окружения важнее профиля.
+
+use crate::client::ClientError;
+use crate::client::args::GlobalArgs;
+use crate::client::output::Format;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Сервер по умолчанию совпадает с `BIND_ADDR` по умолчанию у `api_example serve`.
+const DEFAULT_SERVER: &str = "http://127.0.0.1:8000";
+const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Профиль, если не указан `--profile`
+    default: Option<String>,
+    #[serde(default)]
+    profiles: HashMap<String, Profile>,
+}
+
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    server: Option<String>,
+    token: Option<String>,
+    workspace: Option<i64>,
+    /// Пояс для `todo add`, по умолчанию UTC
+    time_zone: Option<String>,
+    output: Option<Format>,
+}
+
+/// Итоговые настройки запуска.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub server: String,
+    pub token: Option<String>,
+    pub workspace: Option<i64>,
+    pub time_zone: Option<String>,
+    pub output: Format,
+}
+
+impl Config {
+    /// Явно указанный файл обязан существовать, файла по умолчанию может и не быть.
+    pub fn load(path: Option<&Path>) -> Result<Config, ClientError> {
+        let (path, required) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Config::default()),
+            },
+        };
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if !required && err.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => return Err(ClientError::Config(format!("Can not read {}: {err}", path.display()))),
+        };
+        toml::from_str(&text).map_err(|err| ClientError::Config(format!("Invalid {}: {err}", path.display())))
+    }
+
+    pub fn settings(&self, args: &GlobalArgs) -> Result<Settings, ClientError> {
+        let name = args.profile.as_deref().or(self.default.as_deref());
+        let profile = match name {
+            Some(name) => self.profiles.get(name).cloned().ok_or_else(|| {
+                let mut known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                known.sort_unstable();
+                ClientError::Config(format!("Unknown profile '{name}', known profiles: {}", known.join(", ")))
+            })?,
+            None => self.profiles.get(DEFAULT_PROFILE).cloned().unwrap_or_default(),
+        };
+        Ok(Settings {
+            server: args.server.clone().or(profile.server).unwrap_or_else(|| DEFAULT_SERVER.to_string()),
+            token: args.token.clone().or(profile.token),
+            workspace: args.workspace.or(profile.workspace),
+            time_zone: profile.time_zone,
+            output: args.output.or(profile.output).unwrap_or_default(),
+        })
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("todo").join("config.toml"))
+}
\ No newline at end of file
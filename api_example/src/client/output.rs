@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::{self, Write};
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// Таблица с заголовком, для чтения глазами
+    #[default]
+    Table,
+    /// Ответ API как есть
+    Json,
+    /// Строка на todo, поля через табуляцию, без заголовка
+    Plain,
+}
+
+const HEADER: [&str; 6] = ["ID", "DONE", "DUE", "PRIORITY", "TAGS", "BODY"];
+
+pub fn todos(out: &mut impl Write, format: Format, todos: &[Value]) -> io::Result<()> {
+    match format {
+        Format::Json => writeln!(out, "{}", pretty(&Value::from(todos))),
+        Format::Plain => {
+            for todo in todos {
+                writeln!(out, "{}", columns(todo).join("\t"))?;
+            }
+            Ok(())
+        }
+        Format::Table => {
+            let rows: Vec<[String; 6]> = todos.iter().map(columns).collect();
+            let mut widths = HEADER.map(|title| title.chars().count());
+            for row in &rows {
+                for (width, cell) in widths.iter_mut().zip(row) {
+                    *width = (*width).max(cell.chars().count());
+                }
+            }
+            let header = HEADER.map(str::to_string);
+            for row in std::iter::once(&header).chain(&rows) {
+                let line: Vec<String> = row.iter().zip(widths).map(|(cell, width)| format!("{cell:<width$}")).collect();
+                writeln!(out, "{}", line.join("  ").trim_end())?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Один todo: для JSON - объект, а не массив.
+pub fn todo(out: &mut impl Write, format: Format, todo: &Value) -> io::Result<()> {
+    match format {
+        Format::Json => writeln!(out, "{}", pretty(todo)),
+        _ => todos(out, format, std::slice::from_ref(todo)),
+    }
+}
+
+/// Удалённые todo: для JSON - массив id, иначе строка на каждый.
+pub fn deleted(out: &mut impl Write, format: Format, ids: &[i64]) -> io::Result<()> {
+    match format {
+        Format::Json => writeln!(out, "{}", pretty(&Value::from(ids))),
+        Format::Plain => ids.iter().try_for_each(|id| writeln!(out, "{id}")),
+        Format::Table => ids.iter().try_for_each(|id| writeln!(out, "Deleted {id}")),
+    }
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).expect("JSON value is serializable")
+}
+
+fn columns(todo: &Value) -> [String; 6] {
+    let text = |field: &str| todo[field].as_str().unwrap_or_default().to_string();
+    let tags: Vec<&str> = todo["tags"].as_array().into_iter().flatten().filter_map(Value::as_str).collect();
+    [
+        todo["id"].to_string(),
+        if todo["done"].as_bool() == Some(true) { "x" } else { "" }.to_string(),
+        due(todo),
+        text("priority"),
+        tags.join(","),
+        text("body"),
+    ]
+}
+
+/// Срок в часовом поясе самого todo, как его видел автор.
+fn due(todo: &Value) -> String {
+    let Some(due_at) = todo["due_at"].as_str().and_then(|due_at| due_at.parse::<DateTime<Utc>>().ok()) else {
+        return String::new();
+    };
+    let time_zone = todo["time_zone"].as_str().and_then(|name| name.parse::<Tz>().ok()).unwrap_or(Tz::UTC);
+    due_at.with_timezone(&time_zone).format("%Y-%m-%d %H:%M").to_string()
+}
\ No newline at end of file
@@ -1 +1,9 @@
-pub mod todo;
\ No newline at end of file
+pub mod api_key;
+pub mod attachment;
+pub mod comment;
+pub mod fields;
+pub mod health;
+pub mod stats;
+pub mod sync;
+pub mod todo;
+pub mod workspace;
\ No newline at end of file
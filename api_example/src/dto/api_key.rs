@@ -0,0 +1,92 @@
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::error::Error;
+use crate::i18n::Key;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "api_key_kind", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ApiKeyKind {
+    Personal,
+    Service,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "api_key_scope")]
+pub enum Scope {
+    #[sqlx(rename = "todos:read")]
+    #[serde(rename = "todos:read")]
+    TodosRead,
+    #[sqlx(rename = "todos:write")]
+    #[serde(rename = "todos:write")]
+    TodosWrite,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 2] = [Scope::TodosRead, Scope::TodosWrite];
+}
+
+#[derive(Serialize, Clone, sqlx::FromRow, ToSchema)]
+pub struct ApiKey {
+    id: i64,
+    kind: ApiKeyKind,
+    workspace_id: Option<i64>,
+    name: String,
+    /// Первые символы ключа, чтобы его можно было узнать в списке
+    prefix: String,
+    scopes: Vec<Scope>,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+/// Ответ на создание ключа: `token` показывается только один раз.
+#[derive(Serialize, ToSchema)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub(crate) key: ApiKey,
+    pub(crate) token: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateApiKey {
+    pub(crate) name: String,
+    /// Если указан, создаётся сервисный ключ этого workspace
+    pub(crate) workspace_id: Option<i64>,
+    pub(crate) scopes: Vec<Scope>,
+    pub(crate) expires_at: Option<DateTime<Utc>>,
+}
+
+impl CreateApiKey {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.name.trim().is_empty() {
+            return Err(Error::Validation(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Key::NameEmpty.into(),
+            ));
+        }
+        if self.scopes.is_empty() {
+            return Err(Error::Validation(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Key::ScopesEmpty.into(),
+            ));
+        }
+        if self.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+            return Err(Error::Validation(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Key::ExpiryInPast.into(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn kind(&self) -> ApiKeyKind {
+        match self.workspace_id {
+            Some(_) => ApiKeyKind::Service,
+            None => ApiKeyKind::Personal,
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, Clone, sqlx::FromRow, ToSchema)]
+pub struct Attachment {
+    id: i64,
+    todo_id: i64,
+    file_name: String,
+    /// Определяется по содержимому файла
+    content_type: String,
+    size_bytes: i64,
+    created_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub(crate) storage_key: String,
+}
+
+impl Attachment {
+    pub fn todo_id(&self) -> i64 {
+        self.todo_id
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    pub fn size_bytes(&self) -> i64 {
+        self.size_bytes
+    }
+}
+
+/// Тело `multipart/form-data` для загрузки вложения.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct AttachmentUpload {
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+}
+
+pub struct NewAttachment {
+    pub(crate) file_name: String,
+    pub(crate) content_type: String,
+    pub(crate) size_bytes: i64,
+    pub(crate) storage_key: String,
+}
\ No newline at end of file
@@ -0,0 +1,110 @@
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use crate::error::Error;
+use crate::i18n::{Key, Message};
+
+pub const MAX_BODY_CHARS: usize = 10_000;
+pub const DEFAULT_PAGE_LIMIT: i64 = 50;
+pub const MAX_PAGE_LIMIT: i64 = 200;
+
+#[derive(Serialize, Clone, sqlx::FromRow, ToSchema)]
+pub struct Comment {
+    id: i64,
+    todo_id: i64,
+    author_id: i64,
+    body: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl Comment {
+    pub fn todo_id(&self) -> i64 {
+        self.todo_id
+    }
+
+    pub fn author_id(&self) -> i64 {
+        self.author_id
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CommentPage {
+    items: Vec<Comment>,
+    /// Передаётся в `after` для следующей страницы, `null` на последней
+    next_after: Option<i64>,
+}
+
+impl CommentPage {
+    pub fn new(mut items: Vec<Comment>, limit: i64) -> Self {
+        // repo читает на одну запись больше, чтобы узнать, есть ли следующая страница
+        let next_after = if items.len() as i64 > limit {
+            items.truncate(limit as usize);
+            items.last().map(|comment| comment.id)
+        } else {
+            None
+        };
+        CommentPage { items, next_after }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CommentQuery {
+    /// Id последнего комментария предыдущей страницы
+    pub(crate) after: Option<i64>,
+    /// По умолчанию 50, не больше 200
+    pub(crate) limit: Option<i64>,
+}
+
+impl CommentQuery {
+    pub fn limit(&self) -> Result<i64, Error> {
+        match self.limit {
+            None => Ok(DEFAULT_PAGE_LIMIT),
+            Some(limit) if (1..=MAX_PAGE_LIMIT).contains(&limit) => Ok(limit),
+            Some(_) => Err(Error::Validation(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Message::new(Key::LimitOutOfRange).arg("max", MAX_PAGE_LIMIT),
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateComment {
+    pub(crate) body: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateComment {
+    pub(crate) body: String,
+}
+
+impl CreateComment {
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_body(&self.body)
+    }
+}
+
+impl UpdateComment {
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_body(&self.body)
+    }
+}
+
+fn validate_body(body: &str) -> Result<(), Error> {
+    if body.trim().is_empty() {
+        return Err(Error::Validation(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Key::CommentEmpty.into(),
+        ));
+    }
+    if body.chars().count() > MAX_BODY_CHARS {
+        return Err(Error::Validation(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Message::new(Key::CommentTooLong).arg("max", MAX_BODY_CHARS),
+        ));
+    }
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,158 @@
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sqlx::postgres::PgRow;
+use sqlx::Row;
+use utoipa::IntoParams;
+use crate::dto::todo::Priority;
+use crate::error::Error;
+use crate::i18n::{Key, Message};
+
+/// Todo только с запрошенными полями и встроенными связанными данными.
+pub type PartialTodo = Map<String, Value>;
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Int,
+    OptionalInt,
+    Text,
+    OptionalText,
+    Bool,
+    TextList,
+    OptionalPriority,
+    Time,
+    OptionalTime,
+}
+
+/// Поля `Todo`; имена совпадают с колонками таблицы `todo`.
+const FIELDS: [(&str, Kind); 17] = [
+    ("id", Kind::Int),
+    ("workspace_id", Kind::Int),
+    ("body", Kind::Text),
+    ("done", Kind::Bool),
+    ("comment_count", Kind::Int),
+    ("position", Kind::Text),
+    ("due_at", Kind::OptionalTime),
+    ("recurrence", Kind::OptionalText),
+    ("time_zone", Kind::OptionalText),
+    ("next_todo_id", Kind::OptionalInt),
+    ("completed_at", Kind::OptionalTime),
+    ("ical_uid", Kind::OptionalText),
+    ("tags", Kind::TextList),
+    ("priority", Kind::OptionalPriority),
+    ("version", Kind::Int),
+    ("created_at", Kind::Time),
+    ("updated_at", Kind::Time),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Include {
+    /// Первая страница комментариев, остальные через `GET /v1/todos/{id}/comments`
+    Comments,
+    Attachments,
+}
+
+impl Include {
+    const ALL: [(&str, Include); 2] = [("comments", Include::Comments), ("attachments", Include::Attachments)];
+
+    pub fn name(self) -> &'static str {
+        Include::ALL.iter().find(|(_, include)| *include == self).map(|(name, _)| *name).expect("listed in ALL")
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FieldsQuery {
+    /// Поля через запятую, например `id,body,done`; по умолчанию все
+    pub(crate) fields: Option<String>,
+    /// Связанные данные через запятую: `comments`, `attachments`
+    pub(crate) include: Option<String>,
+}
+
+/// Какие поля выбирать из базы и что встраивать в ответ.
+pub struct Projection {
+    fields: Vec<(&'static str, Kind)>,
+    include: Vec<Include>,
+}
+
+impl FieldsQuery {
+    /// `None`, если клиент не просил ни `fields`, ни `include`: тогда отдаётся `Todo` целиком.
+    pub fn projection(&self) -> Result<Option<Projection>, Error> {
+        if self.fields.is_none() && self.include.is_none() {
+            return Ok(None);
+        }
+        let fields = match &self.fields {
+            Some(fields) => parse(fields, &FIELDS, "field")?,
+            None => FIELDS.to_vec(),
+        };
+        let include = match &self.include {
+            Some(include) => parse(include, &Include::ALL, "include")?.into_iter().map(|(_, include)| include).collect(),
+            None => Vec::new(),
+        };
+        Ok(Some(Projection { fields, include }))
+    }
+}
+
+fn parse<T: Copy>(list: &str, known: &[(&'static str, T)], what: &str) -> Result<Vec<(&'static str, T)>, Error> {
+    let mut parsed: Vec<(&'static str, T)> = Vec::new();
+    for name in list.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        let Some(item) = known.iter().find(|(known, _)| *known == name) else {
+            let valid: Vec<&str> = known.iter().map(|(name, _)| *name).collect();
+            return Err(Error::Validation(
+                StatusCode::BAD_REQUEST,
+                Message::new(Key::UnknownName).arg("what", what).arg("name", name).arg("valid", valid.join(", ")),
+            ));
+        };
+        if !parsed.iter().any(|(name, _)| *name == item.0) {
+            parsed.push(*item);
+        }
+    }
+    if parsed.is_empty() {
+        return Err(Error::Validation(StatusCode::BAD_REQUEST, Message::new(Key::NothingSelected).arg("what", what)));
+    }
+    Ok(parsed)
+}
+
+impl Projection {
+    /// Колонки для SELECT; `id` выбирается всегда, по нему встраиваются связанные данные.
+    pub fn columns(&self) -> String {
+        let mut columns = vec!["id"];
+        columns.extend(self.fields.iter().map(|(name, _)| *name).filter(|name| *name != "id"));
+        columns.join(", ")
+    }
+
+    pub fn include(&self) -> &[Include] {
+        &self.include
+    }
+
+    /// Ответ с другим набором полей - другое представление, и ETag у него свой.
+    pub fn etag(&self, etag: &str) -> String {
+        let fields: Vec<&str> = self.fields.iter().map(|(name, _)| *name).collect();
+        format!("{}-{}\"", etag.trim_end_matches('"'), fields.join("."))
+    }
+
+    pub fn to_json(&self, row: &PgRow) -> Result<PartialTodo, sqlx::Error> {
+        let mut todo = Map::new();
+        for (name, kind) in &self.fields {
+            let value = match kind {
+                Kind::Int => Value::from(row.try_get::<i64, _>(*name)?),
+                Kind::OptionalInt => Value::from(row.try_get::<Option<i64>, _>(*name)?),
+                Kind::Text => Value::from(row.try_get::<String, _>(*name)?),
+                Kind::OptionalText => Value::from(row.try_get::<Option<String>, _>(*name)?),
+                Kind::Bool => Value::from(row.try_get::<bool, _>(*name)?),
+                Kind::TextList => Value::from(row.try_get::<Vec<String>, _>(*name)?),
+                Kind::OptionalPriority => {
+                    serde_json::to_value(row.try_get::<Option<Priority>, _>(*name)?).expect("priority is serializable")
+                }
+                // как у `Todo`: формат chrono, а не текст из Postgres
+                Kind::Time => serde_json::to_value(row.try_get::<DateTime<Utc>, _>(*name)?).expect("time is serializable"),
+                Kind::OptionalTime => {
+                    serde_json::to_value(row.try_get::<Option<DateTime<Utc>>, _>(*name)?).expect("time is serializable")
+                }
+            };
+            todo.insert(name.to_string(), value);
+        }
+        Ok(todo)
+    }
+}
\ No newline at end of file
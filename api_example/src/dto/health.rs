@@ -0,0 +1,210 @@
+use serde::Serialize;
+use sqlx::PgPool;
+use std::time::Duration;
+use crate::repo::migrations::{self, State, Status};
+
+/// Порядок важен: общее состояние - худшее из состояний проверок.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Health {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+/// Пороги времени ответа зависимости: `HEALTH_DEGRADED_MS` (100) и `HEALTH_UNHEALTHY_MS` (1000).
+/// Проверка, не уложившаяся во второй, прерывается.
+#[derive(Clone, Debug)]
+pub struct Thresholds {
+    pub degraded: Duration,
+    pub unhealthy: Duration,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds { degraded: Duration::from_millis(100), unhealthy: Duration::from_millis(1000) }
+    }
+}
+
+impl Thresholds {
+    pub fn from_env() -> Self {
+        let millis = |name: &str| std::env::var(name).ok().and_then(|ms| ms.parse().ok()).map(Duration::from_millis);
+        let default = Thresholds::default();
+        Thresholds {
+            degraded: millis("HEALTH_DEGRADED_MS").unwrap_or(default.degraded),
+            unhealthy: millis("HEALTH_UNHEALTHY_MS").unwrap_or(default.unhealthy),
+        }
+    }
+
+    fn classify(&self, latency: Duration) -> Health {
+        if latency >= self.unhealthy {
+            Health::Unhealthy
+        } else if latency >= self.degraded {
+            Health::Degraded
+        } else {
+            Health::Healthy
+        }
+    }
+}
+
+/// Почему зависимость не ответила. `/health/details` открыт без авторизации, а текст ошибки
+/// sqlx выдаёт хосты, порты и имена, поэтому он пишется только в лог.
+#[derive(Debug)]
+pub enum Failure {
+    Timeout(Duration),
+    Error(String),
+}
+
+impl Failure {
+    fn public(self, check: &'static str) -> String {
+        match self {
+            Failure::Timeout(timeout) => format!("No response in {} ms", timeout.as_millis()),
+            Failure::Error(error) => {
+                tracing::warn!(check, error, "health check failed");
+                "Check failed, see server logs".to_string()
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct PoolStats {
+    size: u32,
+    idle: usize,
+    max_connections: u32,
+}
+
+impl PoolStats {
+    /// Свободных соединений нет и новых пул уже не откроет.
+    fn exhausted(&self) -> bool {
+        self.idle == 0 && self.size >= self.max_connections
+    }
+}
+
+#[derive(Serialize)]
+pub struct Migrations {
+    /// Последняя применённая к базе
+    applied: Option<i64>,
+    /// Последняя в этой сборке
+    expected: Option<i64>,
+    pending: Vec<i64>,
+    modified: Vec<i64>,
+}
+
+#[derive(Serialize)]
+pub struct Check {
+    name: &'static str,
+    status: Health,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pool: Option<PoolStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    migrations: Option<Migrations>,
+}
+
+impl Check {
+    fn new(name: &'static str, status: Health) -> Self {
+        Check { name, status, latency_ms: None, error: None, pool: None, migrations: None }
+    }
+
+    /// Время запроса к базе вместе с ожиданием соединения из пула.
+    pub fn database(name: &'static str, thresholds: &Thresholds, pool: &PgPool, latency: Result<Duration, Failure>) -> Self {
+        let stats = PoolStats {
+            size: pool.size(),
+            idle: pool.num_idle(),
+            max_connections: pool.options().get_max_connections(),
+        };
+        let mut check = match latency {
+            Ok(latency) => {
+                let mut check = Check::new(name, thresholds.classify(latency));
+                check.latency_ms = Some(latency.as_secs_f64() * 1000.0);
+                check
+            }
+            Err(failure) => {
+                let mut check = Check::new(name, Health::Unhealthy);
+                check.error = Some(failure.public(name));
+                check
+            }
+        };
+        if stats.exhausted() {
+            check.status = check.status.max(Health::Degraded);
+        }
+        check.pool = Some(stats);
+        check
+    }
+
+    /// Схема отстаёт от сборки - unhealthy. Миграции новее сборки допустимы, см. `migrations::check`.
+    pub fn migrations(statuses: Result<Vec<Status>, Failure>) -> Self {
+        let statuses = match statuses {
+            Ok(statuses) => statuses,
+            Err(failure) => {
+                let mut check = Check::new("migrations", Health::Unhealthy);
+                check.error = Some(failure.public("migrations"));
+                return check;
+            }
+        };
+        let versions = |state: State| {
+            statuses.iter().filter(|status| status.state == state).map(|status| status.version).collect::<Vec<_>>()
+        };
+        let migrations = Migrations {
+            applied: statuses
+                .iter()
+                .filter(|status| status.state != State::Pending)
+                .map(|status| status.version)
+                .max(),
+            expected: migrations::latest(),
+            pending: versions(State::Pending),
+            modified: versions(State::Modified),
+        };
+        let healthy = migrations.pending.is_empty() && migrations.modified.is_empty();
+        let mut check = Check::new("migrations", if healthy { Health::Healthy } else { Health::Unhealthy });
+        check.migrations = Some(migrations);
+        check
+    }
+
+    /// Без этой зависимости сервис работает, но хуже: например, чтения уходят с реплики в primary.
+    pub fn optional(mut self) -> Self {
+        self.status = self.status.min(Health::Degraded);
+        self
+    }
+}
+
+#[derive(Serialize)]
+pub struct Build {
+    version: &'static str,
+    /// `GIT_COMMIT` на момент сборки
+    commit: Option<&'static str>,
+    profile: &'static str,
+}
+
+impl Build {
+    pub fn current() -> Self {
+        Build {
+            version: env!("CARGO_PKG_VERSION"),
+            commit: option_env!("GIT_COMMIT"),
+            profile: if cfg!(debug_assertions) { "debug" } else { "release" },
+        }
+    }
+}
+
+/// Ответ `GET /health/details`.
+#[derive(Serialize)]
+pub struct HealthReport {
+    status: Health,
+    checks: Vec<Check>,
+    build: Build,
+}
+
+impl HealthReport {
+    pub fn new(checks: Vec<Check>) -> Self {
+        let status = checks.iter().map(|check| check.status).max().unwrap_or(Health::Healthy);
+        HealthReport { status, checks, build: Build::current() }
+    }
+
+    pub fn status(&self) -> Health {
+        self.status
+    }
+}
\ No newline at end of file
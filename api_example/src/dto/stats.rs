@@ -0,0 +1,122 @@
+use axum::http::StatusCode;
+use chrono::{Datelike, Days, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use crate::error::Error;
+use crate::i18n::{Key, Message};
+
+pub const MAX_BUCKETS: u64 = 400;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Bucket {
+    Day,
+    Week,
+}
+
+impl Bucket {
+    /// Единица для `date_trunc` в SQL.
+    pub fn unit(self) -> &'static str {
+        match self {
+            Bucket::Day => "day",
+            Bucket::Week => "week",
+        }
+    }
+
+    fn days(self) -> u64 {
+        match self {
+            Bucket::Day => 1,
+            Bucket::Week => 7,
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatsQuery {
+    /// Первый день диапазона, по умолчанию 12 интервалов до `to`
+    pub(crate) from: Option<NaiveDate>,
+    /// Последний день диапазона включительно, по умолчанию сегодня
+    pub(crate) to: Option<NaiveDate>,
+    /// `day` или `week` (по умолчанию), недели начинаются с понедельника
+    pub(crate) bucket: Option<Bucket>,
+    /// Часовой пояс IANA, в котором считаются дни, по умолчанию UTC
+    #[param(value_type = Option<String>)]
+    pub(crate) time_zone: Option<Tz>,
+}
+
+/// Диапазон, выровненный по границам интервалов.
+pub struct StatsRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub bucket: Bucket,
+    pub tz: Tz,
+}
+
+impl StatsQuery {
+    pub fn range(&self) -> Result<StatsRange, Error> {
+        let bucket = self.bucket.unwrap_or(Bucket::Week);
+        let tz = self.time_zone.unwrap_or(Tz::UTC);
+        let to = self.to.unwrap_or_else(|| Utc::now().with_timezone(&tz).date_naive());
+        let from = self.from.unwrap_or_else(|| to - Days::new(bucket.days() * 11));
+        if from > to {
+            return Err(Error::Validation(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Key::RangeReversed.into(),
+            ));
+        }
+
+        let (from, to) = match bucket {
+            Bucket::Day => (from, to),
+            Bucket::Week => (
+                from - Days::new(from.weekday().num_days_from_monday().into()),
+                to + Days::new((6 - to.weekday().num_days_from_monday()).into()),
+            ),
+        };
+        if (to - from).num_days() as u64 / bucket.days() >= MAX_BUCKETS {
+            return Err(Error::Validation(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Message::new(Key::RangeTooLong).arg("max", MAX_BUCKETS),
+            ));
+        }
+        Ok(StatsRange { from, to, bucket, tz })
+    }
+}
+
+#[derive(Serialize, Clone, sqlx::FromRow, ToSchema)]
+pub struct Totals {
+    total: i64,
+    open: i64,
+    done: i64,
+}
+
+impl Totals {
+    /// Доля выполненных, `None` для пустого workspace.
+    pub fn completion_rate(&self) -> Option<f64> {
+        (self.total > 0).then(|| self.done as f64 / self.total as f64)
+    }
+}
+
+#[derive(Serialize, Clone, sqlx::FromRow, ToSchema)]
+pub struct HistogramBucket {
+    /// Первый день интервала
+    start: NaiveDate,
+    created: i64,
+    completed: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct Stats {
+    #[serde(flatten)]
+    pub(crate) totals: Totals,
+    pub(crate) completion_rate: Option<f64>,
+    /// Медиана от `created_at` до `completed_at` у todo, выполненных в диапазоне
+    pub(crate) median_time_to_done_secs: Option<f64>,
+    pub(crate) from: NaiveDate,
+    pub(crate) to: NaiveDate,
+    pub(crate) bucket: Bucket,
+    #[schema(value_type = String)]
+    pub(crate) time_zone: String,
+    pub(crate) histogram: Vec<HistogramBucket>,
+}
\ No newline at end of file
@@ -0,0 +1,136 @@
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use crate::dto::todo::{nullable, Todo};
+use crate::error::Error;
+use crate::i18n::{Key, Locale, Message};
+
+pub const MAX_CHANGES: usize = 500;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SyncQuery {
+    /// `token` из предыдущего ответа; без него отдаются все todo
+    pub(crate) since: Option<String>,
+}
+
+impl SyncQuery {
+    pub fn since(&self) -> Result<Option<u64>, Error> {
+        self.since
+            .as_deref()
+            .map(|since| {
+                since.parse().map_err(|_| {
+                    Error::Validation(StatusCode::UNPROCESSABLE_ENTITY, Key::SinceInvalid.into())
+                })
+            })
+            .transpose()
+    }
+}
+
+#[derive(Serialize, Clone, sqlx::FromRow, ToSchema)]
+pub struct Tombstone {
+    id: i64,
+    deleted_at: DateTime<Utc>,
+}
+
+/// Изменения с момента `since`. Одно и то же изменение может прийти дважды,
+/// клиент применяет их по `id` и `version`.
+#[derive(Serialize, ToSchema)]
+pub struct SyncChanges {
+    pub(crate) todos: Vec<Todo>,
+    /// Удалённые todo, среди них бывают и те, которых клиент не видел
+    pub(crate) deleted: Vec<Tombstone>,
+    /// Передаётся в `since` при следующей синхронизации
+    pub(crate) token: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PushChanges {
+    pub(crate) changes: Vec<Change>,
+}
+
+/// Изменение с клиента: без `id` создаёт todo, с `deleted: true` удаляет его.
+#[derive(Deserialize, ToSchema)]
+pub struct Change {
+    pub(crate) id: Option<i64>,
+    /// Метка клиента для нового todo, возвращается в результате
+    pub(crate) client_id: Option<String>,
+    /// `version` todo, от которой клиент делал изменение
+    pub(crate) base_version: Option<i64>,
+    /// Когда изменение сделано на клиенте; при конфликте выигрывает более позднее
+    pub(crate) updated_at: DateTime<Utc>,
+    #[serde(default)]
+    pub(crate) deleted: bool,
+    pub(crate) body: Option<String>,
+    pub(crate) done: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<DateTime<Utc>>)]
+    pub(crate) due_at: Option<Option<DateTime<Utc>>>,
+}
+
+impl PushChanges {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.changes.len() > MAX_CHANGES {
+            return Err(Error::Validation(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Message::new(Key::TooManyChanges).arg("max", MAX_CHANGES),
+            ));
+        }
+        if self.changes.iter().any(|change| change.id.is_none() && (change.deleted || change.body.is_none())) {
+            return Err(Error::Validation(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Key::NewTodoIncomplete.into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Created,
+    Updated,
+    Deleted,
+    /// Изменение на сервере новее или само изменение невалидно (см. `error`),
+    /// в `todo` лежит состояние сервера
+    Rejected,
+    /// Todo уже удалён на сервере
+    Gone,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ChangeResult {
+    pub(crate) id: Option<i64>,
+    pub(crate) client_id: Option<String>,
+    pub(crate) outcome: Outcome,
+    /// Todo изменился на сервере после `base_version`
+    pub(crate) conflict: bool,
+    pub(crate) todo: Option<Todo>,
+    /// Почему изменение не прошло проверку; остальные изменения пакета применяются
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<ChangeError>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ChangeError {
+    /// Код сообщения из каталога `i18n`
+    #[schema(value_type = String)]
+    code: Key,
+    message: String,
+}
+
+impl From<Message> for ChangeError {
+    fn from(message: Message) -> Self {
+        ChangeError {
+            code: message.key(),
+            message: message.render(Locale::current()),
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PushResult {
+    pub(crate) results: Vec<ChangeResult>,
+}
\ No newline at end of file
@@ -1,37 +1,472 @@
 use axum::http::StatusCode;
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use chrono::{DateTime, FixedOffset, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use utoipa::{IntoParams, ToSchema};
 use crate::error::Error;
+use crate::i18n::{Key, Message};
+use crate::quick_add::{self, Recognised};
+use crate::recurrence::{Rule, Schedule};
+
+pub const DEFAULT_OCCURRENCES: usize = 10;
+pub const MAX_OCCURRENCES: usize = 100;
+pub const MERGE_PATCH: &str = "application/merge-patch+json";
+pub const JSON_PATCH: &str = "application/json-patch+json";
+pub const MAX_TAGS: usize = 20;
+pub const MAX_TAG_LEN: usize = 50;
+/// Поля todo, которые меняются через `PATCH`, остальные только читаются
+const EDITABLE: [&str; 7] = ["body", "done", "due_at", "recurrence", "time_zone", "tags", "priority"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "todo_priority", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
 
 #[derive(Serialize, Clone, sqlx::FromRow, ToSchema)]
 pub struct Todo {
     id: i64,
+    workspace_id: i64,
     body: String,
     done: bool,
+    comment_count: i64,
+    /// Ранг ручной сортировки, см. `rank`
+    position: String,
+    due_at: Option<DateTime<Utc>>,
+    /// RRULE, см. `recurrence::Rule`
+    recurrence: Option<String>,
+    /// Часовой пояс IANA для повторений, `null` означает UTC
+    time_zone: Option<String>,
+    /// Следующее вхождение, созданное при выполнении этого todo
+    next_todo_id: Option<i64>,
+    /// Когда todo был отмечен выполненным, `null` для открытых
+    completed_at: Option<DateTime<Utc>>,
+    /// UID из CalDAV-клиента, `null` для todo, созданных через API
+    ical_uid: Option<String>,
+    tags: Vec<String>,
+    priority: Option<Priority>,
+    /// Растёт с каждым изменением, см. `POST /v1/sync`
+    version: i64,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
 
+impl Todo {
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn done(&self) -> bool {
+        self.done
+    }
+
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    pub fn due_at(&self) -> Option<DateTime<Utc>> {
+        self.due_at
+    }
+
+    pub fn position(&self) -> &str {
+        &self.position
+    }
+
+    pub fn workspace_id(&self) -> i64 {
+        self.workspace_id
+    }
+
+    pub fn next_todo_id(&self) -> Option<i64> {
+        self.next_todo_id
+    }
+
+    pub fn completed_at(&self) -> Option<DateTime<Utc>> {
+        self.completed_at
+    }
+
+    pub fn ical_uid(&self) -> Option<&str> {
+        self.ical_uid.as_deref()
+    }
+
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn priority(&self) -> Option<Priority> {
+        self.priority
+    }
+
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    /// Как хранится: `None`, если пояс не задавали.
+    pub fn time_zone_name(&self) -> Option<&str> {
+        self.time_zone.as_deref()
+    }
+
+    pub fn recurrence(&self) -> Option<&str> {
+        self.recurrence.as_deref()
+    }
+
+    pub fn time_zone(&self) -> Tz {
+        self.time_zone.as_deref().and_then(|name| name.parse().ok()).unwrap_or(Tz::UTC)
+    }
+
+    pub fn rule(&self) -> Option<Rule> {
+        self.recurrence.as_deref().and_then(|rule| rule.parse().ok())
+    }
+
+    pub fn schedule(&self) -> Option<Schedule> {
+        Some(Schedule {
+            rule: self.rule()?,
+            dtstart: self.due_at?,
+            tz: self.time_zone(),
+        })
+    }
+}
+
+/// Состояние списка, из которого строится ETag для `GET /v1/todos`.
+#[derive(Clone, sqlx::FromRow)]
+pub struct ListVersion {
+    pub(crate) count: i64,
+    pub(crate) updated_at: Option<DateTime<Utc>>,
+    /// Комментарии не трогают `updated_at`, но меняют `comment_count` в списке
+    pub(crate) comments: i64,
+}
+
+impl ListVersion {
+    pub fn etag(&self, workspace_id: i64) -> String {
+        let updated_at = self.updated_at.map_or(0, |updated_at| updated_at.timestamp_micros());
+        format!("W/\"{workspace_id}-{}-{updated_at}-{}\"", self.count, self.comments)
+    }
+}
+
 #[derive(Deserialize, ToSchema)]
 pub struct CreateTodo {
     pub(crate) body: String,
+    /// Обязателен для повторений; если не задан, берётся ближайшее вхождение
+    pub(crate) due_at: Option<DateTime<Utc>>,
+    /// RRULE или сокращение: `daily`, `weekly:mo,th`, `monthly:15`, `monthly:-1`
+    #[schema(value_type = Option<String>, example = "FREQ=WEEKLY;BYDAY=MO,TH")]
+    pub(crate) recurrence: Option<Rule>,
+    #[schema(value_type = Option<String>, example = "Europe/Berlin")]
+    pub(crate) time_zone: Option<Tz>,
+    #[serde(default)]
+    pub(crate) tags: Vec<String>,
+    pub(crate) priority: Option<Priority>,
+    /// Только для todo из CalDAV
+    #[serde(skip)]
+    pub(crate) ical_uid: Option<String>,
+    /// Todo из CalDAV может прийти уже выполненным
+    #[serde(skip)]
+    pub(crate) completed_at: Option<DateTime<Utc>>,
+}
+
+/// Тело `POST /v1/todos/quick`: срок, теги и приоритет разбираются из текста, см. `quick_add`.
+#[derive(Deserialize, ToSchema)]
+pub struct QuickAdd {
+    #[schema(example = "pay invoice tomorrow 5pm #finance !high")]
+    pub(crate) text: String,
+    /// Пояс, в котором понимаются `tomorrow` и `5pm`; сохраняется в todo, по умолчанию UTC
+    #[schema(value_type = Option<String>, example = "Europe/Berlin")]
+    pub(crate) time_zone: Option<Tz>,
+}
+
+impl QuickAdd {
+    pub fn parse(self, now: DateTime<Utc>) -> Result<(CreateTodo, Vec<Recognised>), Error> {
+        let parsed = quick_add::parse(&self.text, now.with_timezone(&self.time_zone.unwrap_or(Tz::UTC)));
+        if parsed.body.is_empty() {
+            return Err(Error::Validation(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Message::new(Key::QuickAddEmpty).arg("text", &self.text),
+            ));
+        }
+        let new_todo = CreateTodo {
+            body: parsed.body,
+            due_at: parsed.due_at,
+            recurrence: None,
+            time_zone: self.time_zone,
+            tags: parsed.tags,
+            priority: parsed.priority,
+            ical_uid: None,
+            completed_at: None,
+        };
+        Ok((new_todo, parsed.recognised))
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct QuickAdded {
+    todo: Todo,
+    /// Что было распознано в тексте, в порядке появления
+    recognised: Vec<Recognised>,
+}
+
+impl QuickAdded {
+    pub fn new(todo: Todo, recognised: Vec<Recognised>) -> Self {
+        QuickAdded { todo, recognised }
+    }
 }
 
+/// Куда переставить todo: ровно одно из полей, id соседа в том же workspace.
+#[derive(Deserialize, ToSchema)]
+pub struct MoveTodo {
+    pub(crate) before: Option<i64>,
+    pub(crate) after: Option<i64>,
+}
+
+#[derive(Clone, Copy)]
+pub enum Placement {
+    Before(i64),
+    After(i64),
+}
+
+impl MoveTodo {
+    pub fn placement(&self, id: i64) -> Result<Placement, Error> {
+        let placement = match (self.before, self.after) {
+            (Some(before), None) => Placement::Before(before),
+            (None, Some(after)) => Placement::After(after),
+            _ => {
+                return Err(Error::Validation(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    Key::MoveAnchorAmbiguous.into(),
+                ));
+            }
+        };
+        if self.before == Some(id) || self.after == Some(id) {
+            return Err(Error::Validation(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Key::MoveToItself.into(),
+            ));
+        }
+        Ok(placement)
+    }
+}
+
+/// Поля `due_at` и `recurrence` можно сбросить, передав `null`.
 #[derive(Deserialize, ToSchema)]
 pub struct UpdateTodo {
     pub(crate) body: Option<String>,
     pub(crate) done: Option<bool>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<DateTime<Utc>>)]
+    pub(crate) due_at: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>, example = "FREQ=WEEKLY;BYDAY=MO,TH")]
+    pub(crate) recurrence: Option<Option<Rule>>,
+    #[schema(value_type = Option<String>, example = "Europe/Berlin")]
+    pub(crate) time_zone: Option<Tz>,
+    /// Заменяет все теги
+    pub(crate) tags: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<Priority>)]
+    pub(crate) priority: Option<Option<Priority>>,
 }
 
 impl UpdateTodo {
     pub fn validate(&self) -> Result<(), Error> {
-        if self.body.is_none() && self.done.is_none() {
-            return Err(Error::Validation(
-                StatusCode::UNPROCESSABLE_ENTITY,
-                "At least one of 'body' or 'done' must be provided".to_string(),
-            ));
+        if self.body.is_none()
+            && self.done.is_none()
+            && self.due_at.is_none()
+            && self.recurrence.is_none()
+            && self.time_zone.is_none()
+            && self.tags.is_none()
+            && self.priority.is_none()
+        {
+            return Err(Error::Validation(StatusCode::UNPROCESSABLE_ENTITY, Key::UpdateEmpty.into()));
+        }
+        if let Some(tags) = &self.tags {
+            validate_tags(tags)?;
         }
         Ok(())
     }
+}
+
+impl CreateTodo {
+    pub fn validate(&self) -> Result<(), Error> {
+        validate_tags(&self.tags)
+    }
+}
+
+fn validate_tags(tags: &[String]) -> Result<(), Error> {
+    let invalid = |message: Message| Err(Error::Validation(StatusCode::UNPROCESSABLE_ENTITY, message));
+    if tags.len() > MAX_TAGS {
+        return invalid(Message::new(Key::TooManyTags).arg("max", MAX_TAGS));
+    }
+    for tag in tags {
+        if tag.is_empty() || tag.chars().count() > MAX_TAG_LEN || tag.chars().any(char::is_whitespace) {
+            return invalid(Message::new(Key::TagInvalid).arg("tag", tag).arg("max", MAX_TAG_LEN));
+        }
+    }
+    Ok(())
+}
+
+/// Тело `PATCH /v1/todos/{id}`, формат выбирается по `Content-Type`.
+pub enum TodoPatch {
+    Update(UpdateTodo),
+    /// JSON Merge Patch (RFC 7396)
+    Merge(Value),
+    /// JSON Patch (RFC 6902); `test` может проверять и поля только для чтения, например `version`
+    Json(json_patch::Patch),
+}
+
+impl TodoPatch {
+    pub fn parse(content_type: Option<&str>, body: &[u8]) -> Result<Self, Error> {
+        let media_type = content_type
+            .and_then(|content_type| content_type.split(';').next())
+            .map(|media_type| media_type.trim().to_ascii_lowercase());
+        let patch = match media_type.as_deref() {
+            Some("application/json") => TodoPatch::Update(serde_json::from_slice(body).map_err(invalid_json)?),
+            Some(MERGE_PATCH) => TodoPatch::Merge(serde_json::from_slice(body).map_err(invalid_json)?),
+            Some(JSON_PATCH) => TodoPatch::Json(serde_json::from_slice(body).map_err(invalid_json)?),
+            _ => {
+                return Err(Error::Validation(
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    Message::new(Key::PatchUnsupported).arg("merge", MERGE_PATCH).arg("json", JSON_PATCH),
+                ));
+            }
+        };
+        if let TodoPatch::Update(update_todo) = &patch {
+            update_todo.validate()?;
+        }
+        Ok(patch)
+    }
+
+    /// Изменения, которые патч вносит в `current`, или `None`, если todo не меняется.
+    pub fn resolve(self, current: &Todo) -> Result<Option<UpdateTodo>, Error> {
+        let update_todo = match self {
+            TodoPatch::Update(update_todo) => return Ok(Some(update_todo)),
+            TodoPatch::Merge(patch) => {
+                let mut document = serde_json::to_value(current).expect("todo is serializable");
+                json_patch::merge(&mut document, &patch);
+                changes(current, document)?
+            }
+            TodoPatch::Json(patch) => {
+                let mut document = serde_json::to_value(current).expect("todo is serializable");
+                json_patch::patch(&mut document, &patch).map_err(|err| {
+                    let status = match err.kind {
+                        json_patch::PatchErrorKind::TestFailed => StatusCode::CONFLICT,
+                        _ => StatusCode::UNPROCESSABLE_ENTITY,
+                    };
+                    let message = Message::new(Key::PatchOperationFailed)
+                        .arg("operation", err.operation)
+                        .arg("path", &err.path)
+                        .arg("details", &err.kind);
+                    Error::Validation(status, message)
+                })?;
+                changes(current, document)?
+            }
+        };
+        if let Some(update_todo) = &update_todo {
+            update_todo.validate()?;
+        }
+        Ok(update_todo)
+    }
+}
+
+/// `UpdateTodo` из полей, которые отличаются в `patched`. Удалённое поле считается `null`.
+fn changes(current: &Todo, patched: Value) -> Result<Option<UpdateTodo>, Error> {
+    let invalid = |message: Message| Error::Validation(StatusCode::UNPROCESSABLE_ENTITY, message);
+    let Value::Object(current) = serde_json::to_value(current).expect("todo is serializable") else {
+        unreachable!("todo is serialized as an object");
+    };
+    let Value::Object(patched) = patched else {
+        return Err(invalid(Key::PatchNotObject.into()));
+    };
+
+    let mut changed = Map::new();
+    for key in current.keys().chain(patched.keys().filter(|key| !current.contains_key(*key))) {
+        let value = patched.get(key).cloned().unwrap_or(Value::Null);
+        if current.get(key) == Some(&value) {
+            continue;
+        }
+        if !EDITABLE.contains(&key.as_str()) {
+            return Err(invalid(Message::new(Key::FieldReadOnly).arg("field", key)));
+        }
+        let value = match (key.as_str(), value) {
+            ("body" | "done" | "tags", Value::Null) => return Err(invalid(Message::new(Key::FieldNotNullable).arg("field", key))),
+            // как и в `Todo`, отсутствие пояса означает UTC
+            ("time_zone", Value::Null) => Value::from("UTC"),
+            (_, value) => value,
+        };
+        changed.insert(key.clone(), value);
+    }
+    if changed.is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_value(Value::Object(changed)).map(Some).map_err(|err| invalid(Message::new(Key::InvalidJson).arg("details", err)))
+}
+
+fn invalid_json(err: serde_json::Error) -> Error {
+    let status = match err.classify() {
+        serde_json::error::Category::Data => StatusCode::UNPROCESSABLE_ENTITY,
+        _ => StatusCode::BAD_REQUEST,
+    };
+    Error::Validation(status, Message::new(Key::InvalidJson).arg("details", err))
+}
+
+/// Отличает отсутствующее поле (`None`) от явного `null` (`Some(None)`).
+pub(crate) fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OccurrencesQuery {
+    /// По умолчанию 10, не больше 100
+    pub(crate) limit: Option<usize>,
+}
+
+impl OccurrencesQuery {
+    pub fn limit(&self) -> Result<usize, Error> {
+        match self.limit {
+            None => Ok(DEFAULT_OCCURRENCES),
+            Some(limit) if (1..=MAX_OCCURRENCES).contains(&limit) => Ok(limit),
+            Some(_) => Err(Error::Validation(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Message::new(Key::LimitOutOfRange).arg("max", MAX_OCCURRENCES),
+            )),
+        }
+    }
+}
+
+/// Ближайшие вхождения повторяющегося todo в его часовом поясе.
+#[derive(Serialize, ToSchema)]
+pub struct Occurrences {
+    recurrence: String,
+    time_zone: String,
+    occurrences: Vec<DateTime<FixedOffset>>,
+}
+
+impl Occurrences {
+    pub fn new(schedule: &Schedule, after: DateTime<Utc>, limit: usize) -> Self {
+        let occurrences = schedule
+            .occurrences()
+            .filter(|at| *at > after)
+            .take(limit)
+            .map(|at| at.with_timezone(&schedule.tz).fixed_offset())
+            .collect();
+        Occurrences {
+            recurrence: schedule.rule.to_string(),
+            time_zone: schedule.tz.name().to_string(),
+            occurrences,
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,66 @@
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::error::Error;
+use crate::i18n::Key;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "workspace_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    Editor,
+    Owner,
+}
+
+#[derive(Serialize, Clone, sqlx::FromRow, ToSchema)]
+pub struct Workspace {
+    id: i64,
+    name: String,
+    role: Role,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateWorkspace {
+    pub(crate) name: String,
+}
+
+#[derive(Serialize, Clone, sqlx::FromRow, ToSchema)]
+pub struct Member {
+    user_id: i64,
+    role: Role,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SetMember {
+    pub(crate) role: Role,
+}
+
+impl Workspace {
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+}
+
+impl CreateWorkspace {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.name.trim().is_empty() {
+            return Err(Error::Validation(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Key::NameEmpty.into(),
+            ));
+        }
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -1,13 +1,29 @@
-use axum::http::StatusCode;
+use axum::http::{header, HeaderValue, StatusCode};
 use axum::Json;
 use axum::response::{IntoResponse, Response};
 use serde::Serialize;
+use crate::blob::BlobError;
+use crate::i18n::{Key, Locale, Message};
+
+/// Через сколько секунд повторять запрос после 503.
+const RETRY_AFTER_SECS: &str = "1";
 
 #[derive(Debug)]
 pub enum Error {
+    /// Текст ошибки драйвера: уходит только в лог, клиенту - сообщение из каталога
     Sqlx(StatusCode, String),
-    Validation(StatusCode, String),
+    /// То же для хранилища вложений
+    Storage(StatusCode, String),
+    Validation(StatusCode, Message),
     NotFound,
+    Unauthorized,
+    Forbidden,
+    /// Тело запроса больше предела маршрута, в байтах
+    PayloadTooLarge(usize),
+    /// Запрос не уложился в `REQUEST_TIMEOUT_SECS`
+    Timeout,
+    /// Сброс нагрузки, клиенту стоит повторить позже
+    Overloaded,
 }
 
 impl From<sqlx::Error> for Error {
@@ -19,36 +35,70 @@ impl From<sqlx::Error> for Error {
     }
 }
 
+impl From<BlobError> for Error {
+    fn from(err: BlobError) -> Self {
+        match err {
+            BlobError::NotFound => Error::NotFound,
+            BlobError::Io(message) => Error::Storage(StatusCode::BAD_GATEWAY, message),
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct ApiError {
     error: &'static str,
+    /// Код сообщения из каталога `i18n`, не зависит от языка
+    code: Key,
     message: String,
 }
+
+impl ApiError {
+    /// Сообщение на языке запроса, см. `Locale::current`.
+    fn localized(status: StatusCode, error: &'static str, message: Message) -> Response {
+        let locale = Locale::current();
+        let body = Json(ApiError {
+            error,
+            code: message.key(),
+            message: message.render(locale),
+        });
+        (status, [(header::CONTENT_LANGUAGE, locale.tag())], body).into_response()
+    }
+}
+
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
         match self {
             Error::Sqlx(code, message) => {
-                let body = Json(ApiError {
-                    error: "db_error",
-                    message: message,
-                });
-                (code, body).into_response()
+                tracing::error!("database error: {}", message);
+                ApiError::localized(code, "db_error", Key::DatabaseError.into())
             }
 
-            Error::Validation(code, message) => {
-                let body = Json(ApiError {
-                    error: "validateion_error",
-                    message: message,
-                });
-                (code, body).into_response()
+            Error::Storage(code, message) => {
+                tracing::error!("storage error: {}", message);
+                ApiError::localized(code, "storage_error", Key::StorageError.into())
             }
 
-            Error::NotFound => {
-                let body = Json(ApiError {
-                    error: "not_found",
-                    message: "Not found".to_string(),
-                });
-                (StatusCode::NOT_FOUND, body).into_response()
+            Error::Validation(code, message) => ApiError::localized(code, "validateion_error", message),
+
+            Error::NotFound => ApiError::localized(StatusCode::NOT_FOUND, "not_found", Key::NotFound.into()),
+
+            Error::Unauthorized => ApiError::localized(StatusCode::UNAUTHORIZED, "unauthorized", Key::Unauthorized.into()),
+
+            Error::Forbidden => ApiError::localized(StatusCode::FORBIDDEN, "forbidden", Key::Forbidden.into()),
+
+            Error::PayloadTooLarge(max) => ApiError::localized(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "payload_too_large",
+                Message::new(Key::BodyTooLarge).arg("max", max),
+            ),
+
+            Error::Timeout => ApiError::localized(StatusCode::GATEWAY_TIMEOUT, "timeout", Key::Timeout.into()),
+
+            Error::Overloaded => {
+                let mut response =
+                    ApiError::localized(StatusCode::SERVICE_UNAVAILABLE, "overloaded", Key::Overloaded.into());
+                response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from_static(RETRY_AFTER_SECS));
+                response
             }
         }
     }
@@ -0,0 +1,241 @@
+mod en;
+mod ru;
+
+use serde::Serialize;
+
+tokio::task_local! {
+    /// Язык ответа текущего запроса, см. `api::locale`
+    static LOCALE: Locale;
+}
+
+/// Языки, для которых есть каталог сообщений. Английский - исходный: непереведённые сообщения берутся из него.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Ru,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::En, Locale::Ru];
+
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Ru => "ru",
+        }
+    }
+
+    /// Лучший язык по `Accept-Language` с учётом q; при равном q - первый в списке.
+    /// Регион (`ru-RU`) не различается, `*` и неизвестные языки дают английский.
+    pub fn negotiate(accept_language: &str) -> Locale {
+        let mut best: Option<(Locale, f32)> = None;
+        for range in accept_language.split(',') {
+            let mut parts = range.split(';').map(str::trim);
+            let tag = parts.next().unwrap_or_default();
+            let q = parts
+                .find_map(|param| param.strip_prefix("q=").or_else(|| param.strip_prefix("Q=")))
+                .map_or(Some(1.0), |q| q.parse::<f32>().ok().filter(|q| (0.0..=1.0).contains(q)));
+            let Some(q) = q.filter(|q| *q > 0.0) else {
+                continue;
+            };
+            let primary = tag.split('-').next().unwrap_or_default();
+            let locale = match primary {
+                "*" => Locale::default(),
+                _ => match Locale::ALL.into_iter().find(|locale| locale.tag().eq_ignore_ascii_case(primary)) {
+                    Some(locale) => locale,
+                    None => continue,
+                },
+            };
+            if best.is_none_or(|(_, best)| q > best) {
+                best = Some((locale, q));
+            }
+        }
+        best.map(|(locale, _)| locale).unwrap_or_default()
+    }
+
+    /// Язык текущего запроса; вне запроса - английский.
+    pub fn current() -> Locale {
+        LOCALE.try_with(|locale| *locale).unwrap_or_default()
+    }
+
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        LOCALE.scope(self, future).await
+    }
+
+    /// Шаблон сообщения из каталога этого языка, `None` - если перевода нет.
+    pub fn template(self, key: Key) -> Option<&'static str> {
+        let bundle = match self {
+            Locale::En => en::BUNDLE,
+            Locale::Ru => ru::BUNDLE,
+        };
+        bundle.iter().find(|(known, _)| *known == key).map(|(_, template)| *template)
+    }
+}
+
+/// Стабильные коды сообщений об ошибках, по ним клиенты различают ошибки независимо от языка.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Key {
+    NotFound,
+    Unauthorized,
+    Forbidden,
+    Timeout,
+    Overloaded,
+    DatabaseError,
+    StorageError,
+    BodyTooLarge,
+    BodyNotUtf8,
+    InvalidJson,
+    InvalidXml,
+    InvalidCalendar,
+    InvalidMultipart,
+    WorkspaceMissing,
+    LastOwner,
+    NameEmpty,
+    ScopesEmpty,
+    ExpiryInPast,
+    LimitOutOfRange,
+    RangeReversed,
+    RangeTooLong,
+    UnknownName,
+    NothingSelected,
+    SinceInvalid,
+    TooManyChanges,
+    NewTodoIncomplete,
+    UpdateEmpty,
+    FieldReadOnly,
+    FieldNotNullable,
+    PatchNotObject,
+    PatchUnsupported,
+    PatchOperationFailed,
+    TooManyTags,
+    TagInvalid,
+    QuickAddEmpty,
+    MoveAnchorAmbiguous,
+    MoveToItself,
+    AnchorMissing,
+    NoRecurrence,
+    NoUpcomingOccurrence,
+    CommentEmpty,
+    CommentTooLong,
+    AttachmentMissing,
+    AttachmentEmpty,
+    AttachmentTooLarge,
+    ReportUnsupported,
+    ResourceNameInvalid,
+    CalendarUidMismatch,
+}
+
+impl Key {
+    pub const ALL: [Key; 48] = [
+        Key::NotFound,
+        Key::Unauthorized,
+        Key::Forbidden,
+        Key::Timeout,
+        Key::Overloaded,
+        Key::DatabaseError,
+        Key::StorageError,
+        Key::BodyTooLarge,
+        Key::BodyNotUtf8,
+        Key::InvalidJson,
+        Key::InvalidXml,
+        Key::InvalidCalendar,
+        Key::InvalidMultipart,
+        Key::WorkspaceMissing,
+        Key::LastOwner,
+        Key::NameEmpty,
+        Key::ScopesEmpty,
+        Key::ExpiryInPast,
+        Key::LimitOutOfRange,
+        Key::RangeReversed,
+        Key::RangeTooLong,
+        Key::UnknownName,
+        Key::NothingSelected,
+        Key::SinceInvalid,
+        Key::TooManyChanges,
+        Key::NewTodoIncomplete,
+        Key::UpdateEmpty,
+        Key::FieldReadOnly,
+        Key::FieldNotNullable,
+        Key::PatchNotObject,
+        Key::PatchUnsupported,
+        Key::PatchOperationFailed,
+        Key::TooManyTags,
+        Key::TagInvalid,
+        Key::QuickAddEmpty,
+        Key::MoveAnchorAmbiguous,
+        Key::MoveToItself,
+        Key::AnchorMissing,
+        Key::NoRecurrence,
+        Key::NoUpcomingOccurrence,
+        Key::CommentEmpty,
+        Key::CommentTooLong,
+        Key::AttachmentMissing,
+        Key::AttachmentEmpty,
+        Key::AttachmentTooLarge,
+        Key::ReportUnsupported,
+        Key::ResourceNameInvalid,
+        Key::CalendarUidMismatch,
+    ];
+}
+
+/// Сообщение об ошибке: код и аргументы, которые подставляются в шаблон вместо `{name}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    key: Key,
+    args: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    pub fn new(key: Key) -> Self {
+        Message { key, args: Vec::new() }
+    }
+
+    pub fn arg(mut self, name: &'static str, value: impl ToString) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    pub fn key(&self) -> Key {
+        self.key
+    }
+
+    pub fn render(&self, locale: Locale) -> String {
+        let template = locale.template(self.key).or_else(|| Locale::En.template(self.key));
+        let Some(template) = template else {
+            return format!("{:?}", self.key);
+        };
+        // за один проход, чтобы `{...}` внутри значений аргументов не подставлялись повторно
+        let mut message = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            message.push_str(&rest[..start]);
+            let placeholder = &rest[start..];
+            let end = placeholder.find('}').map_or(placeholder.len(), |end| end + 1);
+            let name = placeholder[1..end].trim_end_matches('}');
+            match self.args.iter().find(|(known, _)| *known == name) {
+                Some((_, value)) => message.push_str(value),
+                None => message.push_str(&placeholder[..end]),
+            }
+            rest = &placeholder[end..];
+        }
+        message.push_str(rest);
+        message
+    }
+
+    /// Имена `{name}` в шаблоне.
+    pub fn placeholders(template: &str) -> Vec<&str> {
+        template
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+}
+
+impl From<Key> for Message {
+    fn from(key: Key) -> Self {
+        Message::new(key)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,55 @@
+use super::Key;
+
+pub(super) const BUNDLE: &[(Key, &str)] = &[
+    (Key::NotFound, "Not found"),
+    (Key::Unauthorized, "Authentication required"),
+    (Key::Forbidden, "Insufficient permissions"),
+    (Key::Timeout, "Request took too long"),
+    (Key::Overloaded, "Server is overloaded, retry later"),
+    (Key::DatabaseError, "Database error"),
+    (Key::StorageError, "File storage error"),
+    (Key::BodyTooLarge, "Request body must not exceed {max} bytes"),
+    (Key::BodyNotUtf8, "Body must be UTF-8"),
+    (Key::InvalidJson, "{details}"),
+    (Key::InvalidXml, "Invalid XML: {details}"),
+    (Key::InvalidCalendar, "{details}"),
+    (Key::InvalidMultipart, "{details}"),
+    (Key::WorkspaceMissing, "Workspace must be given in the path or the 'X-Workspace-Id' header"),
+    (Key::LastOwner, "Workspace must keep at least one owner"),
+    (Key::NameEmpty, "'name' must not be empty"),
+    (Key::ScopesEmpty, "At least one scope must be provided"),
+    (Key::ExpiryInPast, "'expires_at' must be in the future"),
+    (Key::LimitOutOfRange, "'limit' must be between 1 and {max}"),
+    (Key::RangeReversed, "'from' must not be after 'to'"),
+    (Key::RangeTooLong, "The range must not exceed {max} buckets"),
+    (Key::UnknownName, "Unknown {what} '{name}', valid names: {valid}"),
+    (Key::NothingSelected, "At least one {what} must be given"),
+    (Key::SinceInvalid, "'since' is not a sync token"),
+    (Key::TooManyChanges, "At most {max} changes can be pushed at once"),
+    (Key::NewTodoIncomplete, "New todos must have a 'body' and can not be deleted"),
+    (
+        Key::UpdateEmpty,
+        "At least one of 'body', 'done', 'due_at', 'recurrence', 'time_zone', 'tags' or 'priority' must be provided",
+    ),
+    (Key::FieldReadOnly, "'{field}' can not be changed"),
+    (Key::FieldNotNullable, "'{field}' can not be null"),
+    (Key::PatchNotObject, "Patched todo must be an object"),
+    (Key::PatchUnsupported, "Expected 'application/json', '{merge}' or '{json}'"),
+    (Key::PatchOperationFailed, "Operation {operation} at '{path}': {details}"),
+    (Key::TooManyTags, "At most {max} tags are allowed"),
+    (Key::TagInvalid, "Tag '{tag}' must be 1 to {max} characters without spaces"),
+    (Key::QuickAddEmpty, "Nothing is left for the todo body in '{text}'"),
+    (Key::MoveAnchorAmbiguous, "Exactly one of 'before' or 'after' must be provided"),
+    (Key::MoveToItself, "Todo can not be moved relative to itself"),
+    (Key::AnchorMissing, "'{field}' todo does not exist"),
+    (Key::NoRecurrence, "Todo has no 'recurrence'"),
+    (Key::NoUpcomingOccurrence, "'recurrence' has no upcoming occurrences"),
+    (Key::CommentEmpty, "'body' must not be empty"),
+    (Key::CommentTooLong, "'body' must not exceed {max} characters"),
+    (Key::AttachmentMissing, "Multipart field 'file' is required"),
+    (Key::AttachmentEmpty, "Attachment is empty"),
+    (Key::AttachmentTooLarge, "Attachment must not exceed {max} bytes"),
+    (Key::ReportUnsupported, "REPORT '{name}' is not supported"),
+    (Key::ResourceNameInvalid, "New resources must be named after a non-numeric UID, '<UID>.ics'"),
+    (Key::CalendarUidMismatch, "Calendar resource must contain VTODO components with a single UID"),
+];
\ No newline at end of file
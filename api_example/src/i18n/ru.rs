@@ -0,0 +1,55 @@
+use super::Key;
+
+pub(super) const BUNDLE: &[(Key, &str)] = &[
+    (Key::NotFound, "Не найдено"),
+    (Key::Unauthorized, "Требуется аутентификация"),
+    (Key::Forbidden, "Недостаточно прав"),
+    (Key::Timeout, "Запрос выполнялся слишком долго"),
+    (Key::Overloaded, "Сервер перегружен, повторите позже"),
+    (Key::DatabaseError, "Ошибка базы данных"),
+    (Key::StorageError, "Ошибка файлового хранилища"),
+    (Key::BodyTooLarge, "Тело запроса не должно превышать {max} байт"),
+    (Key::BodyNotUtf8, "Тело запроса должно быть в UTF-8"),
+    (Key::InvalidJson, "Некорректный JSON: {details}"),
+    (Key::InvalidXml, "Некорректный XML: {details}"),
+    (Key::InvalidCalendar, "Некорректный iCalendar: {details}"),
+    (Key::InvalidMultipart, "Некорректный multipart: {details}"),
+    (Key::WorkspaceMissing, "Workspace нужно указать в пути или в заголовке 'X-Workspace-Id'"),
+    (Key::LastOwner, "В workspace должен остаться хотя бы один владелец"),
+    (Key::NameEmpty, "'name' не может быть пустым"),
+    (Key::ScopesEmpty, "Нужно указать хотя бы один scope"),
+    (Key::ExpiryInPast, "'expires_at' должен быть в будущем"),
+    (Key::LimitOutOfRange, "'limit' должен быть от 1 до {max}"),
+    (Key::RangeReversed, "'from' не может быть позже 'to'"),
+    (Key::RangeTooLong, "Диапазон не может превышать {max} интервалов"),
+    (Key::UnknownName, "Неизвестное имя '{name}', допустимые: {valid}"),
+    (Key::NothingSelected, "Нужно указать хотя бы одно имя"),
+    (Key::SinceInvalid, "'since' не является токеном синхронизации"),
+    (Key::TooManyChanges, "За раз можно отправить не больше {max} изменений"),
+    (Key::NewTodoIncomplete, "У нового todo должно быть поле 'body', и его нельзя удалить"),
+    (
+        Key::UpdateEmpty,
+        "Нужно указать хотя бы одно из полей 'body', 'done', 'due_at', 'recurrence', 'time_zone', 'tags' или 'priority'",
+    ),
+    (Key::FieldReadOnly, "'{field}' нельзя изменить"),
+    (Key::FieldNotNullable, "'{field}' не может быть null"),
+    (Key::PatchNotObject, "После патча todo должен остаться объектом"),
+    (Key::PatchUnsupported, "Ожидается 'application/json', '{merge}' или '{json}'"),
+    (Key::PatchOperationFailed, "Операция {operation} над '{path}': {details}"),
+    (Key::TooManyTags, "Допускается не больше {max} тегов"),
+    (Key::TagInvalid, "Тег '{tag}' должен содержать от 1 до {max} символов без пробелов"),
+    (Key::QuickAddEmpty, "В '{text}' не осталось текста для todo"),
+    (Key::MoveAnchorAmbiguous, "Нужно указать ровно одно из полей 'before' или 'after'"),
+    (Key::MoveToItself, "Todo нельзя переместить относительно самого себя"),
+    (Key::AnchorMissing, "Todo из '{field}' не существует"),
+    (Key::NoRecurrence, "У todo нет 'recurrence'"),
+    (Key::NoUpcomingOccurrence, "У 'recurrence' нет предстоящих повторений"),
+    (Key::CommentEmpty, "'body' не может быть пустым"),
+    (Key::CommentTooLong, "'body' не может быть длиннее {max} символов"),
+    (Key::AttachmentMissing, "Нужно поле multipart 'file'"),
+    (Key::AttachmentEmpty, "Вложение пустое"),
+    (Key::AttachmentTooLarge, "Вложение не должно превышать {max} байт"),
+    (Key::ReportUnsupported, "REPORT '{name}' не поддерживается"),
+    (Key::ResourceNameInvalid, "Новые ресурсы должны называться по нечисловому UID, '<UID>.ics'"),
+    (Key::CalendarUidMismatch, "Ресурс календаря должен содержать компоненты VTODO с одним UID"),
+];
\ No newline at end of file
@@ -0,0 +1,336 @@
+//! iCalendar (RFC 5545) для подписки на todo и CalDAV: только компоненты VTODO
+//! и свойства, которые соответствуют полям `Todo`. Время всегда отдаётся в UTC,
+//! поэтому VTIMEZONE не нужен; при разборе `TZID` должен быть именем из базы IANA.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use std::fmt;
+use std::str::FromStr;
+use crate::dto::todo::Todo;
+use crate::recurrence::resolve_local;
+
+pub const CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+const PRODID: &str = "-//api_example//todo//EN";
+/// Длина строки в октетах без CRLF, более длинные переносятся
+const LINE_LIMIT: usize = 75;
+const UTC_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const LOCAL_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    NeedsAction,
+    InProcess,
+    Completed,
+    Cancelled,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::NeedsAction => "NEEDS-ACTION",
+            Status::InProcess => "IN-PROCESS",
+            Status::Completed => "COMPLETED",
+            Status::Cancelled => "CANCELLED",
+        })
+    }
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "NEEDS-ACTION" => Ok(Status::NeedsAction),
+            "IN-PROCESS" => Ok(Status::InProcess),
+            "COMPLETED" => Ok(Status::Completed),
+            "CANCELLED" => Ok(Status::Cancelled),
+            _ => Err(format!("Unknown STATUS '{value}'")),
+        }
+    }
+}
+
+/// Компонент VTODO. Остальные свойства (CATEGORIES, PRIORITY, VALARM и т.д.) при разборе пропускаются.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VTodo {
+    pub uid: String,
+    pub summary: Option<String>,
+    pub status: Option<Status>,
+    /// Дата без времени (`VALUE=DATE`) считается полночью UTC, плавающее время - временем UTC
+    pub due: Option<DateTime<Utc>>,
+    pub completed: Option<DateTime<Utc>>,
+    pub created: Option<DateTime<Utc>>,
+    pub last_modified: Option<DateTime<Utc>>,
+    pub dtstamp: Option<DateTime<Utc>>,
+}
+
+impl VTodo {
+    fn new(uid: String) -> Self {
+        VTodo {
+            uid,
+            summary: None,
+            status: None,
+            due: None,
+            completed: None,
+            created: None,
+            last_modified: None,
+            dtstamp: None,
+        }
+    }
+
+    /// UID todo, созданного через API, выводится из id.
+    pub fn uid_of(todo: &Todo) -> String {
+        match todo.ical_uid() {
+            Some(uid) => uid.to_string(),
+            None => format!("todo-{}@api_example", todo.id()),
+        }
+    }
+
+    pub fn done(&self) -> bool {
+        self.status == Some(Status::Completed)
+    }
+
+    fn write(&self, out: &mut String) {
+        write_line(out, "BEGIN:VTODO");
+        write_line(out, &format!("UID:{}", escape(&self.uid)));
+        let times = [
+            ("DTSTAMP", self.dtstamp),
+            ("CREATED", self.created),
+            ("LAST-MODIFIED", self.last_modified),
+            ("DUE", self.due),
+            ("COMPLETED", self.completed),
+        ];
+        for (name, at) in times {
+            if let Some(at) = at {
+                write_line(out, &format!("{name}:{}", at.format(UTC_FORMAT)));
+            }
+        }
+        if let Some(summary) = &self.summary {
+            write_line(out, &format!("SUMMARY:{}", escape(summary)));
+        }
+        if let Some(status) = self.status {
+            write_line(out, &format!("STATUS:{status}"));
+        }
+        write_line(out, "END:VTODO");
+    }
+}
+
+impl From<&Todo> for VTodo {
+    fn from(todo: &Todo) -> Self {
+        VTodo {
+            uid: VTodo::uid_of(todo),
+            summary: Some(todo.body().to_string()),
+            status: Some(if todo.done() { Status::Completed } else { Status::NeedsAction }),
+            due: todo.due_at(),
+            completed: todo.completed_at(),
+            created: Some(todo.created_at()),
+            last_modified: Some(todo.updated_at()),
+            dtstamp: Some(todo.updated_at()),
+        }
+    }
+}
+
+/// VCALENDAR со всеми `todos`; `name` показывается клиентами как название календаря.
+pub fn calendar(name: Option<&str>, todos: &[VTodo]) -> String {
+    let mut out = String::new();
+    write_line(&mut out, "BEGIN:VCALENDAR");
+    write_line(&mut out, "VERSION:2.0");
+    write_line(&mut out, &format!("PRODID:{PRODID}"));
+    write_line(&mut out, "CALSCALE:GREGORIAN");
+    if let Some(name) = name {
+        write_line(&mut out, &format!("X-WR-CALNAME:{}", escape(name)));
+    }
+    for todo in todos {
+        todo.write(&mut out);
+    }
+    write_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Строка с переносом по 75 октетов, продолжение начинается с пробела (RFC 5545, 3.1).
+fn write_line(out: &mut String, line: &str) {
+    let mut limit = LINE_LIMIT;
+    let mut rest = line;
+    while rest.len() > limit {
+        // не разрезаем многобайтовый символ UTF-8
+        let mut end = limit;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        out.push_str(&rest[..end]);
+        out.push_str("\r\n ");
+        rest = &rest[end..];
+        limit = LINE_LIMIT - 1;
+    }
+    out.push_str(rest);
+    out.push_str("\r\n");
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Строки после склейки перенесённых продолжений.
+fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in input.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Result<Self, String> {
+        // ';' и ':' внутри кавычек в значениях параметров не считаются разделителями
+        let mut quoted = false;
+        let mut parts = Vec::new();
+        let mut start = 0;
+        let mut value_at = None;
+        for (i, c) in line.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                ';' if !quoted => {
+                    parts.push(&line[start..i]);
+                    start = i + 1;
+                }
+                ':' if !quoted => {
+                    parts.push(&line[start..i]);
+                    value_at = Some(i + 1);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let value_at = value_at.ok_or_else(|| format!("Malformed content line '{line}'"))?;
+
+        let name = parts[0].trim().to_ascii_uppercase();
+        if name.is_empty() {
+            return Err(format!("Malformed content line '{line}'"));
+        }
+        let params = parts[1..]
+            .iter()
+            .filter_map(|param| param.split_once('='))
+            .map(|(name, value)| (name.trim().to_ascii_uppercase(), value.trim_matches('"').to_string()))
+            .collect();
+        Ok(Property { name, params, value: line[value_at..].to_string() })
+    }
+
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(param, _)| param == name).map(|(_, value)| value.as_str())
+    }
+
+    fn time(&self) -> Result<DateTime<Utc>, String> {
+        let value = self.value.trim();
+        let invalid = || format!("Invalid {} '{value}'", self.name);
+        if self.param("VALUE").is_some_and(|kind| kind.eq_ignore_ascii_case("DATE")) || value.len() == 8 {
+            let date = NaiveDate::parse_from_str(value, "%Y%m%d").map_err(|_| invalid())?;
+            return Ok(date.and_time(Default::default()).and_utc());
+        }
+        if let Some(utc) = value.strip_suffix(['Z', 'z']) {
+            return NaiveDateTime::parse_from_str(utc, LOCAL_FORMAT).map(|at| at.and_utc()).map_err(|_| invalid());
+        }
+        let local = NaiveDateTime::parse_from_str(value, LOCAL_FORMAT).map_err(|_| invalid())?;
+        match self.param("TZID") {
+            Some(tzid) => {
+                let tz: Tz = tzid.parse().map_err(|_| format!("Unknown TZID '{tzid}'"))?;
+                Ok(resolve_local(tz, local))
+            }
+            None => Ok(local.and_utc()),
+        }
+    }
+}
+
+/// Все VTODO из текста: из VCALENDAR или отдельные компоненты, как в примерах RFC 5545.
+pub fn parse(input: &str) -> Result<Vec<VTodo>, String> {
+    let mut todos = Vec::new();
+    // открытые компоненты, свойства VTODO берутся только на его собственном уровне
+    let mut stack: Vec<String> = Vec::new();
+    let mut current: Option<(Option<String>, VTodo)> = None;
+
+    for line in unfold(input) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let property = Property::parse(&line)?;
+        let value = property.value.trim();
+        match property.name.as_str() {
+            "BEGIN" => {
+                let component = value.to_ascii_uppercase();
+                if component == "VTODO" && stack.iter().all(|open| open == "VCALENDAR") {
+                    current = Some((None, VTodo::new(String::new())));
+                }
+                stack.push(component);
+            }
+            "END" => {
+                let component = value.to_ascii_uppercase();
+                if stack.pop().as_ref() != Some(&component) {
+                    return Err(format!("Unexpected END:{component}"));
+                }
+                if component == "VTODO" && stack.iter().all(|open| open == "VCALENDAR") {
+                    let (uid, mut todo) = current.take().expect("VTODO is open");
+                    todo.uid = uid.ok_or("VTODO must have a UID")?;
+                    todos.push(todo);
+                }
+            }
+            name if stack.last().is_some_and(|open| open == "VTODO") => {
+                let Some((uid, todo)) = current.as_mut() else {
+                    continue;
+                };
+                match name {
+                    "UID" => *uid = Some(unescape(value)),
+                    "SUMMARY" => todo.summary = Some(unescape(&property.value)),
+                    "STATUS" => todo.status = Some(value.parse()?),
+                    "DUE" => todo.due = Some(property.time()?),
+                    "COMPLETED" => todo.completed = Some(property.time()?),
+                    "CREATED" => todo.created = Some(property.time()?),
+                    "LAST-MODIFIED" => todo.last_modified = Some(property.time()?),
+                    "DTSTAMP" => todo.dtstamp = Some(property.time()?),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(open) = stack.last() {
+        return Err(format!("BEGIN:{open} is not closed"));
+    }
+    Ok(todos)
+}
\ No newline at end of file
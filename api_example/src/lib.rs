@@ -0,0 +1,14 @@
+pub mod api;
+pub mod blob;
+pub mod cli;
+pub mod client;
+pub mod dto;
+pub mod error;
+pub mod i18n;
+pub mod ical;
+pub mod logger;
+pub mod quick_add;
+pub mod rank;
+pub mod recurrence;
+pub mod repo;
+pub mod tls;
\ No newline at end of file
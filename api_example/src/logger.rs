@@ -1,15 +1,94 @@
-pub(crate) fn init() {
+use axum::http::{HeaderMap, Request};
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::{TraceContextExt, TracerProvider};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Логи в stdout (`LOG_FORMAT=json` - в JSON с trace_id/span_id запроса)
+/// и, если задан `OTEL_EXPORTER_OTLP_ENDPOINT`, экспорт трейсов по OTLP/HTTP.
+/// Возвращённый провайдер нужно остановить через `shutdown`, чтобы дослать спаны.
+pub fn init() -> SdkTracerProvider {
     use tracing_subscriber::{EnvFilter, filter::LevelFilter, fmt, prelude::*};
 
     let rust_log = std::env::var(EnvFilter::DEFAULT_ENV)
         .unwrap_or_else(|_| "sqlx=info,tower_http=info,info".to_string());
 
+    let mut provider = SdkTracerProvider::builder().with_resource(
+        opentelemetry_sdk::Resource::builder()
+            .with_service_name(env!("CARGO_PKG_NAME"))
+            .build(),
+    );
+    // без экспортёра спаны всё равно получают id, они нужны для корреляции логов
+    if std::env::var_os("OTEL_EXPORTER_OTLP_ENDPOINT").is_some() {
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .build()
+            .expect("couldn't initialize OTLP exporter");
+        provider = provider.with_batch_exporter(exporter);
+    }
+    let provider = provider.build();
+
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    opentelemetry::global::set_tracer_provider(provider.clone());
+
+    let json = std::env::var("LOG_FORMAT").is_ok_and(|format| format.eq_ignore_ascii_case("json"));
+    let fmt_layer = if json {
+        fmt::layer().json().with_current_span(true).with_span_list(false).boxed()
+    } else {
+        fmt::layer().boxed()
+    };
+
     tracing_subscriber::registry()
-        .with(fmt::layer())
+        .with(fmt_layer)
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer(env!("CARGO_PKG_NAME"))))
         .with(
             EnvFilter::builder()
                 .with_default_directive(LevelFilter::INFO.into())
                 .parse_lossy(rust_log),
         )
         .init();
+
+    provider
+}
+
+/// Спан HTTP-запроса для `TraceLayer::make_span_with`: родителем становится
+/// контекст из W3C `traceparent`, а trace_id/span_id попадают в поля спана для JSON-логов.
+pub(crate) fn http_span<B>(request: &Request<B>) -> Span {
+    let span = tracing::info_span!(
+        "request",
+        otel.kind = "server",
+        http.request.method = %request.method(),
+        url.path = %request.uri().path(),
+        trace_id = tracing::field::Empty,
+        span_id = tracing::field::Empty,
+    );
+
+    let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(request.headers()))
+    });
+    if parent.span().span_context().is_valid() {
+        let _ = span.set_parent(parent);
+    }
+
+    let context = span.context();
+    let span_context = context.span().span_context().clone();
+    if span_context.is_valid() {
+        span.record("trace_id", span_context.trace_id().to_string());
+        span.record("span_id", span_context.span_id().to_string());
+    }
+    span
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
 }
\ No newline at end of file
@@ -1,27 +1,127 @@
-mod api;
-mod dto;
-mod error;
-mod logger;
-mod repo;
-
+use api_example::cli::{Cli, Command, EventsCommand, MigrateCommand, MigrateMode, ServeArgs, WorkspacesCommand};
+use api_example::repo::{event, migrations, workspace};
+use api_example::{api, logger, repo, tls};
+use clap::Parser;
 use tokio::net::TcpListener;
 
 #[tokio::main]
 async fn main() {
-    logger::init();
+    let cli = Cli::parse();
+    let tracer_provider = logger::init();
 
     let dbpool = repo::pg::init_dbpool()
         .await
         .expect("couldn't initialize DB pool");
 
-    let router = api::router::create_router(dbpool);
+    match cli.command.unwrap_or(Command::Serve(cli.serve)) {
+        Command::Serve(args) => serve(dbpool, args).await,
+        Command::Migrate(command) => migrate(dbpool, command).await,
+        Command::Events(command) => events(dbpool, command).await,
+        Command::Workspaces(command) => workspaces(dbpool, command).await,
+    }
+
+    if let Err(e) = tracer_provider.shutdown() {
+        eprintln!("Failed to flush traces: {}", e);
+    }
+}
+
+async fn serve(dbpool: sqlx::PgPool, args: ServeArgs) {
+    match args.migrate {
+        MigrateMode::Never => {}
+        MigrateMode::Check => migrations::check(&dbpool)
+            .await
+            .unwrap_or_else(|e| panic!("Refusing to serve: {}", e)),
+        MigrateMode::Apply => migrations::up(&dbpool)
+            .await
+            .unwrap_or_else(|e| panic!("DB migrations failed: {}", e)),
+    }
+
+    match workspace::orphaned(&dbpool).await {
+        Ok(0) => {}
+        Ok(orphaned) => tracing::warn!(
+            "{} workspaces have no members and their todos are unreachable; run `workspaces adopt --owner <user id>`",
+            orphaned
+        ),
+        Err(e) => tracing::warn!("Can not count workspaces without members: {:?}", e),
+    }
+
+    let db = repo::pg::Db::from_env(dbpool)
+        .await
+        .expect("couldn't initialize replica DB pool");
+    let router = api::router::create_router(api::state::AppState::from_env(db));
 
     let bind_addr = std::env::var("BIND_ADDR").unwrap_or_else(|_| "127.0.0.1:8000".to_string());
     let listener = TcpListener::bind(&bind_addr)
         .await
         .unwrap_or_else(|e| panic!("Can not bind to {}: {}", &bind_addr, e));
 
-    axum::serve(listener, router)
-        .await
-        .unwrap_or_else(|e| panic!("Failed to start: {}", e));
+    let shutdown = async {
+        tokio::signal::ctrl_c().await.expect("couldn't listen for Ctrl+C");
+    };
+
+    match tls::TlsSettings::from_env() {
+        Some(settings) => tls::serve(listener, router, settings, shutdown).await,
+        None => axum::serve(listener, router).with_graceful_shutdown(shutdown).await,
+    }
+    .unwrap_or_else(|e| panic!("Failed to start: {}", e));
+}
+
+async fn migrate(dbpool: sqlx::PgPool, command: MigrateCommand) {
+    match command {
+        MigrateCommand::Up => {
+            migrations::up(&dbpool)
+                .await
+                .unwrap_or_else(|e| panic!("DB migrations failed: {}", e));
+            println!("Migrations applied");
+        }
+        MigrateCommand::Status => {
+            let statuses = migrations::status(&dbpool)
+                .await
+                .unwrap_or_else(|e| panic!("Can not read migration status: {}", e));
+            for status in statuses {
+                println!("{:<10} {:<9} {}", status.version, format!("{:?}", status.state).to_lowercase(), status.description);
+            }
+        }
+        MigrateCommand::Down { to } => {
+            match migrations::down(&dbpool, to)
+                .await
+                .unwrap_or_else(|e| panic!("DB rollback failed: {}", e))
+            {
+                Some(target) => println!("Reverted migrations newer than {}", target),
+                None => println!("Nothing to revert"),
+            }
+        }
+    }
 }
+
+
+async fn events(dbpool: sqlx::PgPool, command: EventsCommand) {
+    match command {
+        EventsCommand::Seed => {
+            let seeded = event::seed(&dbpool)
+                .await
+                .unwrap_or_else(|e| panic!("Can not seed todo history: {:?}", e));
+            println!("Started history for {} todos", seeded);
+        }
+        EventsCommand::Rebuild { workspace } => {
+            let rebuilt = event::rebuild(&dbpool, workspace, event::snapshot_every())
+                .await
+                .unwrap_or_else(|e| panic!("Can not rebuild todos: {:?}", e));
+            println!(
+                "Replayed {} events for {} todos: {} inserted, {} updated, {} deleted, {} snapshots taken",
+                rebuilt.events, rebuilt.todos, rebuilt.inserted, rebuilt.updated, rebuilt.deleted, rebuilt.snapshots,
+            );
+        }
+    }
+}
+
+async fn workspaces(dbpool: sqlx::PgPool, command: WorkspacesCommand) {
+    match command {
+        WorkspacesCommand::Adopt { owner } => {
+            let adopted = workspace::adopt(&dbpool, owner)
+                .await
+                .unwrap_or_else(|e| panic!("Can not adopt workspaces: {:?}", e));
+            println!("User {} now owns {} workspaces", owner, adopted);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,522 @@
+//! Разбор строки быстрого добавления вида `pay invoice tomorrow 5pm #finance !high`.
+//!
+//! Распознаются срок (относительные даты и время на английском), теги `#tag` и приоритет
+//! `!high`, `!medium`, `!low`, `!1`..`!3`, `!!!`, `!!`. Распознанные слова убираются из текста,
+//! остальное становится телом todo. Учитываются только первый срок, первое время и первый приоритет,
+//! повторные остаются в тексте как есть.
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveTime, TimeDelta, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::Serialize;
+use utoipa::ToSchema;
+use crate::dto::todo::{Priority, MAX_TAGS, MAX_TAG_LEN};
+use crate::recurrence::resolve_local;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Due,
+    Tag,
+    Priority,
+}
+
+/// Фрагмент исходного текста и что из него получилось.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq, ToSchema)]
+pub struct Recognised {
+    pub kind: Kind,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Parsed {
+    pub body: String,
+    pub due_at: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+    pub priority: Option<Priority>,
+    pub recognised: Vec<Recognised>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Due {
+    /// День и, возможно, время суток из той же фразы (`tonight`, `friday evening`)
+    Day(NaiveDate, Option<NaiveTime>),
+    /// Точный момент: `in 2 hours` - это два часа реального времени, даже через перевод часов
+    At(DateTime<Utc>),
+}
+
+enum Match {
+    Tag(String),
+    Priority(Priority),
+    Due(Due),
+    Time(NaiveTime),
+}
+
+/// Время для срока, заданного только днём.
+fn default_time() -> NaiveTime {
+    hm(9, 0)
+}
+
+fn hm(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).expect("valid time")
+}
+
+/// `now` задаёт и часовой пояс, в котором понимаются `tomorrow` и `5pm`.
+pub fn parse(text: &str, now: DateTime<Tz>) -> Parsed {
+    let raw: Vec<&str> = text.split_whitespace().collect();
+    let words: Vec<String> = raw.iter().map(|word| normalize(word)).collect();
+    let mut used = vec![false; raw.len()];
+
+    let mut tags: Vec<String> = Vec::new();
+    let mut priority = None;
+    let mut due = None;
+    let mut time = None;
+    let mut recognised = Vec::new();
+
+    let mut i = 0;
+    while i < raw.len() {
+        let rest = &words[i..];
+        let found = if let Some(tag) = tag(raw[i]).filter(|_| tags.len() < MAX_TAGS) {
+            Some((Match::Tag(tag), 1))
+        } else if let Some(found) = parse_priority(&words[i]).filter(|_| priority.is_none()) {
+            Some((Match::Priority(found), 1))
+        } else if let Some((found, len)) = date(rest, now, false).filter(|_| due.is_none()) {
+            Some((Match::Due(found), len))
+        } else if time.is_none() && !matches!(due, Some(Due::At(_))) {
+            time_of_day(rest).map(|(found, len)| (Match::Time(found), len))
+        } else {
+            None
+        };
+
+        let Some((found, len)) = found else {
+            i += 1;
+            continue;
+        };
+        let kind = match found {
+            Match::Tag(tag) => {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+                Kind::Tag
+            }
+            Match::Priority(p) => {
+                priority = Some(p);
+                Kind::Priority
+            }
+            Match::Due(d) => {
+                due = Some(d);
+                Kind::Due
+            }
+            Match::Time(t) => {
+                time = Some(t);
+                Kind::Due
+            }
+        };
+        used[i..i + len].fill(true);
+        recognised.push(Recognised { kind, text: raw[i..i + len].join(" ") });
+        i += len;
+    }
+
+    let body = raw.iter().zip(&used).filter(|(_, used)| !**used).map(|(word, _)| *word).collect::<Vec<_>>().join(" ");
+    Parsed { body, due_at: due_at(due, time, now), tags, priority, recognised }
+}
+
+fn due_at(due: Option<Due>, time: Option<NaiveTime>, now: DateTime<Tz>) -> Option<DateTime<Utc>> {
+    let tz = now.timezone();
+    let local = match (due, time) {
+        (None, None) => return None,
+        (Some(Due::At(at)), _) => return Some(at),
+        (Some(Due::Day(day, implied)), time) => day.and_time(time.or(implied).unwrap_or_else(default_time)),
+        // одно время без дня: ближайшее в будущем
+        (None, Some(time)) => {
+            let today = now.date_naive().and_time(time);
+            if today > now.naive_local() { today } else { today + Days::new(1) }
+        }
+    };
+    Some(resolve_local(tz, local))
+}
+
+/// Приводит слово к нижнему регистру и убирает знаки препинания в конце: `Friday,` -> `friday`.
+fn normalize(word: &str) -> String {
+    word.trim_end_matches([',', ';', '.', ')']).to_lowercase()
+}
+
+fn tag(word: &str) -> Option<String> {
+    let tag = word.strip_prefix('#')?.trim_end_matches([',', ';', '.']);
+    let valid = tag.chars().next().is_some_and(char::is_alphabetic)
+        && tag.chars().count() <= MAX_TAG_LEN
+        && tag.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'));
+    valid.then(|| tag.to_lowercase())
+}
+
+fn parse_priority(word: &str) -> Option<Priority> {
+    match word {
+        "!high" | "!1" | "!!!" => Some(Priority::High),
+        "!medium" | "!med" | "!2" | "!!" => Some(Priority::Medium),
+        "!low" | "!3" => Some(Priority::Low),
+        _ => None,
+    }
+}
+
+/// Срок в начале `words` и сколько слов он занимает. Сокращения дней недели (`mon`, `sun`)
+/// принимаются только после `on`, `by`, `due`, `this` или `next`: иначе `buy sun cream` стало бы сроком.
+fn date(words: &[String], now: DateTime<Tz>, prefixed: bool) -> Option<(Due, usize)> {
+    let today = now.date_naive();
+    let first = words.first()?.as_str();
+    let second = words.get(1).map(String::as_str);
+    let phrase = |len: usize| words.get(..len).map(|words| words.join(" "));
+
+    if matches!(first, "on" | "by" | "due") {
+        return date(&words[1..], now, true).map(|(due, len)| (due, len + 1));
+    }
+
+    let (day, len) = match first {
+        "today" => (today, 1),
+        "tonight" => return Some((Due::Day(today, Some(hm(21, 0))), 1)),
+        "tomorrow" | "tmr" | "tmrw" => (today + Days::new(1), 1),
+        "day" if phrase(3).as_deref() == Some("day after tomorrow") => (today + Days::new(2), 3),
+        "the" if phrase(4).as_deref() == Some("the day after tomorrow") => (today + Days::new(2), 4),
+        "this" => match second? {
+            "weekend" => (weekend(today), 2),
+            word => match (weekday(word, true), part_of_day(word)) {
+                (Some(day), _) => (upcoming(today, day), 2),
+                (None, Some(time)) => return Some((Due::Day(today, Some(time)), 2)),
+                (None, None) => return None,
+            },
+        },
+        "next" => match second? {
+            "week" => (week_start(today) + Days::new(7), 2),
+            "month" => (today.with_day(1)?.checked_add_months(Months::new(1))?, 2),
+            "year" => (NaiveDate::from_ymd_opt(today.year() + 1, 1, 1)?, 2),
+            word => (week_start(today) + Days::new(7 + weekday(word, true)?.num_days_from_monday() as u64), 2),
+        },
+        "end" if second == Some("of") => {
+            let the = usize::from(words.get(2).map(String::as_str) == Some("the"));
+            match words.get(2 + the).map(String::as_str)? {
+                "week" => (upcoming(today, Weekday::Fri), 3 + the),
+                "month" => (end_of_month(today)?, 3 + the),
+                _ => return None,
+            }
+        }
+        "eow" => (upcoming(today, Weekday::Fri), 1),
+        "eom" => (end_of_month(today)?, 1),
+        "weekend" => (weekend(today), 1),
+        "in" => return relative(&words[1..], now).map(|(due, len)| (due, len + 1)),
+        word => {
+            if let Some(day) = weekday(word, prefixed) {
+                (upcoming(today, day), 1)
+            } else if let Ok(day) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+                (day, 1)
+            } else {
+                calendar_date(words, today)?
+            }
+        }
+    };
+
+    // `tomorrow morning`, `friday evening`
+    match words.get(len).and_then(|word| part_of_day(word)) {
+        Some(time) => Some((Due::Day(day, Some(time)), len + 1)),
+        None => Some((Due::Day(day, None), len)),
+    }
+}
+
+/// `3 days`, `a week`, `2 hours` после `in`.
+fn relative(words: &[String], now: DateTime<Tz>) -> Option<(Due, usize)> {
+    let count = number(words.first()?)?;
+    let today = now.date_naive();
+    let due = match words.get(1)?.as_str() {
+        "minute" | "minutes" | "min" | "mins" => Due::At(now.to_utc() + TimeDelta::minutes(count.into())),
+        "hour" | "hours" | "hr" | "hrs" => Due::At(now.to_utc() + TimeDelta::hours(count.into())),
+        "day" | "days" => Due::Day(today.checked_add_days(Days::new(count.into()))?, None),
+        "week" | "weeks" => Due::Day(today.checked_add_days(Days::new(7 * u64::from(count)))?, None),
+        "month" | "months" => Due::Day(today.checked_add_months(Months::new(count))?, None),
+        "year" | "years" => Due::Day(today.checked_add_months(Months::new(12 * count))?, None),
+        _ => return None,
+    };
+    Some((due, 2))
+}
+
+fn number(word: &str) -> Option<u32> {
+    const WORDS: [&str; 12] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven", "twelve"];
+    match word {
+        "a" | "an" => Some(1),
+        word => WORDS
+            .iter()
+            .position(|name| *name == word)
+            .map(|index| index as u32 + 1)
+            .or_else(|| word.parse().ok().filter(|count| (1..=999).contains(count))),
+    }
+}
+
+/// `jan 5`, `january 5th 2027`, `5 jan`, `5th of january`. Дата без года, которая уже прошла,
+/// относится к следующему году, а `feb 29` - к ближайшему високосному.
+fn calendar_date(words: &[String], today: NaiveDate) -> Option<(NaiveDate, usize)> {
+    let (month, day, len) = if let Some(month) = month(&words[0]) {
+        (month, day_of_month(words.get(1)?)?, 2)
+    } else {
+        let day = day_of_month(&words[0])?;
+        let of = usize::from(words.get(1).map(String::as_str) == Some("of"));
+        (month(words.get(1 + of)?)?, day, 2 + of)
+    };
+
+    let year = words.get(len).and_then(|word| word.parse::<i32>().ok()).filter(|year| (2000..=2100).contains(year));
+    match year {
+        Some(year) => Some((NaiveDate::from_ymd_opt(year, month, day)?, len + 1)),
+        None => (today.year()..=today.year() + 4)
+            .filter_map(|year| NaiveDate::from_ymd_opt(year, month, day))
+            .find(|date| *date >= today)
+            .map(|date| (date, len)),
+    }
+}
+
+fn month(word: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "january", "february", "march", "april", "may", "june", "july", "august", "september", "october", "november", "december",
+    ];
+    MONTHS
+        .iter()
+        .position(|name| *name == word || (word.len() >= 3 && name.starts_with(word)))
+        .map(|index| index as u32 + 1)
+}
+
+fn day_of_month(word: &str) -> Option<u32> {
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let suffix = &word[digits.len()..];
+    if !matches!(suffix, "" | "st" | "nd" | "rd" | "th") {
+        return None;
+    }
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+fn weekday(word: &str, abbreviated: bool) -> Option<Weekday> {
+    let day = match word {
+        "monday" => Weekday::Mon,
+        "tuesday" => Weekday::Tue,
+        "wednesday" => Weekday::Wed,
+        "thursday" => Weekday::Thu,
+        "friday" => Weekday::Fri,
+        "saturday" => Weekday::Sat,
+        "sunday" => Weekday::Sun,
+        "mon" if abbreviated => Weekday::Mon,
+        "tue" | "tues" if abbreviated => Weekday::Tue,
+        "wed" if abbreviated => Weekday::Wed,
+        "thu" | "thur" | "thurs" if abbreviated => Weekday::Thu,
+        "fri" if abbreviated => Weekday::Fri,
+        "sat" if abbreviated => Weekday::Sat,
+        "sun" if abbreviated => Weekday::Sun,
+        _ => return None,
+    };
+    Some(day)
+}
+
+/// Ближайший такой день недели, считая сегодняшний.
+fn upcoming(today: NaiveDate, day: Weekday) -> NaiveDate {
+    let ahead = (7 + day.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    today + Days::new(ahead.into())
+}
+
+fn week_start(today: NaiveDate) -> NaiveDate {
+    today - Days::new(today.weekday().num_days_from_monday().into())
+}
+
+/// Суббота; в воскресенье - сегодня, выходные ещё идут.
+fn weekend(today: NaiveDate) -> NaiveDate {
+    if today.weekday() == Weekday::Sun { today } else { upcoming(today, Weekday::Sat) }
+}
+
+fn end_of_month(today: NaiveDate) -> Option<NaiveDate> {
+    today.with_day(1)?.checked_add_months(Months::new(1))?.pred_opt()
+}
+
+fn part_of_day(word: &str) -> Option<NaiveTime> {
+    match word {
+        "morning" => Some(hm(9, 0)),
+        "noon" | "midday" => Some(hm(12, 0)),
+        "afternoon" => Some(hm(15, 0)),
+        "evening" => Some(hm(18, 0)),
+        "night" => Some(hm(21, 0)),
+        _ => None,
+    }
+}
+
+/// Время суток: `5pm`, `5:30 pm`, `17:00`, `at 17`, `noon`, `in the evening`.
+fn time_of_day(words: &[String]) -> Option<(NaiveTime, usize)> {
+    let first = words.first()?.as_str();
+    if matches!(first, "at" | "@") {
+        return clock(&words[1..], true).map(|(time, len)| (time, len + 1));
+    }
+    match first {
+        "noon" | "midday" => Some((hm(12, 0), 1)),
+        "midnight" => Some((hm(0, 0), 1)),
+        "in" if words.get(1).map(String::as_str) == Some("the") => Some((part_of_day(words.get(2)?)?, 3)),
+        _ => clock(words, false),
+    }
+}
+
+/// `bare` разрешает час без минут и без am/pm, как в `at 17`.
+fn clock(words: &[String], bare: bool) -> Option<(NaiveTime, usize)> {
+    let word = words.first()?.as_str();
+    if let Some(time) = part_of_day(word).filter(|_| bare).or_else(|| (word == "midnight").then(|| hm(0, 0))) {
+        return Some((time, 1));
+    }
+
+    let digits = word.trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '.');
+    let (suffix, len) = match &word[digits.len()..] {
+        "" => match words.get(1).map(String::as_str) {
+            Some(next) if meridiem(next).is_some() => (next, 2),
+            _ => ("", 1),
+        },
+        suffix => (suffix, 1),
+    };
+
+    let (hour, minute) = match digits.split_once([':', '.']) {
+        Some((hour, minute)) if minute.len() == 2 => (hour.parse::<u32>().ok()?, minute.parse::<u32>().ok()?),
+        Some(_) => return None,
+        None => (digits.parse::<u32>().ok()?, 0),
+    };
+    if digits.len() > 5 || minute > 59 {
+        return None;
+    }
+
+    let hour = match meridiem(suffix) {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None if suffix == "h" || (suffix.is_empty() && (bare || digits.contains(':'))) => hour,
+        None => return None,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0).map(|time| (time, len))
+}
+
+/// `true` для pm.
+fn meridiem(word: &str) -> Option<bool> {
+    match word {
+        "am" | "a.m" | "a.m." => Some(false),
+        "pm" | "p.m" | "p.m." => Some(true),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::Europe::Berlin;
+
+    fn berlin(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Tz> {
+        Berlin.with_ymd_and_hms(y, m, d, h, min, 0).single().expect("unambiguous local time")
+    }
+
+    /// Срок по Берлину, чтобы таблицы читались как календарь.
+    fn local(text: &str, now: DateTime<Tz>) -> Option<String> {
+        parse(text, now).due_at.map(|at| at.with_timezone(&Berlin).format("%Y-%m-%d %H:%M").to_string())
+    }
+
+    #[test]
+    fn dst_boundaries() {
+        // 29 марта 2026 часы переводятся вперёд (02:00 -> 03:00), 25 октября - назад (03:00 -> 02:00)
+        let cases = [
+            (berlin(2026, 3, 28, 12, 0), "tomorrow 9am", "2026-03-29T07:00:00+00:00"),
+            (berlin(2026, 3, 28, 12, 0), "tomorrow 1:30am", "2026-03-29T00:30:00+00:00"),
+            // пропущенный час: 02:30 становится 03:30 летнего времени
+            (berlin(2026, 3, 28, 12, 0), "tomorrow 2:30am", "2026-03-29T01:30:00+00:00"),
+            (berlin(2026, 3, 29, 1, 30), "in an hour", "2026-03-29T01:30:00+00:00"),
+            (berlin(2026, 3, 29, 1, 30), "in 90 mins", "2026-03-29T02:00:00+00:00"),
+            (berlin(2026, 10, 24, 12, 0), "tomorrow 9am", "2026-10-25T08:00:00+00:00"),
+            // повторяющийся час: берётся первое, ещё летнее 02:30
+            (berlin(2026, 10, 24, 12, 0), "tomorrow 2:30am", "2026-10-25T00:30:00+00:00"),
+            (berlin(2026, 10, 24, 12, 0), "tomorrow 3:30am", "2026-10-25T02:30:00+00:00"),
+            // `in` считает реальное время, а не показания часов
+            (berlin(2026, 10, 25, 1, 30), "in 2 hours", "2026-10-25T01:30:00+00:00"),
+            (berlin(2026, 10, 25, 1, 30), "in 45 mins", "2026-10-25T00:15:00+00:00"),
+            (berlin(2026, 10, 25, 1, 30), "at 9", "2026-10-25T08:00:00+00:00"),
+            (berlin(2026, 10, 25, 1, 30), "monday", "2026-10-26T08:00:00+00:00"),
+        ];
+        for (now, text, expected) in cases {
+            let due_at = parse(text, now).due_at.map(|at| at.to_rfc3339());
+            assert_eq!(due_at.as_deref(), Some(expected), "{text} at {now}");
+        }
+    }
+
+    #[test]
+    fn next_weekday() {
+        // next - день недели следующей недели, неделя начинается с понедельника
+        let cases = [
+            (berlin(2026, 10, 21, 12, 0), "next wednesday", "2026-10-28 09:00"),
+            (berlin(2026, 10, 21, 12, 0), "next monday", "2026-10-26 09:00"),
+            (berlin(2026, 10, 21, 12, 0), "next friday", "2026-10-30 09:00"),
+            (berlin(2026, 10, 21, 12, 0), "next sun", "2026-11-01 09:00"),
+            (berlin(2026, 10, 25, 12, 0), "next monday", "2026-10-26 09:00"),
+            (berlin(2026, 10, 25, 12, 0), "next sunday", "2026-11-01 09:00"),
+            (berlin(2026, 10, 25, 12, 0), "next sat evening", "2026-10-31 18:00"),
+            (berlin(2026, 10, 26, 12, 0), "next monday", "2026-11-02 09:00"),
+            (berlin(2026, 12, 25, 12, 0), "next tuesday", "2026-12-29 09:00"),
+            (berlin(2026, 12, 25, 12, 0), "next week", "2026-12-28 09:00"),
+        ];
+        for (now, text, expected) in cases {
+            assert_eq!(local(text, now).as_deref(), Some(expected), "{text} at {now}");
+        }
+    }
+
+    #[test]
+    fn past_month_day_rolls_over() {
+        let cases = [
+            (berlin(2026, 10, 21, 12, 0), "oct 20", "2027-10-20 09:00"),
+            (berlin(2026, 10, 21, 12, 0), "20 october", "2027-10-20 09:00"),
+            (berlin(2026, 10, 21, 12, 0), "oct 21", "2026-10-21 09:00"),
+            (berlin(2026, 10, 21, 12, 0), "oct 22", "2026-10-22 09:00"),
+            // год указан явно - дата остаётся в прошлом
+            (berlin(2026, 10, 21, 12, 0), "jan 5 2026", "2026-01-05 09:00"),
+            (berlin(2026, 12, 31, 12, 0), "jan 1", "2027-01-01 09:00"),
+            (berlin(2026, 12, 31, 12, 0), "dec 31", "2026-12-31 09:00"),
+            (berlin(2026, 12, 31, 12, 0), "dec 30", "2027-12-30 09:00"),
+            (berlin(2026, 3, 1, 12, 0), "feb 29", "2028-02-29 09:00"),
+            (berlin(2028, 2, 28, 12, 0), "feb 29", "2028-02-29 09:00"),
+            (berlin(2028, 3, 1, 12, 0), "feb 29", "2032-02-29 09:00"),
+        ];
+        for (now, text, expected) in cases {
+            assert_eq!(local(text, now).as_deref(), Some(expected), "{text} at {now}");
+        }
+        assert_eq!(parse("feb 30", berlin(2026, 1, 1, 12, 0)).due_at, None);
+    }
+
+    #[test]
+    fn twelve_am_and_pm() {
+        let afternoon = berlin(2026, 10, 21, 14, 30);
+        let morning = berlin(2026, 10, 21, 9, 0);
+        let cases = [
+            (afternoon, "tomorrow 12am", Some("2026-10-22 00:00")),
+            (afternoon, "tomorrow 12pm", Some("2026-10-22 12:00")),
+            // без дня - ближайшие в будущем
+            (afternoon, "12am", Some("2026-10-22 00:00")),
+            (afternoon, "12pm", Some("2026-10-22 12:00")),
+            (morning, "12pm", Some("2026-10-21 12:00")),
+            (morning, "12am", Some("2026-10-22 00:00")),
+            (afternoon, "12:30am", Some("2026-10-22 00:30")),
+            (afternoon, "12:15 pm", Some("2026-10-22 12:15")),
+            (morning, "12 p.m.", Some("2026-10-21 12:00")),
+            (afternoon, "0am", None),
+            (afternoon, "13pm", None),
+            (afternoon, "12:60pm", None),
+        ];
+        for (now, text, expected) in cases {
+            assert_eq!(local(text, now).as_deref(), expected, "{text} at {now}");
+        }
+    }
+
+    #[test]
+    fn clock_forms() {
+        let words = |text: &str| text.split_whitespace().map(normalize).collect::<Vec<_>>();
+        let cases = [
+            ("12am", false, Some((hm(0, 0), 1))),
+            ("12pm", false, Some((hm(12, 0), 1))),
+            ("12 am", false, Some((hm(0, 0), 2))),
+            ("1pm", false, Some((hm(13, 0), 1))),
+            ("17", false, None),
+            ("17", true, Some((hm(17, 0), 1))),
+            ("17:05", false, Some((hm(17, 5), 1))),
+            ("9h", false, Some((hm(9, 0), 1))),
+            ("24:00", false, None),
+        ];
+        for (text, bare, expected) in cases {
+            assert_eq!(clock(&words(text), bare), expected, "{text}");
+        }
+    }
+}
\ No newline at end of file
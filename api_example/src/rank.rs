@@ -0,0 +1,95 @@
+//! Лексикографические ранги для ручной сортировки: строка из цифр base36, которая
+//! сравнивается побайтно (`COLLATE "C"`). Между любыми двумя рангами всегда есть ещё
+//! один, поэтому перемещение меняет одну строку, а не перенумеровывает список.
+//! Ранг никогда не кончается на `0`, иначе перед ним могло бы не найтись места.
+
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+const BASE: usize = DIGITS.len();
+
+/// Ранги длиннее этого пересчитываются через `spread`.
+pub const MAX_LEN: usize = 24;
+
+fn value(digit: u8) -> usize {
+    DIGITS.iter().position(|d| *d == digit).expect("rank contains a non-base36 digit")
+}
+
+/// Ранг строго между `lower` и `upper`; `None` - начало или конец списка.
+pub fn between(lower: Option<&str>, upper: Option<&str>) -> String {
+    let lower = lower.unwrap_or_default().as_bytes();
+    let upper = upper.map(str::as_bytes);
+    debug_assert!(upper.is_none_or(|upper| lower < upper), "lower rank must be less than upper");
+
+    String::from_utf8(midpoint(lower, upper)).expect("ranks are ASCII")
+}
+
+fn midpoint(lower: &[u8], upper: Option<&[u8]>) -> Vec<u8> {
+    if let Some(upper) = upper {
+        // общий префикс (недостающие цифры lower считаются нулями) переносится как есть
+        let common = upper
+            .iter()
+            .enumerate()
+            .take_while(|(i, digit)| lower.get(*i).copied().unwrap_or(b'0') == **digit)
+            .count();
+        if common > 0 {
+            let mut rank = upper[..common].to_vec();
+            rank.extend(midpoint(lower.get(common..).unwrap_or_default(), Some(&upper[common..])));
+            return rank;
+        }
+    }
+
+    let low = lower.first().map_or(0, |digit| value(*digit));
+    let high = upper.map_or(BASE, |upper| value(upper[0]));
+    if high - low > 1 {
+        return vec![DIGITS[(low + high) / 2]];
+    }
+    // соседние цифры: либо хватает первой цифры upper, либо уходим на разряд глубже
+    if let Some(upper) = upper.filter(|upper| upper.len() > 1) {
+        return vec![upper[0]];
+    }
+    let mut rank = vec![DIGITS[low]];
+    rank.extend(midpoint(lower.get(1..).unwrap_or_default(), None));
+    rank
+}
+
+/// Ранг для добавления в конец: `last`, увеличенный на единицу младшего разряда.
+/// В отличие от `between(last, None)` не удлиняется, пока в этой длине есть место.
+pub fn after(last: Option<&str>) -> String {
+    let Some(last) = last else {
+        return between(None, None);
+    };
+    let mut rank = last.as_bytes().to_vec();
+    // перенос: `z` в младшем разряде отбрасывается, нули в конце не остаются
+    while let Some(digit) = rank.pop() {
+        let next = value(digit) + 1;
+        if next < BASE {
+            rank.push(DIGITS[next]);
+            return String::from_utf8(rank).expect("ranks are ASCII");
+        }
+    }
+    between(Some(last), None)
+}
+
+/// `count` возрастающих рангов одинаковой длины с равными промежутками.
+pub fn spread(count: usize) -> Vec<String> {
+    let slots = count as u128 + 1;
+    // запас в одну цифру, чтобы после пересчёта хватало места для вставок
+    let mut width = 1;
+    let mut capacity = BASE as u128;
+    while capacity < slots * BASE as u128 {
+        width += 1;
+        capacity *= BASE as u128;
+    }
+
+    (1..slots)
+        .map(|slot| {
+            let mut number = slot * capacity / slots;
+            let mut rank = vec![b'0'; width];
+            for digit in rank.iter_mut().rev() {
+                *digit = DIGITS[(number % BASE as u128) as usize];
+                number /= BASE as u128;
+            }
+            let rank = String::from_utf8(rank).expect("ranks are ASCII");
+            rank.trim_end_matches('0').to_string()
+        })
+        .collect()
+}
\ No newline at end of file
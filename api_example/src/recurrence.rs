@@ -0,0 +1,338 @@
+use chrono::{DateTime, Datelike, Days, LocalResult, Months, NaiveDate, NaiveDateTime, Offset, TimeDelta, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Сколько периодов правила просматривается, прежде чем считать серию законченной.
+const MAX_PERIODS: u32 = 10_000;
+const MAX_INTERVAL: u32 = 1_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Freq {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Until {
+    Date(NaiveDate),
+    Time(DateTime<Utc>),
+}
+
+/// Подмножество RFC 5545 RRULE: `FREQ=DAILY|WEEKLY|MONTHLY`, `INTERVAL`, `BYDAY` (без порядковых
+/// номеров, только для WEEKLY), `BYMONTHDAY` (только для MONTHLY), `COUNT` и `UNTIL`.
+///
+/// Кроме RRULE принимаются сокращения `daily`, `weekly`, `weekly:mo,th`, `monthly`, `monthly:15`
+/// и `monthly:-1`. Хранится и отдаётся всегда в виде RRULE.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rule {
+    freq: Freq,
+    interval: u32,
+    by_day: Vec<Weekday>,
+    by_month_day: Vec<i32>,
+    count: Option<u32>,
+    until: Option<Until>,
+}
+
+impl Rule {
+    fn new(freq: Freq) -> Self {
+        Rule {
+            freq,
+            interval: 1,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            count: None,
+            until: None,
+        }
+    }
+
+    /// Даты вхождений в `period`-м периоде правила, считая от периода `start`.
+    fn period_dates(&self, start: NaiveDate, period: u32) -> Vec<NaiveDate> {
+        let step = period.saturating_mul(self.interval);
+        match self.freq {
+            Freq::Daily => start.checked_add_days(Days::new(step.into())).into_iter().collect(),
+            Freq::Weekly => {
+                let monday = start - Days::new(start.weekday().num_days_from_monday().into());
+                let Some(monday) = monday.checked_add_days(Days::new(u64::from(step) * 7)) else {
+                    return Vec::new();
+                };
+                let days = if self.by_day.is_empty() { vec![start.weekday()] } else { self.by_day.clone() };
+                days.into_iter()
+                    .filter_map(|day| monday.checked_add_days(Days::new(day.num_days_from_monday().into())))
+                    .collect()
+            }
+            Freq::Monthly => {
+                let Some(first) = start.with_day(1).and_then(|first| first.checked_add_months(Months::new(step))) else {
+                    return Vec::new();
+                };
+                let days_in_month = first.num_days_in_month() as i32;
+                let days = if self.by_month_day.is_empty() { vec![start.day() as i32] } else { self.by_month_day.clone() };
+                // как в RFC 5545, несуществующие дни (31 число в апреле) пропускаются
+                let mut days: Vec<u32> = days
+                    .into_iter()
+                    .map(|day| if day > 0 { day } else { days_in_month + 1 + day })
+                    .filter(|day| (1..=days_in_month).contains(day))
+                    .map(|day| day as u32)
+                    .collect();
+                days.sort_unstable();
+                days.dedup();
+                days.into_iter().filter_map(|day| first.with_day(day)).collect()
+            }
+        }
+    }
+}
+
+impl FromStr for Rule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let lower = value.to_ascii_lowercase();
+        let (name, argument) = match lower.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (lower.as_str(), None),
+        };
+
+        match (name, argument) {
+            ("daily", None) => Ok(Rule::new(Freq::Daily)),
+            ("weekly", argument) => {
+                let mut rule = Rule::new(Freq::Weekly);
+                if let Some(days) = argument {
+                    rule.by_day = parse_list(days, parse_weekday)?;
+                }
+                rule.normalize()
+            }
+            ("monthly", argument) => {
+                let mut rule = Rule::new(Freq::Monthly);
+                if let Some(days) = argument {
+                    rule.by_month_day = parse_list(days, parse_month_day)?;
+                }
+                rule.normalize()
+            }
+            ("rrule", Some(_)) => parse_rrule(&value["rrule:".len()..]),
+            _ => parse_rrule(value),
+        }
+    }
+}
+
+impl TryFrom<String> for Rule {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Rule> for String {
+    fn from(rule: Rule) -> Self {
+        rule.to_string()
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let freq = match self.freq {
+            Freq::Daily => "DAILY",
+            Freq::Weekly => "WEEKLY",
+            Freq::Monthly => "MONTHLY",
+        };
+        write!(f, "FREQ={freq}")?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<&str> = self.by_day.iter().map(|day| weekday_code(*day)).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(i32::to_string).collect();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        match self.until {
+            Some(Until::Date(date)) => write!(f, ";UNTIL={}", date.format("%Y%m%d")),
+            Some(Until::Time(time)) => write!(f, ";UNTIL={}", time.format("%Y%m%dT%H%M%SZ")),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Rule {
+    fn normalize(mut self) -> Result<Self, String> {
+        if !self.by_day.is_empty() && self.freq != Freq::Weekly {
+            return Err("BYDAY is only supported with FREQ=WEEKLY".to_string());
+        }
+        if !self.by_month_day.is_empty() && self.freq != Freq::Monthly {
+            return Err("BYMONTHDAY is only supported with FREQ=MONTHLY".to_string());
+        }
+        if self.count.is_some() && self.until.is_some() {
+            return Err("COUNT and UNTIL must not be used together".to_string());
+        }
+        self.by_day.sort_unstable_by_key(|day| day.num_days_from_monday());
+        self.by_day.dedup();
+        self.by_month_day.sort_unstable();
+        self.by_month_day.dedup();
+        Ok(self)
+    }
+}
+
+fn parse_rrule(value: &str) -> Result<Rule, String> {
+    let mut freq = None;
+    let mut rule = Rule::new(Freq::Daily);
+
+    for part in value.split(';').filter(|part| !part.is_empty()) {
+        let (name, value) = part
+            .split_once('=')
+            .ok_or_else(|| format!("RRULE part '{part}' must look like NAME=VALUE"))?;
+        match name.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                freq = Some(match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Freq::Daily,
+                    "WEEKLY" => Freq::Weekly,
+                    "MONTHLY" => Freq::Monthly,
+                    _ => return Err(format!("FREQ={value} is not supported, use DAILY, WEEKLY or MONTHLY")),
+                })
+            }
+            "INTERVAL" => {
+                rule.interval = value
+                    .parse()
+                    .ok()
+                    .filter(|interval| (1..=MAX_INTERVAL).contains(interval))
+                    .ok_or_else(|| format!("INTERVAL must be between 1 and {MAX_INTERVAL}"))?
+            }
+            "BYDAY" => rule.by_day = parse_list(value, parse_weekday)?,
+            "BYMONTHDAY" => rule.by_month_day = parse_list(value, parse_month_day)?,
+            "COUNT" => {
+                rule.count = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|count| *count >= 1)
+                        .ok_or_else(|| "COUNT must be a positive number".to_string())?,
+                )
+            }
+            "UNTIL" => rule.until = Some(parse_until(value)?),
+            "WKST" if value.eq_ignore_ascii_case("MO") => {}
+            _ => return Err(format!("RRULE part '{name}={value}' is not supported")),
+        }
+    }
+
+    rule.freq = freq.ok_or_else(|| "RRULE must contain FREQ".to_string())?;
+    rule.normalize()
+}
+
+fn parse_list<T>(value: &str, parse: fn(&str) -> Result<T, String>) -> Result<Vec<T>, String> {
+    value.split(',').map(|item| parse(item.trim())).collect()
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday, String> {
+    match value.to_ascii_uppercase().as_str() {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(format!("'{value}' is not a weekday, use MO, TU, WE, TH, FR, SA or SU")),
+    }
+}
+
+fn weekday_code(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn parse_month_day(value: &str) -> Result<i32, String> {
+    value
+        .parse::<i32>()
+        .ok()
+        .filter(|day| *day != 0 && (-31..=31).contains(day))
+        .ok_or_else(|| format!("'{value}' is not a day of month, use 1..31 or -31..-1"))
+}
+
+fn parse_until(value: &str) -> Result<Until, String> {
+    if let Ok(time) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ") {
+        return Ok(Until::Time(time.and_utc()));
+    }
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .map(Until::Date)
+        .map_err(|_| format!("UNTIL={value} must look like 20261231 or 20261231T235959Z"))
+}
+
+/// Правило вместе с первым вхождением серии и часовым поясом, в котором считаются даты.
+#[derive(Clone, Debug)]
+pub struct Schedule {
+    pub rule: Rule,
+    pub dtstart: DateTime<Utc>,
+    pub tz: Tz,
+}
+
+impl Schedule {
+    /// Серия, которая начинается с первого вхождения правила не раньше `now`.
+    pub fn starting_at(rule: Rule, tz: Tz, now: DateTime<Utc>) -> Option<Self> {
+        let today = now.with_timezone(&tz).date_naive();
+        if rule.period_dates(today, 0).contains(&today) {
+            return Some(Schedule { rule, dtstart: now, tz }).filter(|schedule| schedule.occurrences().next().is_some());
+        }
+
+        // сам `now` вхождением не является, поэтому COUNT на нём не тратится
+        let probe = Schedule { rule: Rule { count: None, ..rule.clone() }, dtstart: now, tz };
+        let dtstart = probe.occurrences().nth(1)?;
+        Some(Schedule { rule, dtstart, tz })
+    }
+
+    /// Вхождения начиная с `dtstart`, который по RFC 5545 всегда считается первым.
+    /// Время суток берётся местное, поэтому переход на летнее время его не сдвигает.
+    pub fn occurrences(&self) -> impl Iterator<Item = DateTime<Utc>> + '_ {
+        let start = self.dtstart.with_timezone(&self.tz).naive_local();
+        let rest = (0..MAX_PERIODS)
+            .flat_map(move |period| self.rule.period_dates(start.date(), period))
+            .filter(move |date| *date > start.date())
+            .map(move |date| resolve_local(self.tz, date.and_time(start.time())));
+
+        // UNTIL ограничивает и сам `dtstart`: серия, которая уже закончилась, пуста
+        std::iter::once(self.dtstart)
+            .chain(rest)
+            .take_while(move |at| match self.rule.until {
+                Some(Until::Date(until)) => at.with_timezone(&self.tz).date_naive() <= until,
+                Some(Until::Time(until)) => *at <= until,
+                None => true,
+            })
+            .take(self.rule.count.map_or(usize::MAX, |count| count as usize))
+    }
+
+    /// Следующая серия, которая начинается с первого вхождения позже `after`.
+    /// `COUNT` уменьшается на число пройденных вхождений.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<Schedule> {
+        let (index, dtstart) = self.occurrences().enumerate().find(|(_, at)| *at > after)?;
+        let mut rule = self.rule.clone();
+        rule.count = rule.count.map(|count| count - index as u32);
+        Some(Schedule { rule, dtstart, tz: self.tz })
+    }
+}
+
+pub(crate) fn resolve_local(tz: Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(at) => at.with_timezone(&Utc),
+        // при переводе часов назад время встречается дважды, берём первое
+        LocalResult::Ambiguous(earliest, _) => earliest.with_timezone(&Utc),
+        // время попало в пропущенный час: считаем по смещению до перехода, 02:30 становится 03:30
+        LocalResult::None => {
+            let offset = tz.offset_from_utc_datetime(&(local - TimeDelta::days(1))).fix();
+            local.and_utc() - TimeDelta::seconds(offset.local_minus_utc().into())
+        }
+    }
+}
\ No newline at end of file
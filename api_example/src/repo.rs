@@ -1,3 +1,12 @@
-pub(crate) mod pg;
+pub(crate) mod api_key;
+pub(crate) mod attachment;
+pub mod cache;
+pub(crate) mod comment;
+pub mod event;
+pub(crate) mod stats;
+pub(crate) mod sync;
+pub mod migrations;
+pub mod pg;
 pub(crate) mod todo;
-pub(crate) mod system;
\ No newline at end of file
+pub(crate) mod system;
+pub mod workspace;
\ No newline at end of file
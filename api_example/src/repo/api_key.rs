@@ -0,0 +1,89 @@
+use crate::dto::api_key::{ApiKey, CreateApiKey, CreatedApiKey, Scope};
+use crate::error::Error;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::{query, query_as, PgPool};
+
+const TOKEN_PREFIX: &str = "tk_";
+
+/// Ключ, по которому прошёл запрос.
+#[derive(sqlx::FromRow)]
+pub struct Grant {
+    pub id: i64,
+    pub user_id: i64,
+    pub workspace_id: Option<i64>,
+    pub scopes: Vec<Scope>,
+}
+
+pub async fn list_for_user(dbpool: &PgPool, user_id: i64) -> Result<Vec<ApiKey>, Error> {
+    query_as::<_, ApiKey>("SELECT * FROM api_key WHERE user_id = $1 ORDER BY id")
+        .bind(user_id)
+        .fetch_all(dbpool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn create(dbpool: &PgPool, user_id: i64, new_key: CreateApiKey) -> Result<CreatedApiKey, Error> {
+    let mut secret = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut secret);
+    let token = format!("{TOKEN_PREFIX}{}", hex::encode(secret));
+    let prefix = token[..TOKEN_PREFIX.len() + 8].to_string();
+
+    let key = query_as::<_, ApiKey>(
+        "INSERT INTO api_key (kind, user_id, workspace_id, name, prefix, secret_hash, scopes, expires_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+         RETURNING *",
+    )
+        .bind(new_key.kind())
+        .bind(user_id)
+        .bind(new_key.workspace_id)
+        .bind(new_key.name)
+        .bind(prefix)
+        .bind(hash_token(&token))
+        .bind(new_key.scopes)
+        .bind(new_key.expires_at)
+        .fetch_one(dbpool)
+        .await?;
+
+    Ok(CreatedApiKey { key, token })
+}
+
+pub async fn revoke(dbpool: &PgPool, user_id: i64, id: i64) -> Result<(), Error> {
+    let revoked = query("UPDATE api_key SET revoked_at = now() WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL")
+        .bind(id)
+        .bind(user_id)
+        .execute(dbpool)
+        .await?;
+
+    if revoked.rows_affected() == 0 {
+        return Err(Error::NotFound);
+    }
+
+    Ok(())
+}
+
+pub async fn authenticate(dbpool: &PgPool, token: &str) -> Result<Option<Grant>, Error> {
+    // last_used_at пишем не чаще раза в минуту, чтобы не превращать каждый запрос в UPDATE
+    query_as::<_, Grant>(
+        "WITH k AS (
+           SELECT id, user_id, workspace_id, scopes FROM api_key
+           WHERE secret_hash = $1
+             AND revoked_at IS NULL
+             AND (expires_at IS NULL OR expires_at > now())
+         ),
+         touch AS (
+           UPDATE api_key SET last_used_at = now()
+           WHERE id IN (SELECT id FROM k)
+             AND (last_used_at IS NULL OR last_used_at < now() - interval '1 minute')
+         )
+         SELECT * FROM k",
+    )
+        .bind(hash_token(token))
+        .fetch_optional(dbpool)
+        .await
+        .map_err(Into::into)
+}
+
+fn hash_token(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
\ No newline at end of file
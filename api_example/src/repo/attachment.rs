@@ -0,0 +1,103 @@
+use crate::dto::attachment::{Attachment, NewAttachment};
+use crate::error::Error;
+use crate::repo::pg::Db;
+use sqlx::{query, query_as, query_scalar};
+
+pub async fn list(db: &Db, workspace_id: i64, todo_id: i64) -> Result<Vec<Attachment>, Error> {
+    query_as::<_, Attachment>(
+        "SELECT a.* FROM attachment a
+         JOIN todo t ON t.id = a.todo_id
+         WHERE t.workspace_id = $1 AND a.todo_id = $2
+         ORDER BY a.id",
+    )
+        .bind(workspace_id)
+        .bind(todo_id)
+        .fetch_all(db.reader())
+        .await
+        .map_err(Into::into)
+}
+
+/// Вложения всех `todo_ids`, для `?include=attachments`.
+pub async fn for_todos(db: &Db, workspace_id: i64, todo_ids: &[i64]) -> Result<Vec<Attachment>, Error> {
+    query_as::<_, Attachment>(
+        "SELECT a.* FROM attachment a
+         JOIN todo t ON t.id = a.todo_id
+         WHERE t.workspace_id = $1 AND a.todo_id = ANY($2)
+         ORDER BY a.todo_id, a.id",
+    )
+        .bind(workspace_id)
+        .bind(todo_ids)
+        .fetch_all(db.reader())
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn read(db: &Db, workspace_id: i64, todo_id: i64, id: i64) -> Result<Attachment, Error> {
+    query_as::<_, Attachment>(
+        "SELECT a.* FROM attachment a
+         JOIN todo t ON t.id = a.todo_id
+         WHERE t.workspace_id = $1 AND a.todo_id = $2 AND a.id = $3",
+    )
+        .bind(workspace_id)
+        .bind(todo_id)
+        .bind(id)
+        .fetch_one(db.reader())
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn create(db: &Db, workspace_id: i64, todo_id: i64, new_attachment: NewAttachment) -> Result<Attachment, Error> {
+    query_as::<_, Attachment>(
+        "INSERT INTO attachment (todo_id, file_name, content_type, size_bytes, storage_key)
+         SELECT id, $3, $4, $5, $6 FROM todo WHERE workspace_id = $1 AND id = $2
+         RETURNING *",
+    )
+        .bind(workspace_id)
+        .bind(todo_id)
+        .bind(new_attachment.file_name)
+        .bind(new_attachment.content_type)
+        .bind(new_attachment.size_bytes)
+        .bind(new_attachment.storage_key)
+        .fetch_one(db.primary())
+        .await
+        .map_err(Into::into)
+}
+
+/// Удаляет метаданные и возвращает ключ содержимого в `BlobStore`.
+pub async fn delete(db: &Db, workspace_id: i64, todo_id: i64, id: i64) -> Result<String, Error> {
+    query_scalar::<_, String>(
+        "DELETE FROM attachment a
+         USING todo t
+         WHERE t.id = a.todo_id AND t.workspace_id = $1 AND a.todo_id = $2 AND a.id = $3
+         RETURNING a.storage_key",
+    )
+        .bind(workspace_id)
+        .bind(todo_id)
+        .bind(id)
+        .fetch_one(db.primary())
+        .await
+        .map_err(Into::into)
+}
+
+/// Удаляет вложения todo в транзакции его удаления и возвращает их ключи: содержимое
+/// удаляется после commit. Строка todo блокируется отдельным запросом, до выборки: вложение,
+/// загружаемое в это же время, либо уже закоммичено и попадёт в снимок DELETE, либо после
+/// commit не найдёт todo и уберёт свой blob само.
+pub(crate) async fn detach_all(tx: &mut sqlx::PgConnection, workspace_id: i64, todo_id: i64) -> Result<Vec<String>, Error> {
+    query("SELECT 1 FROM todo WHERE workspace_id = $1 AND id = $2 FOR UPDATE")
+        .bind(workspace_id)
+        .bind(todo_id)
+        .execute(&mut *tx)
+        .await?;
+    query_scalar::<_, String>(
+        "DELETE FROM attachment a
+         USING todo t
+         WHERE t.id = a.todo_id AND t.workspace_id = $1 AND a.todo_id = $2
+         RETURNING a.storage_key",
+    )
+        .bind(workspace_id)
+        .bind(todo_id)
+        .fetch_all(tx)
+        .await
+        .map_err(Into::into)
+}
\ No newline at end of file
@@ -0,0 +1,220 @@
+use crate::dto::todo::{ListVersion, Todo};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+pub(crate) const CHANNEL: &str = "todo_changed";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Key {
+    Todo { workspace_id: i64, id: i64 },
+    List { workspace_id: i64 },
+    ListVersion { workspace_id: i64 },
+}
+
+impl Key {
+    fn workspace_id(&self) -> i64 {
+        match *self {
+            Key::Todo { workspace_id, .. } | Key::List { workspace_id } | Key::ListVersion { workspace_id } => workspace_id,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) enum Value {
+    Todo(Arc<Todo>),
+    List(Arc<Vec<Todo>>),
+    ListVersion(ListVersion),
+}
+
+/// Метка состояния workspace на момент начала чтения из базы.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Stamp {
+    epoch: u64,
+    generation: u64,
+}
+
+struct Entry {
+    value: Value,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct Store {
+    entries: HashMap<Key, Entry>,
+    generations: HashMap<i64, u64>,
+    /// Растёт при полной очистке, например после переподключения LISTEN
+    epoch: u64,
+}
+
+/// Кэш чтений `repo::todo` с ограничением размера и TTL.
+/// Любое изменение todo сбрасывает все записи его workspace. Наполняется только чтениями
+/// из primary, см. `Db::cached_reader`.
+pub struct TodoCache {
+    capacity: usize,
+    ttl: Duration,
+    store: Mutex<Store>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub capacity: usize,
+}
+
+impl TodoCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        TodoCache {
+            capacity,
+            ttl,
+            store: Mutex::new(Store::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// `TODO_CACHE_CAPACITY` (по умолчанию 10000, 0 - выключен) и `TODO_CACHE_TTL_SECS` (30).
+    pub(crate) fn from_env() -> Self {
+        let capacity = std::env::var("TODO_CACHE_CAPACITY")
+            .ok()
+            .and_then(|capacity| capacity.parse().ok())
+            .unwrap_or(10_000);
+        let ttl = std::env::var("TODO_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|secs| secs.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(30));
+
+        TodoCache::new(capacity, ttl)
+    }
+
+    pub(crate) fn get(&self, key: Key) -> Option<Value> {
+        if self.capacity == 0 {
+            return None;
+        }
+
+        let mut store = self.store.lock().unwrap();
+        let value = match store.entries.get(&key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.value.clone()),
+            Some(_) => {
+                store.entries.remove(&key);
+                None
+            }
+            None => None,
+        };
+        drop(store);
+
+        match value {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+        value
+    }
+
+    /// Снимается до запроса в базу и передаётся в `put`.
+    pub(crate) fn stamp(&self, workspace_id: i64) -> Stamp {
+        let store = self.store.lock().unwrap();
+        Stamp {
+            epoch: store.epoch,
+            generation: store.generations.get(&workspace_id).copied().unwrap_or(0),
+        }
+    }
+
+    /// Кладёт значение, только если workspace не менялся с момента `stamp`:
+    /// иначе медленное чтение могло бы вернуть в кэш уже устаревшие данные.
+    pub(crate) fn put(&self, key: Key, value: Value, stamp: Stamp) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut store = self.store.lock().unwrap();
+        if stamp != (Stamp {
+            epoch: store.epoch,
+            generation: store.generations.get(&key.workspace_id()).copied().unwrap_or(0),
+        }) {
+            return;
+        }
+
+        if store.entries.len() >= self.capacity && !store.entries.contains_key(&key) {
+            let now = Instant::now();
+            store.entries.retain(|_, entry| entry.expires_at > now);
+            if store.entries.len() >= self.capacity {
+                // вытесняем самую старую запись
+                let oldest = store
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.expires_at)
+                    .map(|(key, _)| *key);
+                if let Some(oldest) = oldest {
+                    store.entries.remove(&oldest);
+                }
+            }
+        }
+
+        store.entries.insert(key, Entry { value, expires_at: Instant::now() + self.ttl });
+    }
+
+    pub(crate) fn invalidate_workspace(&self, workspace_id: i64) {
+        let mut store = self.store.lock().unwrap();
+        *store.generations.entry(workspace_id).or_default() += 1;
+        store.entries.retain(|key, _| key.workspace_id() != workspace_id);
+    }
+
+    pub(crate) fn clear(&self) {
+        let mut store = self.store.lock().unwrap();
+        store.epoch += 1;
+        store.entries.clear();
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.store.lock().unwrap().entries.len(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+/// Слушает NOTIFY от триггера на `todo` и сбрасывает кэш, когда todo меняет другой инстанс.
+pub(crate) async fn listen(dbpool: sqlx::PgPool, cache: Arc<TodoCache>) {
+    use sqlx::postgres::PgListener;
+
+    loop {
+        let mut listener = match PgListener::connect_with(&dbpool).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::warn!("todo cache listener can not connect: {}", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+        if let Err(e) = listener.listen(CHANNEL).await {
+            tracing::warn!("todo cache listener can not LISTEN: {}", e);
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            continue;
+        }
+        // пока не слушали, изменения могли пройти мимо
+        cache.clear();
+
+        loop {
+            match listener.try_recv().await {
+                Ok(Some(notification)) => match notification.payload().parse() {
+                    Ok(workspace_id) => cache.invalidate_workspace(workspace_id),
+                    Err(_) => cache.clear(),
+                },
+                // соединение потеряно; try_recv переподключится сам
+                Ok(None) => cache.clear(),
+                Err(e) => {
+                    tracing::warn!("todo cache listener failed: {}", e);
+                    cache.clear();
+                    break;
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
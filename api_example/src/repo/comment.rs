@@ -0,0 +1,111 @@
+use crate::dto::comment::{Comment, CreateComment, UpdateComment};
+use crate::error::Error;
+use crate::repo::pg::Db;
+use sqlx::{query, query_as};
+
+/// Возвращает до `limit + 1` комментариев, см. `CommentPage::new`.
+pub async fn list(db: &Db, workspace_id: i64, todo_id: i64, after: Option<i64>, limit: i64) -> Result<Vec<Comment>, Error> {
+    query_as::<_, Comment>(
+        "SELECT c.* FROM todo_comment c
+         JOIN todo t ON t.id = c.todo_id
+         WHERE t.workspace_id = $1 AND c.todo_id = $2 AND c.id > $3
+         ORDER BY c.id
+         LIMIT $4",
+    )
+        .bind(workspace_id)
+        .bind(todo_id)
+        .bind(after.unwrap_or(0))
+        .bind(limit + 1)
+        .fetch_all(db.reader())
+        .await
+        .map_err(Into::into)
+}
+
+/// Первые `limit` комментариев каждого из `todo_ids`, для `?include=comments`.
+pub async fn first_pages(db: &Db, workspace_id: i64, todo_ids: &[i64], limit: i64) -> Result<Vec<Comment>, Error> {
+    query_as::<_, Comment>(
+        "SELECT id, todo_id, author_id, body, created_at, updated_at FROM (
+           SELECT c.*, row_number() OVER (PARTITION BY c.todo_id ORDER BY c.id) AS n
+           FROM todo_comment c
+           JOIN todo t ON t.id = c.todo_id
+           WHERE t.workspace_id = $1 AND c.todo_id = ANY($2)
+         ) page
+         WHERE n <= $3
+         ORDER BY todo_id, id",
+    )
+        .bind(workspace_id)
+        .bind(todo_ids)
+        .bind(limit)
+        .fetch_all(db.reader())
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn read(db: &Db, workspace_id: i64, todo_id: i64, id: i64) -> Result<Comment, Error> {
+    query_as::<_, Comment>(
+        "SELECT c.* FROM todo_comment c
+         JOIN todo t ON t.id = c.todo_id
+         WHERE t.workspace_id = $1 AND c.todo_id = $2 AND c.id = $3",
+    )
+        .bind(workspace_id)
+        .bind(todo_id)
+        .bind(id)
+        .fetch_one(db.reader())
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn create(db: &Db, workspace_id: i64, todo_id: i64, author_id: i64, new_comment: CreateComment) -> Result<Comment, Error> {
+    let comment = query_as::<_, Comment>(
+        "INSERT INTO todo_comment (todo_id, author_id, body)
+         SELECT id, $3, $4 FROM todo WHERE workspace_id = $1 AND id = $2
+         RETURNING *",
+    )
+        .bind(workspace_id)
+        .bind(todo_id)
+        .bind(author_id)
+        .bind(new_comment.body)
+        .fetch_one(db.primary())
+        .await?;
+
+    // триггер обновил comment_count у todo
+    db.cache().invalidate_workspace(workspace_id);
+    Ok(comment)
+}
+
+pub async fn update(db: &Db, workspace_id: i64, todo_id: i64, id: i64, update_comment: UpdateComment) -> Result<Comment, Error> {
+    query_as::<_, Comment>(
+        "UPDATE todo_comment c
+         SET body = $4, updated_at = now()
+         FROM todo t
+         WHERE t.id = c.todo_id AND t.workspace_id = $1 AND c.todo_id = $2 AND c.id = $3
+         RETURNING c.*",
+    )
+        .bind(workspace_id)
+        .bind(todo_id)
+        .bind(id)
+        .bind(update_comment.body)
+        .fetch_one(db.primary())
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn delete(db: &Db, workspace_id: i64, todo_id: i64, id: i64) -> Result<(), Error> {
+    let deleted = query(
+        "DELETE FROM todo_comment c
+         USING todo t
+         WHERE t.id = c.todo_id AND t.workspace_id = $1 AND c.todo_id = $2 AND c.id = $3",
+    )
+        .bind(workspace_id)
+        .bind(todo_id)
+        .bind(id)
+        .execute(db.primary())
+        .await?;
+
+    db.cache().invalidate_workspace(workspace_id);
+    if deleted.rows_affected() == 0 {
+        return Err(Error::NotFound);
+    }
+
+    Ok(())
+}
\ No newline at end of file
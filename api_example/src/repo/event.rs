@@ -0,0 +1,448 @@
+//! Режим хранения с полной историей todo.
+//!
+//! При `TODO_STORE=events` каждая запись в `repo::todo` в той же транзакции добавляет события
+//! в `todo_event`, а строки `todo` становятся проекцией, которую `rebuild` собирает заново.
+//! Каждые `EVENT_SNAPSHOT_EVERY` событий состояние todo сохраняется в `todo_snapshot`,
+//! и пересборка читает только события после снимка.
+use crate::dto::todo::{nullable, Priority, Todo};
+use crate::error::Error;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{query, query_as, query_scalar, PgConnection, PgPool};
+
+pub const DEFAULT_SNAPSHOT_EVERY: i64 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Store {
+    /// Только текущее состояние в `todo`
+    Rows,
+    Events { snapshot_every: i64 },
+}
+
+impl Store {
+    /// `TODO_STORE` - `rows` (по умолчанию) или `events`.
+    pub fn from_env() -> Self {
+        match std::env::var("TODO_STORE").as_deref() {
+            Ok("events") => Store::Events { snapshot_every: snapshot_every() },
+            Ok("rows") | Err(_) => Store::Rows,
+            Ok(other) => {
+                tracing::warn!(store = other, "unknown TODO_STORE, keeping only rows");
+                Store::Rows
+            }
+        }
+    }
+}
+
+/// `EVENT_SNAPSHOT_EVERY`, по умолчанию 100.
+pub fn snapshot_every() -> i64 {
+    std::env::var("EVENT_SNAPSHOT_EVERY")
+        .ok()
+        .and_then(|every| every.parse().ok())
+        .filter(|every| *every > 0)
+        .unwrap_or(DEFAULT_SNAPSHOT_EVERY)
+}
+
+/// Поля todo, которыми владеют события. `comment_count`, `version` и `change_xid`
+/// ведут триггеры, в историю они не попадают.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct State {
+    id: i64,
+    workspace_id: i64,
+    body: String,
+    done: bool,
+    completed_at: Option<DateTime<Utc>>,
+    due_at: Option<DateTime<Utc>>,
+    recurrence: Option<String>,
+    time_zone: Option<String>,
+    tags: Vec<String>,
+    priority: Option<Priority>,
+    position: String,
+    next_todo_id: Option<i64>,
+    ical_uid: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<&Todo> for State {
+    fn from(todo: &Todo) -> Self {
+        State {
+            id: todo.id(),
+            workspace_id: todo.workspace_id(),
+            body: todo.body().to_string(),
+            done: todo.done(),
+            completed_at: todo.completed_at(),
+            due_at: todo.due_at(),
+            recurrence: todo.recurrence().map(str::to_string),
+            time_zone: todo.time_zone_name().map(str::to_string),
+            tags: todo.tags().to_vec(),
+            priority: todo.priority(),
+            position: todo.position().to_string(),
+            next_todo_id: todo.next_todo_id(),
+            ical_uid: todo.ical_uid().map(str::to_string),
+            created_at: todo.created_at(),
+            updated_at: todo.updated_at(),
+        }
+    }
+}
+
+impl State {
+    /// `updated_at` не сравнивается: его двигают и комментарии.
+    fn differs(&self, row: &State) -> bool {
+        State { updated_at: row.updated_at, ..self.clone() } != *row
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum Event {
+    Created(State),
+    BodyChanged { body: String },
+    Completed { completed_at: DateTime<Utc> },
+    Reopened,
+    Edited(Edit),
+    Deleted,
+}
+
+/// Остальные изменения: срок, повторение, теги, приоритет, место в списке, следующее вхождение.
+/// Отсутствующее поле не менялось, `null` - сброшено.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub(crate) struct Edit {
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "nullable")]
+    due_at: Option<Option<DateTime<Utc>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "nullable")]
+    recurrence: Option<Option<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "nullable")]
+    time_zone: Option<Option<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "nullable")]
+    priority: Option<Option<Priority>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    position: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "nullable")]
+    next_todo_id: Option<Option<i64>>,
+}
+
+/// События, которые переводят todo из `before` в `after`.
+fn diff(before: Option<&State>, after: Option<&State>) -> Vec<Event> {
+    let (before, after) = match (before, after) {
+        (None, None) => return Vec::new(),
+        (None, Some(after)) => return vec![Event::Created(after.clone())],
+        (Some(_), None) => return vec![Event::Deleted],
+        (Some(before), Some(after)) => (before, after),
+    };
+
+    let mut events = Vec::new();
+    if before.body != after.body {
+        events.push(Event::BodyChanged { body: after.body.clone() });
+    }
+    match (before.done, after.done) {
+        (false, true) => events.push(Event::Completed { completed_at: after.completed_at.unwrap_or(after.updated_at) }),
+        (true, false) => events.push(Event::Reopened),
+        _ => {}
+    }
+
+    fn changed<T: PartialEq + Clone>(before: &T, after: &T) -> Option<T> {
+        (before != after).then(|| after.clone())
+    }
+    let edit = Edit {
+        due_at: changed(&before.due_at, &after.due_at),
+        recurrence: changed(&before.recurrence, &after.recurrence),
+        time_zone: changed(&before.time_zone, &after.time_zone),
+        tags: changed(&before.tags, &after.tags),
+        priority: changed(&before.priority, &after.priority),
+        position: changed(&before.position, &after.position),
+        next_todo_id: changed(&before.next_todo_id, &after.next_todo_id),
+    };
+    if edit != Edit::default() {
+        events.push(Event::Edited(edit));
+    }
+    events
+}
+
+/// Состояние после события; без `Created` в начале истории состояния нет.
+fn apply(state: Option<State>, event: Event, at: DateTime<Utc>) -> Option<State> {
+    let mut state = match (state, event) {
+        (_, Event::Created(state)) => return Some(state),
+        (_, Event::Deleted) | (None, _) => return None,
+        (Some(state), Event::BodyChanged { body }) => State { body, ..state },
+        (Some(state), Event::Completed { completed_at }) => State { done: true, completed_at: Some(completed_at), ..state },
+        (Some(state), Event::Reopened) => State { done: false, completed_at: None, ..state },
+        (Some(mut state), Event::Edited(edit)) => {
+            if let Some(due_at) = edit.due_at {
+                state.due_at = due_at;
+            }
+            if let Some(recurrence) = edit.recurrence {
+                state.recurrence = recurrence;
+            }
+            if let Some(time_zone) = edit.time_zone {
+                state.time_zone = time_zone;
+            }
+            if let Some(tags) = edit.tags {
+                state.tags = tags;
+            }
+            if let Some(priority) = edit.priority {
+                state.priority = priority;
+            }
+            if let Some(position) = edit.position {
+                state.position = position;
+            }
+            if let Some(next_todo_id) = edit.next_todo_id {
+                state.next_todo_id = next_todo_id;
+            }
+            state
+        }
+    };
+    state.updated_at = at;
+    Some(state)
+}
+
+/// Записывает в историю переход todo из `before` в `after`. Обе версии прочитаны в транзакции
+/// `tx` под блокировкой строки, иначе параллельная запись вклинилась бы между ними.
+pub(crate) async fn record(tx: &mut PgConnection, store: Store, before: Option<&Todo>, after: Option<&Todo>) -> Result<(), Error> {
+    let Store::Events { snapshot_every } = store else {
+        return Ok(());
+    };
+    let Some(todo) = after.or(before) else {
+        return Ok(());
+    };
+    let before = before.map(State::from);
+    let mut events: Vec<(Event, Option<DateTime<Utc>>)> =
+        diff(before.as_ref(), after.map(State::from).as_ref()).into_iter().map(|event| (event, None)).collect();
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let last: Option<i64> = query_scalar("SELECT max(seq) FROM todo_event WHERE todo_id = $1")
+        .bind(todo.id())
+        .fetch_one(&mut *tx)
+        .await?;
+    // todo создан до включения режима: история начинается с его состояния на этот момент
+    if let (None, Some(before)) = (last, before) {
+        let at = before.updated_at;
+        events.insert(0, (Event::Created(before), Some(at)));
+    }
+
+    let first = last.unwrap_or(0);
+    let last = append(tx, todo.workspace_id(), todo.id(), first, events).await?;
+    if last / snapshot_every > first / snapshot_every {
+        let replayed = replay(tx, todo.id()).await?;
+        if let Some(state) = &replayed.state {
+            save_snapshot(tx, state, replayed.seq).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Добавляет события после `seq` и возвращает номер последнего.
+async fn append(
+    tx: &mut PgConnection,
+    workspace_id: i64,
+    todo_id: i64,
+    mut seq: i64,
+    events: Vec<(Event, Option<DateTime<Utc>>)>,
+) -> Result<i64, Error> {
+    for (event, occurred_at) in events {
+        seq += 1;
+        query(
+            "INSERT INTO todo_event (workspace_id, todo_id, seq, data, occurred_at)
+             VALUES ($1, $2, $3, $4, COALESCE($5, now()))",
+        )
+            .bind(workspace_id)
+            .bind(todo_id)
+            .bind(seq)
+            .bind(Json(event))
+            .bind(occurred_at)
+            .execute(&mut *tx)
+            .await?;
+    }
+    Ok(seq)
+}
+
+struct Replayed {
+    state: Option<State>,
+    /// Последнее учтённое событие
+    seq: i64,
+    /// Сколько событий прочитано после снимка
+    events: i64,
+}
+
+/// Состояние todo по последнему снимку и событиям после него.
+async fn replay(tx: &mut PgConnection, todo_id: i64) -> Result<Replayed, Error> {
+    let snapshot: Option<(i64, Json<State>)> = query_as("SELECT seq, state FROM todo_snapshot WHERE todo_id = $1")
+        .bind(todo_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let (seq, mut state) = match snapshot {
+        Some((seq, Json(state))) => (seq, Some(state)),
+        None => (0, None),
+    };
+
+    let events: Vec<(i64, Json<Event>, DateTime<Utc>)> =
+        query_as("SELECT seq, data, occurred_at FROM todo_event WHERE todo_id = $1 AND seq > $2 ORDER BY seq")
+            .bind(todo_id)
+            .bind(seq)
+            .fetch_all(&mut *tx)
+            .await?;
+
+    let mut replayed = Replayed { state: None, seq, events: events.len() as i64 };
+    for (seq, Json(event), at) in events {
+        state = apply(state, event, at);
+        replayed.seq = seq;
+    }
+    replayed.state = state;
+    Ok(replayed)
+}
+
+async fn save_snapshot(tx: &mut PgConnection, state: &State, seq: i64) -> Result<(), Error> {
+    query(
+        "INSERT INTO todo_snapshot (todo_id, workspace_id, seq, state)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (todo_id) DO UPDATE SET seq = EXCLUDED.seq, state = EXCLUDED.state, taken_at = now()",
+    )
+        .bind(state.id)
+        .bind(state.workspace_id)
+        .bind(seq)
+        .bind(Json(state))
+        .execute(&mut *tx)
+        .await?;
+    Ok(())
+}
+
+/// Импортирует todo без истории, например созданные до включения режима: каждому
+/// добавляется `Created` с текущим состоянием. Возвращает число импортированных.
+pub async fn seed(pool: &PgPool) -> Result<u64, Error> {
+    let mut tx = pool.begin().await?;
+    let todos = query_as::<_, Todo>(
+        "SELECT * FROM todo t WHERE NOT EXISTS (SELECT 1 FROM todo_event e WHERE e.todo_id = t.id) FOR UPDATE",
+    )
+        .fetch_all(&mut *tx)
+        .await?;
+    for todo in &todos {
+        let state = State::from(todo);
+        let at = state.updated_at;
+        append(&mut tx, todo.workspace_id(), todo.id(), 0, vec![(Event::Created(state), Some(at))]).await?;
+    }
+    tx.commit().await?;
+    Ok(todos.len() as u64)
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Rebuilt {
+    /// Todo с историей
+    pub todos: u64,
+    /// Прочитано событий после снимков
+    pub events: u64,
+    pub inserted: u64,
+    pub updated: u64,
+    pub deleted: u64,
+    pub snapshots: u64,
+}
+
+/// Пересобирает проекцию `todo` из снимков и событий. Каждый todo - своя транзакция
+/// с блокировкой строки, так что сервер может работать во время пересборки.
+/// Todo без истории не трогаются, см. `seed`.
+pub async fn rebuild(pool: &PgPool, workspace_id: Option<i64>, snapshot_every: i64) -> Result<Rebuilt, Error> {
+    let ids: Vec<i64> =
+        query_scalar("SELECT DISTINCT todo_id FROM todo_event WHERE $1::bigint IS NULL OR workspace_id = $1 ORDER BY todo_id")
+            .bind(workspace_id)
+            .fetch_all(pool)
+            .await?;
+
+    let mut rebuilt = Rebuilt { todos: ids.len() as u64, ..Rebuilt::default() };
+    let mut unlinked = Vec::new();
+    for &id in &ids {
+        if !rebuild_todo(pool, id, snapshot_every, &mut rebuilt).await? {
+            unlinked.push(id);
+        }
+    }
+    // следующее вхождение восстанавливается позже todo, который на него ссылается
+    for id in unlinked {
+        rebuild_todo(pool, id, snapshot_every, &mut rebuilt).await?;
+    }
+    Ok(rebuilt)
+}
+
+/// `false`, если следующего вхождения ещё нет в проекции и ссылку надо восстановить повторно.
+async fn rebuild_todo(pool: &PgPool, id: i64, snapshot_every: i64, rebuilt: &mut Rebuilt) -> Result<bool, Error> {
+    let mut tx = pool.begin().await?;
+    let row = query_as::<_, Todo>("SELECT * FROM todo WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let replayed = replay(&mut tx, id).await?;
+    rebuilt.events += replayed.events as u64;
+
+    let Some(state) = &replayed.state else {
+        if row.is_some() {
+            query("DELETE FROM todo WHERE id = $1").bind(id).execute(&mut *tx).await?;
+            rebuilt.deleted += 1;
+        }
+        tx.commit().await?;
+        return Ok(true);
+    };
+
+    match &row {
+        None => {
+            project(&mut tx, state, true).await?;
+            // todo снова есть, клиентам синхронизации не нужна его могила
+            query("DELETE FROM todo_tombstone WHERE todo_id = $1").bind(id).execute(&mut *tx).await?;
+            rebuilt.inserted += 1;
+        }
+        Some(row) if state.differs(&State::from(row)) => {
+            project(&mut tx, state, false).await?;
+            rebuilt.updated += 1;
+        }
+        Some(_) => {}
+    }
+    if replayed.events >= snapshot_every {
+        save_snapshot(&mut tx, state, replayed.seq).await?;
+        rebuilt.snapshots += 1;
+    }
+
+    let linked = match state.next_todo_id {
+        Some(next_id) => query_scalar("SELECT EXISTS (SELECT 1 FROM todo WHERE id = $1)")
+            .bind(next_id)
+            .fetch_one(&mut *tx)
+            .await?,
+        None => true,
+    };
+    tx.commit().await?;
+    Ok(linked)
+}
+
+/// Пишет состояние в `todo`. `version` и `change_xid` двигает триггер, так что клиенты
+/// синхронизации увидят исправленные строки.
+async fn project(tx: &mut PgConnection, state: &State, insert: bool) -> Result<(), Error> {
+    let sql = if insert {
+        "INSERT INTO todo (id, workspace_id, body, done, completed_at, due_at, recurrence, time_zone, tags, priority,
+                           position, next_todo_id, ical_uid, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, (SELECT id FROM todo WHERE id = $12), $13, $14, $15)"
+    } else {
+        "UPDATE todo
+         SET body = $3, done = $4, completed_at = $5, due_at = $6, recurrence = $7, time_zone = $8, tags = $9,
+             priority = $10, position = $11, next_todo_id = (SELECT id FROM todo WHERE id = $12), ical_uid = $13,
+             created_at = $14, updated_at = $15
+         WHERE id = $1 AND workspace_id = $2"
+    };
+    query(sql)
+        .bind(state.id)
+        .bind(state.workspace_id)
+        .bind(&state.body)
+        .bind(state.done)
+        .bind(state.completed_at)
+        .bind(state.due_at)
+        .bind(&state.recurrence)
+        .bind(&state.time_zone)
+        .bind(&state.tags)
+        .bind(state.priority)
+        .bind(&state.position)
+        .bind(state.next_todo_id)
+        .bind(&state.ical_uid)
+        .bind(state.created_at)
+        .bind(state.updated_at)
+        .execute(&mut *tx)
+        .await?;
+    Ok(())
+}
\ No newline at end of file
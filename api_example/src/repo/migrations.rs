@@ -0,0 +1,119 @@
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+use sqlx::{query_as, query_scalar, Connection, PgConnection, PgPool};
+use std::collections::HashMap;
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Applied,
+    Pending,
+    /// Применена, но файл миграции с тех пор изменился
+    Modified,
+    /// Есть в базе, но неизвестна этой сборке (схему накатила более новая версия)
+    Unknown,
+}
+
+pub struct Status {
+    pub version: i64,
+    pub description: String,
+    pub state: State,
+}
+
+/// Накатывает все новые миграции. sqlx держит `pg_advisory_lock` на время применения,
+/// так что одновременно стартующие реплики ждут друг друга, а не применяют миграции дважды.
+pub async fn up(dbpool: &PgPool) -> Result<(), MigrateError> {
+    let mut conn = dedicated_connection(dbpool).await?;
+    let result = MIGRATOR.run(&mut conn).await;
+    let _ = conn.close().await;
+    result
+}
+
+/// Откатывает миграции новее `target`, по умолчанию - только последнюю применённую.
+pub async fn down(dbpool: &PgPool, target: Option<i64>) -> Result<Option<i64>, MigrateError> {
+    let mut conn = dedicated_connection(dbpool).await?;
+    conn.ensure_migrations_table().await?;
+
+    let mut applied: Vec<i64> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+    applied.sort_unstable();
+
+    if applied.is_empty() {
+        return Ok(None);
+    }
+    let target = target.unwrap_or_else(|| applied.iter().rev().nth(1).copied().unwrap_or(0));
+
+    let result = MIGRATOR.undo(&mut conn, target).await;
+    let _ = conn.close().await;
+    result.map(|_| Some(target))
+}
+
+/// Версия последней миграции этой сборки.
+pub fn latest() -> Option<i64> {
+    MIGRATOR.iter().map(|migration| migration.version).max()
+}
+
+/// Только читает `_sqlx_migrations`: вызывается из `/health/details` на каждый опрос,
+/// поэтому не создаёт таблицу, как `ensure_migrations_table`. Нет таблицы - всё pending.
+pub async fn status(dbpool: &PgPool) -> Result<Vec<Status>, MigrateError> {
+    let exists: bool = query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(dbpool)
+        .await?;
+    let applied: HashMap<i64, Vec<u8>> = if exists {
+        query_as("SELECT version, checksum FROM _sqlx_migrations WHERE success")
+            .fetch_all(dbpool)
+            .await?
+            .into_iter()
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    let mut statuses: Vec<Status> = MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| Status {
+            version: migration.version,
+            description: migration.description.to_string(),
+            state: match applied.get(&migration.version) {
+                Some(checksum) if *checksum == *migration.checksum => State::Applied,
+                Some(_) => State::Modified,
+                None => State::Pending,
+            },
+        })
+        .collect();
+
+    for version in applied.keys().filter(|version| !MIGRATOR.version_exists(**version)) {
+        statuses.push(Status { version: *version, description: String::new(), state: State::Unknown });
+    }
+    statuses.sort_by_key(|status| status.version);
+
+    Ok(statuses)
+}
+
+/// Ошибка, если схема отстаёт от этой сборки: есть неприменённые или изменённые миграции.
+/// Неизвестные миграции допустимы - во время раскатки базу уже могла обновить новая версия.
+pub async fn check(dbpool: &PgPool) -> Result<(), String> {
+    let outdated: Vec<String> = status(dbpool)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|status| matches!(status.state, State::Pending | State::Modified))
+        .map(|status| format!("{} ({:?})", status.version, status.state))
+        .collect();
+
+    if !outdated.is_empty() {
+        return Err(format!("database schema is out of date: {}", outdated.join(", ")));
+    }
+    Ok(())
+}
+
+// advisory lock живёт в сессии: если миграция упадёт, соединение закрывается, а не
+// возвращается в пул с висящей блокировкой
+async fn dedicated_connection(dbpool: &PgPool) -> Result<PgConnection, MigrateError> {
+    Ok(dbpool.acquire().await?.detach())
+}
\ No newline at end of file
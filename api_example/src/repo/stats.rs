@@ -0,0 +1,78 @@
+use crate::dto::stats::{HistogramBucket, Stats, StatsRange, Totals};
+use crate::error::Error;
+use crate::repo::pg::{record_rows, Db};
+use sqlx::{query_as, query_scalar};
+use tracing::instrument;
+
+#[instrument(name = "todo.stats", skip(db, range), fields(otel.kind = "client", db.system = "postgresql", db.statement = "todo.stats", db.rows))]
+pub async fn stats(db: &Db, workspace_id: i64, range: StatsRange) -> Result<Stats, Error> {
+    let totals = query_as::<_, Totals>(
+        "SELECT count(*) AS total, count(*) FILTER (WHERE NOT done) AS open, count(*) FILTER (WHERE done) AS done
+         FROM todo
+         WHERE workspace_id = $1",
+    )
+        .bind(workspace_id)
+        .fetch_one(db.reader())
+        .await?;
+
+    // границы диапазона - местная полночь, поэтому сутки при переходе на летнее время бывают 23 или 25 часов
+    let median_time_to_done_secs: Option<f64> = query_scalar(
+        "SELECT percentile_cont(0.5) WITHIN GROUP (ORDER BY extract(epoch FROM completed_at - created_at))
+         FROM todo
+         WHERE workspace_id = $1
+           AND completed_at >= $2::timestamp AT TIME ZONE $4
+           AND completed_at < ($3::date + 1)::timestamp AT TIME ZONE $4",
+    )
+        .bind(workspace_id)
+        .bind(range.from)
+        .bind(range.to)
+        .bind(range.tz.name())
+        .fetch_one(db.reader())
+        .await?;
+
+    let histogram = query_as::<_, HistogramBucket>(
+        "WITH series AS (
+             SELECT generate_series($2::date, $3::date, ('1 ' || $5)::interval)::date AS start
+         ),
+         created AS (
+             SELECT date_trunc($5, created_at AT TIME ZONE $4)::date AS start, count(*) AS n
+             FROM todo
+             WHERE workspace_id = $1
+               AND created_at >= $2::timestamp AT TIME ZONE $4
+               AND created_at < ($3::date + 1)::timestamp AT TIME ZONE $4
+             GROUP BY 1
+         ),
+         completed AS (
+             SELECT date_trunc($5, completed_at AT TIME ZONE $4)::date AS start, count(*) AS n
+             FROM todo
+             WHERE workspace_id = $1
+               AND completed_at >= $2::timestamp AT TIME ZONE $4
+               AND completed_at < ($3::date + 1)::timestamp AT TIME ZONE $4
+             GROUP BY 1
+         )
+         SELECT series.start, COALESCE(created.n, 0) AS created, COALESCE(completed.n, 0) AS completed
+         FROM series
+         LEFT JOIN created USING (start)
+         LEFT JOIN completed USING (start)
+         ORDER BY series.start",
+    )
+        .bind(workspace_id)
+        .bind(range.from)
+        .bind(range.to)
+        .bind(range.tz.name())
+        .bind(range.bucket.unit())
+        .fetch_all(db.reader())
+        .await?;
+
+    record_rows(histogram.len() as u64 + 2);
+    Ok(Stats {
+        completion_rate: totals.completion_rate(),
+        totals,
+        median_time_to_done_secs,
+        from: range.from,
+        to: range.to,
+        bucket: range.bucket,
+        time_zone: range.tz.name().to_string(),
+        histogram,
+    })
+}
\ No newline at end of file
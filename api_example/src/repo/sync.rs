@@ -0,0 +1,143 @@
+use crate::dto::sync::{Change, ChangeResult, Outcome, SyncChanges, Tombstone};
+use crate::dto::todo::{CreateTodo, Todo, UpdateTodo};
+use crate::error::Error;
+use crate::repo::event::Store;
+use crate::repo::pg::{record_rows, Db};
+use crate::repo::todo;
+use chrono::Utc;
+use sqlx::{query, query_as, query_scalar};
+use tracing::instrument;
+
+/// Todo и надгробия, изменённые транзакциями с id не меньше `since`. Новый токен - xmin
+/// снимка: все транзакции до него уже видны, а более поздние попадут в следующий ответ.
+#[instrument(name = "todo.sync_changes", skip(db), fields(otel.kind = "client", db.system = "postgresql", db.statement = "todo.sync_changes", db.rows))]
+pub async fn changes(db: &Db, workspace_id: i64, since: Option<u64>) -> Result<SyncChanges, Error> {
+    let mut tx = db.reader().begin().await?;
+    // токен и обе выборки из одного снимка
+    query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY").execute(&mut *tx).await?;
+    let token: String = query_scalar("SELECT pg_snapshot_xmin(pg_current_snapshot())::text")
+        .fetch_one(&mut *tx)
+        .await?;
+
+    let since = since.map(|since| since.to_string());
+    let todos = query_as::<_, Todo>(
+        "SELECT * FROM todo
+         WHERE workspace_id = $1 AND ($2::text IS NULL OR change_xid >= $2::text::xid8)
+         ORDER BY position, id",
+    )
+        .bind(workspace_id)
+        .bind(&since)
+        .fetch_all(&mut *tx)
+        .await?;
+    let deleted = match &since {
+        Some(since) => {
+            query_as::<_, Tombstone>(
+                "SELECT todo_id AS id, deleted_at FROM todo_tombstone
+                 WHERE workspace_id = $1 AND change_xid >= $2::text::xid8
+                 ORDER BY todo_id",
+            )
+                .bind(workspace_id)
+                .bind(since)
+                .fetch_all(&mut *tx)
+                .await?
+        }
+        None => Vec::new(),
+    };
+    tx.commit().await?;
+
+    record_rows((todos.len() + deleted.len()) as u64);
+    Ok(SyncChanges { todos, deleted, token })
+}
+
+/// Применяет изменения клиента одной транзакцией, по порядку. Вместе с результатами
+/// возвращает ключи содержимого вложений удалённых todo, их удаляет вызывающий.
+#[instrument(name = "todo.sync_push", skip(db, changes), fields(otel.kind = "client", db.system = "postgresql", db.statement = "todo.sync_push", db.rows))]
+pub async fn push(db: &Db, workspace_id: i64, changes: Vec<Change>) -> Result<(Vec<ChangeResult>, Vec<String>), Error> {
+    let mut tx = db.primary().begin().await?;
+    let mut results = Vec::with_capacity(changes.len());
+    let mut storage_keys = Vec::new();
+    for change in changes {
+        results.push(apply(&mut tx, db.store(), workspace_id, change, &mut storage_keys).await?);
+    }
+    tx.commit().await?;
+
+    record_rows(results.len() as u64);
+    db.cache().invalidate_workspace(workspace_id);
+    Ok((results, storage_keys))
+}
+
+async fn apply(
+    tx: &mut sqlx::PgConnection,
+    store: Store,
+    workspace_id: i64,
+    change: Change,
+    storage_keys: &mut Vec<String>,
+) -> Result<ChangeResult, Error> {
+    let result = |outcome, conflict, todo: Option<Todo>| ChangeResult {
+        id: todo.as_ref().map(Todo::id).or(change.id),
+        client_id: change.client_id.clone(),
+        outcome,
+        conflict,
+        todo,
+        error: None,
+    };
+    // невалидное изменение отклоняется само по себе, а не откатывает весь пакет
+    let invalid = |err: Error, conflict, todo: Option<Todo>| match err {
+        Error::Validation(_, message) => Ok(ChangeResult {
+            error: Some(message.into()),
+            ..result(Outcome::Rejected, conflict, todo)
+        }),
+        err => Err(err),
+    };
+
+    let Some(id) = change.id else {
+        let new_todo = CreateTodo {
+            body: change.body.clone().unwrap_or_default(),
+            due_at: change.due_at.flatten(),
+            recurrence: None,
+            time_zone: None,
+            tags: Vec::new(),
+            priority: None,
+            ical_uid: None,
+            // часы клиента могут спешить
+            completed_at: change.done.unwrap_or(false).then(|| change.updated_at.min(Utc::now())),
+        };
+        if let Err(err) = new_todo.validate() {
+            return invalid(err, false, None);
+        }
+        let todo = todo::insert(tx, store, workspace_id, new_todo).await?;
+        return Ok(result(Outcome::Created, false, Some(todo)));
+    };
+
+    let Some(current) = todo::lock(tx, workspace_id, id).await? else {
+        return Ok(result(Outcome::Gone, !change.deleted, None));
+    };
+    // последний записавший выигрывает: при конфликте сравнивается время изменения на клиенте и на сервере
+    let conflict = change.base_version != Some(current.version());
+    if conflict && change.updated_at <= current.updated_at() {
+        return Ok(result(Outcome::Rejected, true, Some(current)));
+    }
+
+    if change.deleted {
+        storage_keys.extend(todo::remove(tx, store, workspace_id, id).await?.unwrap_or_default());
+        return Ok(result(Outcome::Deleted, conflict, None));
+    }
+
+    let update_todo = UpdateTodo {
+        body: change.body.clone(),
+        done: change.done,
+        due_at: change.due_at,
+        recurrence: None,
+        time_zone: None,
+        tags: None,
+        priority: None,
+    };
+    if let Err(err) = update_todo.validate() {
+        return invalid(err, conflict, Some(current));
+    }
+    // `apply_update` проверяет срок повторения до записи, так что транзакция остаётся целой
+    match todo::apply_update(tx, store, workspace_id, current.clone(), update_todo).await {
+        Ok(todo) => Ok(result(Outcome::Updated, conflict, Some(todo))),
+        Err(err) => invalid(err, conflict, Some(current)),
+    }
+}
\ No newline at end of file
@@ -1,4 +1,6 @@
 use sqlx::{Connection, PgPool};
+use std::time::{Duration, Instant};
+use crate::dto::health::Failure;
 use crate::error::Error;
 
 pub(crate) async fn ping(dbpool: &PgPool) -> Result<String, Error>  {
@@ -7,4 +9,18 @@ pub(crate) async fn ping(dbpool: &PgPool) -> Result<String, Error>  {
         .await
         .map(|_| "ok".to_string())
         .map_err(Into::into)
+}
+
+/// Время от запроса соединения до ответа на ping, не дольше `timeout`.
+pub(crate) async fn round_trip(dbpool: &PgPool, timeout: Duration) -> Result<Duration, Failure> {
+    let started = Instant::now();
+    let ping = async {
+        let mut conn = dbpool.acquire().await?;
+        conn.ping().await
+    };
+    match tokio::time::timeout(timeout, ping).await {
+        Ok(Ok(())) => Ok(started.elapsed()),
+        Ok(Err(err)) => Err(Failure::Error(err.to_string())),
+        Err(_) => Err(Failure::Timeout(timeout)),
+    }
 }
\ No newline at end of file
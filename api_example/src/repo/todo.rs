@@ -1,57 +1,462 @@
-use crate::dto::todo::{CreateTodo, Todo, UpdateTodo};
+use crate::dto::comment::DEFAULT_PAGE_LIMIT;
+use crate::dto::fields::{Include, PartialTodo, Projection};
+use crate::dto::todo::{CreateTodo, ListVersion, Placement, Todo, TodoPatch, UpdateTodo};
 use crate::error::Error;
-use sqlx::{query, query_as, PgPool};
+use crate::i18n::{self, Message};
+use crate::rank;
+use crate::recurrence::{Rule, Schedule};
+use crate::repo::cache::{Key, Value};
+use crate::repo::event::{self, Store};
+use crate::repo::pg::{record_rows, Db};
+use crate::repo::{attachment, comment};
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use sqlx::postgres::PgRow;
+use sqlx::{query, query_as, query_scalar, Row};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::instrument;
 
-pub async fn list(dbpool: &PgPool) -> Result<Vec<Todo>, Error> {
-    query_as::<_, Todo>("SELECT * FROM todo ORDER BY id")
-        .fetch_all(dbpool)
-        .await
-        .map_err(Into::into)
+#[instrument(name = "todo.list", skip(db), fields(otel.kind = "client", db.system = "postgresql", db.statement = "todo.list", db.rows))]
+pub async fn list(db: &Db, workspace_id: i64) -> Result<Vec<Todo>, Error> {
+    let key = Key::List { workspace_id };
+    if let Some(Value::List(todos)) = db.cache().get(key) {
+        return Ok(todos.to_vec());
+    }
+
+    let stamp = db.cache().stamp(workspace_id);
+    let (reader, cacheable) = db.cached_reader();
+    let todos = query_as::<_, Todo>("SELECT * FROM todo WHERE workspace_id = $1 ORDER BY position, id")
+        .bind(workspace_id)
+        .fetch_all(reader)
+        .await?;
+
+    record_rows(todos.len() as u64);
+    let todos = Arc::new(todos);
+    if cacheable {
+        db.cache().put(key, Value::List(todos.clone()), stamp);
+    }
+    Ok(Arc::unwrap_or_clone(todos))
 }
 
-pub async fn read(dbpool: &PgPool, id: i64) -> Result<Todo, Error> {
-    query_as::<_, Todo>("SELECT * FROM todo WHERE id = $1")
+#[instrument(name = "todo.list_version", skip(db), fields(otel.kind = "client", db.system = "postgresql", db.statement = "todo.list_version", db.rows))]
+pub async fn list_version(db: &Db, workspace_id: i64) -> Result<ListVersion, Error> {
+    let key = Key::ListVersion { workspace_id };
+    if let Some(Value::ListVersion(version)) = db.cache().get(key) {
+        return Ok(version);
+    }
+
+    let stamp = db.cache().stamp(workspace_id);
+    let (reader, cacheable) = db.cached_reader();
+    let version = query_as::<_, ListVersion>("SELECT count(*) AS count, max(updated_at) AS updated_at, coalesce(sum(comment_count), 0)::BIGINT AS comments FROM todo WHERE workspace_id = $1")
+        .bind(workspace_id)
+        .fetch_one(reader)
+        .await?;
+
+    record_rows(1);
+    if cacheable {
+        db.cache().put(key, Value::ListVersion(version.clone()), stamp);
+    }
+    Ok(version)
+}
+
+#[instrument(name = "todo.read", skip(db), fields(otel.kind = "client", db.system = "postgresql", db.statement = "todo.read", db.rows))]
+pub async fn read(db: &Db, workspace_id: i64, id: i64) -> Result<Todo, Error> {
+    let key = Key::Todo { workspace_id, id };
+    if let Some(Value::Todo(todo)) = db.cache().get(key) {
+        return Ok(Todo::clone(&todo));
+    }
+
+    let stamp = db.cache().stamp(workspace_id);
+    let (reader, cacheable) = db.cached_reader();
+    let todo = query_as::<_, Todo>("SELECT * FROM todo WHERE workspace_id = $1 AND id = $2")
+        .bind(workspace_id)
         .bind(id)
-        .fetch_one(dbpool)
-        .await
-        .map_err(Into::into)
+        .fetch_optional(reader)
+        .await?;
+
+    record_rows(todo.is_some() as u64);
+    let todo = todo.ok_or(Error::NotFound)?;
+    if cacheable {
+        db.cache().put(key, Value::Todo(Arc::new(todo.clone())), stamp);
+    }
+    Ok(todo)
+}
+
+/// Только выбранные колонки, поэтому мимо кэша: в нём лежат целые `Todo`.
+#[instrument(name = "todo.list_projected", skip(db, projection), fields(otel.kind = "client", db.system = "postgresql", db.statement = "todo.list_projected", db.rows))]
+pub async fn list_projected(db: &Db, workspace_id: i64, projection: &Projection) -> Result<Vec<PartialTodo>, Error> {
+    let rows = query(&format!(
+        "SELECT {} FROM todo WHERE workspace_id = $1 ORDER BY position, id",
+        projection.columns(),
+    ))
+        .bind(workspace_id)
+        .fetch_all(db.reader())
+        .await?;
+
+    record_rows(rows.len() as u64);
+    embed(db, workspace_id, projection, rows).await
 }
 
-pub async fn create(dbpool: &PgPool, new_todo: CreateTodo) -> Result<Todo, Error> {
-    query_as::<_, Todo>("INSERT INTO todo (body) VALUES ($1) RETURNING *")
+#[instrument(name = "todo.read_projected", skip(db, projection), fields(otel.kind = "client", db.system = "postgresql", db.statement = "todo.read_projected", db.rows))]
+pub async fn read_projected(db: &Db, workspace_id: i64, id: i64, projection: &Projection) -> Result<PartialTodo, Error> {
+    let row = query(&format!("SELECT {} FROM todo WHERE workspace_id = $1 AND id = $2", projection.columns()))
+        .bind(workspace_id)
+        .bind(id)
+        .fetch_optional(db.reader())
+        .await?;
+
+    record_rows(row.is_some() as u64);
+    let row = row.ok_or(Error::NotFound)?;
+    let mut todos = embed(db, workspace_id, projection, vec![row]).await?;
+    Ok(todos.remove(0))
+}
+
+/// Связанные данные читаются одним запросом на все todo, а не по запросу на каждый.
+async fn embed(db: &Db, workspace_id: i64, projection: &Projection, rows: Vec<PgRow>) -> Result<Vec<PartialTodo>, Error> {
+    let ids = rows.iter().map(|row| row.try_get::<i64, _>("id")).collect::<Result<Vec<_>, _>>()?;
+    let mut todos = rows.iter().map(|row| projection.to_json(row)).collect::<Result<Vec<_>, _>>()?;
+
+    for include in projection.include() {
+        let mut embedded: HashMap<i64, Vec<serde_json::Value>> = HashMap::new();
+        let mut push = |todo_id: i64, item: serde_json::Value| embedded.entry(todo_id).or_default().push(item);
+        match include {
+            Include::Comments => {
+                for comment in comment::first_pages(db, workspace_id, &ids, DEFAULT_PAGE_LIMIT).await? {
+                    push(comment.todo_id(), serde_json::to_value(&comment).expect("comment is serializable"));
+                }
+            }
+            Include::Attachments => {
+                for attachment in attachment::for_todos(db, workspace_id, &ids).await? {
+                    push(attachment.todo_id(), serde_json::to_value(&attachment).expect("attachment is serializable"));
+                }
+            }
+        }
+        for (id, todo) in ids.iter().zip(&mut todos) {
+            todo.insert(include.name().to_string(), embedded.remove(id).unwrap_or_default().into());
+        }
+    }
+    Ok(todos)
+}
+
+/// Todo, пришедший из CalDAV с этим UID.
+#[instrument(name = "todo.read_by_ical_uid", skip(db), fields(otel.kind = "client", db.system = "postgresql", db.statement = "todo.read_by_ical_uid", db.rows))]
+pub async fn read_by_ical_uid(db: &Db, workspace_id: i64, ical_uid: &str) -> Result<Option<Todo>, Error> {
+    let todo = query_as::<_, Todo>("SELECT * FROM todo WHERE workspace_id = $1 AND ical_uid = $2")
+        .bind(workspace_id)
+        .bind(ical_uid)
+        .fetch_optional(db.reader())
+        .await?;
+
+    record_rows(todo.is_some() as u64);
+    Ok(todo)
+}
+
+#[instrument(name = "todo.create", skip(db, new_todo), fields(otel.kind = "client", db.system = "postgresql", db.statement = "todo.create", db.rows))]
+pub async fn create(db: &Db, workspace_id: i64, new_todo: CreateTodo) -> Result<Todo, Error> {
+    let mut tx = db.primary().begin().await?;
+    let todo = insert(&mut tx, db.store(), workspace_id, new_todo).await?;
+    tx.commit().await?;
+
+    record_rows(1);
+    db.cache().invalidate_workspace(workspace_id);
+    Ok(todo)
+}
+
+pub(crate) async fn insert(tx: &mut sqlx::PgConnection, store: Store, workspace_id: i64, new_todo: CreateTodo) -> Result<Todo, Error> {
+    new_todo.validate()?;
+    let tz = new_todo.time_zone.unwrap_or(Tz::UTC);
+    let due_at = resolve_due_at(new_todo.recurrence.as_ref(), new_todo.due_at, tz)?;
+    let position = next_position(tx, store, workspace_id).await?;
+    let todo = query_as::<_, Todo>(
+        "INSERT INTO todo (workspace_id, body, due_at, recurrence, time_zone, position, ical_uid, done, completed_at, tags, priority)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8 IS NOT NULL, $8, $9, $10)
+         RETURNING *",
+    )
+        .bind(workspace_id)
         .bind(new_todo.body)
-        .fetch_one(dbpool)
+        .bind(due_at)
+        .bind(new_todo.recurrence.map(|rule| rule.to_string()))
+        .bind(new_todo.time_zone.map(|tz| tz.name()))
+        .bind(position)
+        .bind(new_todo.ical_uid)
+        .bind(new_todo.completed_at)
+        .bind(new_todo.tags)
+        .bind(new_todo.priority)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    event::record(tx, store, None, Some(&todo)).await?;
+    Ok(todo)
+}
+
+/// Патч разбирается по заблокированной строке: `test` и запись видят одно и то же состояние.
+#[instrument(name = "todo.update", skip(db, patch), fields(otel.kind = "client", db.system = "postgresql", db.statement = "todo.update", db.rows))]
+pub async fn update(db: &Db, workspace_id: i64, id: i64, patch: TodoPatch) -> Result<Todo, Error> {
+    let mut tx = db.primary().begin().await?;
+    let Some(current) = lock(&mut tx, workspace_id, id).await? else {
+        record_rows(0);
+        return Err(Error::NotFound);
+    };
+    let Some(update_todo) = patch.resolve(&current)? else {
+        record_rows(1);
+        return Ok(current);
+    };
+    let todo = apply_update(&mut tx, db.store(), workspace_id, current, update_todo).await?;
+    tx.commit().await?;
+
+    record_rows(1);
+    db.cache().invalidate_workspace(workspace_id);
+    Ok(todo)
+}
+
+pub(crate) async fn lock(tx: &mut sqlx::PgConnection, workspace_id: i64, id: i64) -> Result<Option<Todo>, Error> {
+    query_as::<_, Todo>("SELECT * FROM todo WHERE workspace_id = $1 AND id = $2 FOR UPDATE")
+        .bind(workspace_id)
+        .bind(id)
+        .fetch_optional(&mut *tx)
         .await
         .map_err(Into::into)
 }
 
-pub async fn update(dbpool: &PgPool, id: i64, update_todo: UpdateTodo) -> Result<Todo, Error> {
-    query_as::<_, Todo>(
+/// Изменяет todo, заблокированный через `lock`.
+pub(crate) async fn apply_update(
+    tx: &mut sqlx::PgConnection,
+    store: Store,
+    workspace_id: i64,
+    current: Todo,
+    update_todo: UpdateTodo,
+) -> Result<Todo, Error> {
+    let id = current.id();
+    let rule = update_todo.recurrence.unwrap_or_else(|| current.rule());
+    let tz = update_todo.time_zone.unwrap_or_else(|| current.time_zone());
+    let due_at = resolve_due_at(rule.as_ref(), update_todo.due_at.unwrap_or(current.due_at()), tz)?;
+    let todo = query_as::<_, Todo>(
         "UPDATE todo
          SET
            body = COALESCE($1, body),
            done = COALESCE($2, done),
+           completed_at = CASE WHEN COALESCE($2, done) THEN COALESCE(completed_at, now()) END,
+           due_at = $3,
+           recurrence = $4,
+           time_zone = COALESCE($5, time_zone),
+           tags = COALESCE($6, tags),
+           priority = $7,
            updated_at = now()
-         WHERE id = $3
+         WHERE workspace_id = $8 AND id = $9
          RETURNING *",
     )
         .bind(update_todo.body)
         .bind(update_todo.done)
+        .bind(due_at)
+        .bind(rule.map(|rule| rule.to_string()))
+        .bind(update_todo.time_zone.map(|tz| tz.name()))
+        .bind(update_todo.tags)
+        .bind(update_todo.priority.unwrap_or(current.priority()))
+        .bind(workspace_id)
         .bind(id)
-        .fetch_one(dbpool)
-        .await
-        .map_err(Into::into)
+        .fetch_one(&mut *tx)
+        .await?;
+    event::record(tx, store, Some(&current), Some(&todo)).await?;
+
+    if !current.done() && todo.done() && todo.next_todo_id().is_none() {
+        spawn_next(tx, store, workspace_id, todo).await
+    } else {
+        Ok(todo)
+    }
 }
 
-pub async fn delete(dbpool: &PgPool, id: i64) -> Result<(), Error> {
-    let deleted = query("DELETE FROM todo WHERE id = $1")
-        .bind(id)
-        .execute(dbpool)
+/// Создаёт следующее вхождение выполненного повторяющегося todo и ссылается на него.
+/// При выполнении с опозданием пропущенные вхождения не создаются.
+async fn spawn_next(tx: &mut sqlx::PgConnection, store: Store, workspace_id: i64, todo: Todo) -> Result<Todo, Error> {
+    let Some(next) = todo.schedule().and_then(|schedule| schedule.next_after(schedule.dtstart.max(Utc::now()))) else {
+        return Ok(todo);
+    };
+
+    let position = next_position(tx, store, workspace_id).await?;
+    let next = query_as::<_, Todo>(
+        "INSERT INTO todo (workspace_id, body, due_at, recurrence, time_zone, position, tags, priority)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+         RETURNING *",
+    )
+        .bind(workspace_id)
+        .bind(todo.body())
+        .bind(next.dtstart)
+        .bind(next.rule.to_string())
+        .bind(next.tz.name())
+        .bind(position)
+        .bind(todo.tags())
+        .bind(todo.priority())
+        .fetch_one(&mut *tx)
         .await?;
+    event::record(tx, store, None, Some(&next)).await?;
 
-    if deleted.rows_affected() == 0 {
+    let linked = query_as::<_, Todo>("UPDATE todo SET next_todo_id = $1 WHERE id = $2 RETURNING *")
+        .bind(next.id())
+        .bind(todo.id())
+        .fetch_one(&mut *tx)
+        .await?;
+    event::record(tx, store, Some(&todo), Some(&linked)).await?;
+    Ok(linked)
+}
+
+#[instrument(name = "todo.move", skip(db, placement), fields(otel.kind = "client", db.system = "postgresql", db.statement = "todo.move", db.rows))]
+pub async fn move_to(db: &Db, workspace_id: i64, id: i64, placement: Placement) -> Result<Todo, Error> {
+    let mut tx = db.primary().begin().await?;
+    lock_positions(&mut tx, workspace_id).await?;
+
+    let Some(current) = lock(&mut tx, workspace_id, id).await? else {
+        record_rows(0);
         return Err(Error::NotFound);
+    };
+
+    let (target, field) = match placement {
+        Placement::Before(target) => (target, "before"),
+        Placement::After(target) => (target, "after"),
+    };
+    let anchor: String = query_scalar("SELECT position FROM todo WHERE workspace_id = $1 AND id = $2")
+        .bind(workspace_id)
+        .bind(target)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| Error::Validation(StatusCode::UNPROCESSABLE_ENTITY, Message::new(i18n::Key::AnchorMissing).arg("field", field)))?;
+
+    // сосед с другой стороны от anchor; сам перемещаемый todo не считается
+    let (lower, upper) = match placement {
+        Placement::Before(_) => {
+            let lower: Option<String> = query_scalar("SELECT max(position) FROM todo WHERE workspace_id = $1 AND position < $2 AND id <> $3")
+                .bind(workspace_id)
+                .bind(&anchor)
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await?;
+            (lower, Some(anchor))
+        }
+        Placement::After(_) => {
+            let upper: Option<String> = query_scalar("SELECT min(position) FROM todo WHERE workspace_id = $1 AND position > $2 AND id <> $3")
+                .bind(workspace_id)
+                .bind(&anchor)
+                .bind(id)
+                .fetch_one(&mut *tx)
+                .await?;
+            (Some(anchor), upper)
+        }
+    };
+
+    let position = rank::between(lower.as_deref(), upper.as_deref());
+    let needs_rebalance = position.len() > rank::MAX_LEN;
+    let mut todo = query_as::<_, Todo>("UPDATE todo SET position = $1, updated_at = now() WHERE id = $2 RETURNING *")
+        .bind(position)
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+    event::record(&mut tx, db.store(), Some(&current), Some(&todo)).await?;
+
+    if needs_rebalance {
+        rebalance(&mut tx, db.store(), workspace_id).await?;
+        todo = query_as::<_, Todo>("SELECT * FROM todo WHERE id = $1")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    record_rows(1);
+    db.cache().invalidate_workspace(workspace_id);
+    Ok(todo)
+}
+
+/// Добавления в конец и перестановки сериализуются по workspace, иначе параллельные
+/// запросы могли бы выбрать один и тот же ранг.
+async fn lock_positions(tx: &mut sqlx::PgConnection, workspace_id: i64) -> Result<(), Error> {
+    query("SELECT 1 FROM workspace WHERE id = $1 FOR NO KEY UPDATE")
+        .bind(workspace_id)
+        .fetch_one(tx)
+        .await?;
+    Ok(())
+}
+
+/// Ранг для нового todo в конце списка; если он вышел длиннее `rank::MAX_LEN`,
+/// workspace сначала пересчитывается.
+async fn next_position(tx: &mut sqlx::PgConnection, store: Store, workspace_id: i64) -> Result<String, Error> {
+    lock_positions(tx, workspace_id).await?;
+    let position = rank::after(last_position(tx, workspace_id).await?.as_deref());
+    if position.len() <= rank::MAX_LEN {
+        return Ok(position);
     }
 
+    rebalance(tx, store, workspace_id).await?;
+    Ok(rank::after(last_position(tx, workspace_id).await?.as_deref()))
+}
+
+async fn last_position(tx: &mut sqlx::PgConnection, workspace_id: i64) -> Result<Option<String>, Error> {
+    let last = query_scalar("SELECT max(position) FROM todo WHERE workspace_id = $1")
+        .bind(workspace_id)
+        .fetch_one(tx)
+        .await?;
+    Ok(last)
+}
+
+/// Раздаёт todo workspace ранги заново с равными промежутками, порядок не меняется.
+async fn rebalance(tx: &mut sqlx::PgConnection, store: Store, workspace_id: i64) -> Result<(), Error> {
+    let todos = query_as::<_, Todo>("SELECT * FROM todo WHERE workspace_id = $1 ORDER BY position, id")
+        .bind(workspace_id)
+        .fetch_all(&mut *tx)
+        .await?;
+    let ids: Vec<i64> = todos.iter().map(Todo::id).collect();
+    let positions = rank::spread(ids.len());
+
+    tracing::info!(workspace_id, todos = ids.len(), "rebalancing todo positions");
+    let rebalanced = query_as::<_, Todo>(
+        "UPDATE todo t
+         SET position = ranked.position, updated_at = now()
+         FROM unnest($1::bigint[], $2::text[]) AS ranked (id, position)
+         WHERE t.id = ranked.id
+         RETURNING t.*",
+    )
+        .bind(&ids)
+        .bind(&positions)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    for after in &rebalanced {
+        let before = todos.iter().find(|todo| todo.id() == after.id());
+        event::record(tx, store, before, Some(after)).await?;
+    }
     Ok(())
+}
+
+/// Повторению нужна дата отсчёта: без `due_at` берётся ближайшее вхождение правила.
+fn resolve_due_at(rule: Option<&Rule>, due_at: Option<DateTime<Utc>>, tz: Tz) -> Result<Option<DateTime<Utc>>, Error> {
+    match (rule, due_at) {
+        (Some(rule), None) => Schedule::starting_at(rule.clone(), tz, Utc::now())
+            .map(|schedule| Some(schedule.dtstart))
+            .ok_or_else(|| Error::Validation(StatusCode::UNPROCESSABLE_ENTITY, i18n::Key::NoUpcomingOccurrence.into())),
+        (_, due_at) => Ok(due_at),
+    }
+}
+
+/// Возвращает ключи содержимого вложений удалённого todo, их удаляет вызывающий.
+#[instrument(name = "todo.delete", skip(db), fields(otel.kind = "client", db.system = "postgresql", db.statement = "todo.delete", db.rows))]
+pub async fn delete(db: &Db, workspace_id: i64, id: i64) -> Result<Vec<String>, Error> {
+    let mut tx = db.primary().begin().await?;
+    let removed = remove(&mut tx, db.store(), workspace_id, id).await?;
+    tx.commit().await?;
+
+    record_rows(removed.is_some() as u64);
+    db.cache().invalidate_workspace(workspace_id);
+    removed.ok_or(Error::NotFound)
+}
+
+/// `None`, если todo нет, иначе ключи содержимого его вложений.
+pub(crate) async fn remove(tx: &mut sqlx::PgConnection, store: Store, workspace_id: i64, id: i64) -> Result<Option<Vec<String>>, Error> {
+    let storage_keys = attachment::detach_all(tx, workspace_id, id).await?;
+    let deleted = query_as::<_, Todo>("DELETE FROM todo WHERE workspace_id = $1 AND id = $2 RETURNING *")
+        .bind(workspace_id)
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+    event::record(tx, store, deleted.as_ref(), None).await?;
+    Ok(deleted.map(|_| storage_keys))
 }
\ No newline at end of file
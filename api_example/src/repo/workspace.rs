@@ -0,0 +1,145 @@
+use crate::dto::workspace::{CreateWorkspace, Member, Role, Workspace};
+use crate::error::Error;
+use crate::i18n::Key;
+use axum::http::StatusCode;
+use sqlx::{query, query_as, query_scalar, PgPool};
+
+pub async fn list_for_user(dbpool: &PgPool, user_id: i64) -> Result<Vec<Workspace>, Error> {
+    query_as::<_, Workspace>(
+        "SELECT w.id, w.name, m.role, w.created_at
+         FROM workspace w
+         JOIN membership m ON m.workspace_id = w.id
+         WHERE m.user_id = $1
+         ORDER BY w.id",
+    )
+        .bind(user_id)
+        .fetch_all(dbpool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn create(dbpool: &PgPool, user_id: i64, new_workspace: CreateWorkspace) -> Result<Workspace, Error> {
+    let mut tx = dbpool.begin().await?;
+
+    let workspace = query_as::<_, Workspace>(
+        "WITH w AS (INSERT INTO workspace (name) VALUES ($1) RETURNING *),
+              m AS (INSERT INTO membership (workspace_id, user_id, role)
+                    SELECT id, $2, 'owner' FROM w RETURNING role)
+         SELECT w.id, w.name, m.role, w.created_at FROM w, m",
+    )
+        .bind(new_workspace.name)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(workspace)
+}
+
+pub async fn role_of(dbpool: &PgPool, workspace_id: i64, user_id: i64) -> Result<Option<Role>, Error> {
+    query_scalar::<_, Role>("SELECT role FROM membership WHERE workspace_id = $1 AND user_id = $2")
+        .bind(workspace_id)
+        .bind(user_id)
+        .fetch_optional(dbpool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn members(dbpool: &PgPool, workspace_id: i64) -> Result<Vec<Member>, Error> {
+    query_as::<_, Member>(
+        "SELECT user_id, role, created_at FROM membership WHERE workspace_id = $1 ORDER BY user_id",
+    )
+        .bind(workspace_id)
+        .fetch_all(dbpool)
+        .await
+        .map_err(Into::into)
+}
+
+pub async fn set_member(dbpool: &PgPool, workspace_id: i64, user_id: i64, role: Role) -> Result<Member, Error> {
+    let mut tx = dbpool.begin().await?;
+    lock_workspace(&mut tx, workspace_id).await?;
+
+    let member = query_as::<_, Member>(
+        "INSERT INTO membership (workspace_id, user_id, role) VALUES ($1, $2, $3)
+         ON CONFLICT (workspace_id, user_id) DO UPDATE SET role = EXCLUDED.role
+         RETURNING user_id, role, created_at",
+    )
+        .bind(workspace_id)
+        .bind(user_id)
+        .bind(role)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    ensure_has_owner(&mut tx, workspace_id).await?;
+    tx.commit().await?;
+    Ok(member)
+}
+
+pub async fn remove_member(dbpool: &PgPool, workspace_id: i64, user_id: i64) -> Result<(), Error> {
+    let mut tx = dbpool.begin().await?;
+    lock_workspace(&mut tx, workspace_id).await?;
+
+    let deleted = query("DELETE FROM membership WHERE workspace_id = $1 AND user_id = $2")
+        .bind(workspace_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    if deleted.rows_affected() == 0 {
+        return Err(Error::NotFound);
+    }
+
+    ensure_has_owner(&mut tx, workspace_id).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Делает `owner_id` владельцем workspace без участников. Такие остаются после миграции
+/// на workspace: туда переехали todo, созданные до них, а участников взять было неоткуда.
+pub async fn adopt(dbpool: &PgPool, owner_id: i64) -> Result<u64, Error> {
+    let adopted = query(
+        "INSERT INTO membership (workspace_id, user_id, role)
+         SELECT w.id, $1, 'owner' FROM workspace w
+         WHERE NOT EXISTS (SELECT 1 FROM membership m WHERE m.workspace_id = w.id)
+         ON CONFLICT DO NOTHING",
+    )
+        .bind(owner_id)
+        .execute(dbpool)
+        .await?;
+    Ok(adopted.rows_affected())
+}
+
+/// Сколько workspace без участников: их todo через API не видит никто.
+pub async fn orphaned(dbpool: &PgPool) -> Result<i64, Error> {
+    query_scalar(
+        "SELECT count(*) FROM workspace w
+         WHERE NOT EXISTS (SELECT 1 FROM membership m WHERE m.workspace_id = w.id)",
+    )
+        .fetch_one(dbpool)
+        .await
+        .map_err(Into::into)
+}
+
+// изменения участников сериализуются по workspace, чтобы параллельно не убрать последнего owner
+async fn lock_workspace(tx: &mut sqlx::PgConnection, workspace_id: i64) -> Result<(), Error> {
+    query("SELECT 1 FROM workspace WHERE id = $1 FOR UPDATE")
+        .bind(workspace_id)
+        .fetch_one(tx)
+        .await?;
+    Ok(())
+}
+
+async fn ensure_has_owner(tx: &mut sqlx::PgConnection, workspace_id: i64) -> Result<(), Error> {
+    let owners: i64 = query_scalar("SELECT count(*) FROM membership WHERE workspace_id = $1 AND role = 'owner'")
+        .bind(workspace_id)
+        .fetch_one(tx)
+        .await?;
+
+    if owners == 0 {
+        return Err(Error::Validation(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Key::LastOwner.into(),
+        ));
+    }
+    Ok(())
+}
\ No newline at end of file
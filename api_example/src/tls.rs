@@ -0,0 +1,201 @@
+use axum::Router;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::server::graceful::GracefulShutdown;
+use hyper_util::service::TowerToHyperService;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Настройки TLS из окружения:
+/// `TLS_CERT_PATH`, `TLS_KEY_PATH` - сертификат (цепочка) и ключ в PEM,
+/// `TLS_CLIENT_CA_PATH` - если задан, клиенты обязаны предъявить сертификат от этого CA (mTLS),
+/// `TLS_RELOAD_INTERVAL_SECS` - как часто проверять файлы на изменение (по умолчанию 30).
+#[derive(Clone, Debug)]
+pub struct TlsSettings {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    client_ca_path: Option<PathBuf>,
+    reload_interval: Duration,
+}
+
+impl TlsSettings {
+    pub fn new(cert_path: impl Into<PathBuf>, key_path: impl Into<PathBuf>) -> Self {
+        TlsSettings {
+            cert_path: cert_path.into(),
+            key_path: key_path.into(),
+            client_ca_path: None,
+            reload_interval: Duration::from_secs(30),
+        }
+    }
+
+    /// Требовать от клиентов сертификат от CA из этого файла.
+    pub fn with_client_ca(mut self, path: impl Into<PathBuf>) -> Self {
+        self.client_ca_path = Some(path.into());
+        self
+    }
+
+    pub fn with_reload_interval(mut self, interval: Duration) -> Self {
+        self.reload_interval = interval;
+        self
+    }
+
+    pub fn from_env() -> Option<Self> {
+        let cert_path = std::env::var_os("TLS_CERT_PATH")?;
+        let key_path = std::env::var_os("TLS_KEY_PATH").expect("TLS_KEY_PATH must be set together with TLS_CERT_PATH");
+        let mut settings = TlsSettings::new(cert_path, key_path);
+        if let Some(path) = std::env::var_os("TLS_CLIENT_CA_PATH") {
+            settings = settings.with_client_ca(path);
+        }
+        if let Some(secs) = std::env::var("TLS_RELOAD_INTERVAL_SECS").ok().and_then(|secs| secs.parse().ok()) {
+            settings = settings.with_reload_interval(Duration::from_secs(secs));
+        }
+        Some(settings)
+    }
+
+    fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+        [&self.cert_path, &self.key_path].into_iter().chain(self.client_ca_path.as_ref())
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        self.paths()
+            .map(|path| std::fs::metadata(path).and_then(|meta| meta.modified()).ok())
+            .max()
+            .flatten()
+    }
+
+    pub(crate) fn load(&self) -> Result<ServerConfig, String> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+
+        let certs = CertificateDer::pem_file_iter(&self.cert_path)
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| format!("can not read {}: {}", self.cert_path.display(), e))?;
+        let key = PrivateKeyDer::from_pem_file(&self.key_path)
+            .map_err(|e| format!("can not read {}: {}", self.key_path.display(), e))?;
+
+        let builder = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| e.to_string())?;
+
+        let builder = match &self.client_ca_path {
+            Some(ca_path) => {
+                let mut roots = RootCertStore::empty();
+                for cert in CertificateDer::pem_file_iter(ca_path)
+                    .map_err(|e| format!("can not read {}: {}", ca_path.display(), e))?
+                {
+                    let cert = cert.map_err(|e| format!("can not read {}: {}", ca_path.display(), e))?;
+                    roots.add(cert).map_err(|e| e.to_string())?;
+                }
+                let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                    .build()
+                    .map_err(|e| e.to_string())?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        let mut config = builder.with_single_cert(certs, key).map_err(|e| e.to_string())?;
+        // HTTP/2 выбирается через ALPN, старые клиенты остаются на HTTP/1.1
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(config)
+    }
+}
+
+/// Аналог `axum::serve(..).with_graceful_shutdown(shutdown)` поверх TLS.
+/// Сертификаты перечитываются при изменении файлов, уже открытые соединения продолжают работать со старыми.
+/// После `shutdown` новые соединения не принимаются, открытые дорабатывают начатые запросы.
+pub async fn serve(
+    listener: TcpListener,
+    router: Router,
+    settings: TlsSettings,
+    shutdown: impl Future<Output = ()>,
+) -> std::io::Result<()> {
+    let config = settings
+        .load()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    let current = Arc::new(RwLock::new(Arc::new(config)));
+
+    let watcher = tokio::spawn(watch(settings, current.clone()));
+    let graceful = GracefulShutdown::new();
+    let mut shutdown = std::pin::pin!(shutdown);
+
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = &mut shutdown => break,
+        };
+        let (stream, peer) = match accepted {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                tracing::warn!("accept failed: {}", e);
+                continue;
+            }
+        };
+
+        let acceptor = TlsAcceptor::from(current.read().unwrap().clone());
+        let router = router.clone();
+        // берётся до рукопожатия, чтобы остановка дождалась и его
+        let connection = graceful.watcher();
+
+        tokio::spawn(async move {
+            let stream = match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(e)) => {
+                    tracing::debug!("TLS handshake with {} failed: {}", peer, e);
+                    return;
+                }
+                Err(_) => {
+                    tracing::debug!("TLS handshake with {} timed out", peer);
+                    return;
+                }
+            };
+
+            let service = TowerToHyperService::new(router);
+            let builder = auto::Builder::new(TokioExecutor::new());
+            if let Err(e) = connection
+                .watch(builder.serve_connection_with_upgrades(TokioIo::new(stream), service))
+                .await
+            {
+                tracing::debug!("connection with {} closed: {}", peer, e);
+            }
+        });
+    }
+
+    drop(listener);
+    watcher.abort();
+    graceful.shutdown().await;
+    Ok(())
+}
+
+async fn watch(settings: TlsSettings, current: Arc<RwLock<Arc<ServerConfig>>>) {
+    let mut last_modified = settings.modified();
+    let mut interval = tokio::time::interval(settings.reload_interval);
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let modified = settings.modified();
+        if modified == last_modified {
+            continue;
+        }
+
+        match settings.load() {
+            Ok(config) => {
+                *current.write().unwrap() = Arc::new(config);
+                last_modified = modified;
+                tracing::info!("TLS certificates reloaded");
+            }
+            // файлы могли записать не полностью - попробуем на следующем тике
+            Err(e) => tracing::warn!("TLS reload failed, keeping previous certificates: {}", e),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,178 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::{json, Value};
+
+async fn create_key(app: &TestApp, user_id: i64, body: Value) -> Value {
+    app.post("/v1/api-keys")
+        .user(user_id)
+        .json(body)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json()
+}
+
+#[tokio::test]
+async fn personal_key_acts_as_its_owner() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    app.todo(1, workspace_id, "todo").await;
+
+    let key = create_key(&app, 1, json!({ "name": "cli", "scopes": ["todos:read", "todos:write"] })).await;
+    let token = key["token"].as_str().unwrap();
+    assert!(token.starts_with("tk_"));
+    assert!(token.starts_with(key["prefix"].as_str().unwrap()));
+    assert_eq!(key["kind"], "personal");
+
+    let list = app.get("/v1/todos").bearer(token).workspace(workspace_id).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(list.as_array().unwrap().len(), 1);
+    app.get("/v1/todos")
+        .header("x-api-key", token)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let keys = app.get("/v1/api-keys").user(1).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(keys.as_array().unwrap().len(), 1);
+    assert!(keys[0].get("token").is_none());
+    assert!(!keys[0]["last_used_at"].is_null());
+}
+
+#[tokio::test]
+async fn scopes_limit_routes() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "todo").await;
+
+    let key = create_key(&app, 1, json!({ "name": "read only", "scopes": ["todos:read"] })).await;
+    let token = key["token"].as_str().unwrap();
+
+    app.get(&format!("/v1/todos/{id}")).bearer(token).workspace(workspace_id).send().await.assert_status(StatusCode::OK);
+    app.patch(&format!("/v1/todos/{id}"))
+        .bearer(token)
+        .workspace(workspace_id)
+        .json(json!({ "done": true }))
+        .send()
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "forbidden");
+
+    // ключами нельзя управлять с помощью ключа
+    app.get("/v1/api-keys").bearer(token).send().await.assert_error(StatusCode::FORBIDDEN, "forbidden");
+    app.post("/v1/api-keys")
+        .bearer(token)
+        .json(json!({ "name": "nested", "scopes": ["todos:read"] }))
+        .send()
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "forbidden");
+}
+
+/// Ключ, даже owner-а и с записью, не меняет состав и роли workspace.
+#[tokio::test]
+async fn keys_can_not_manage_workspaces() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    app.add_member(1, workspace_id, 2, "viewer").await;
+    let members = format!("/v1/workspaces/{workspace_id}/members");
+
+    for scopes in [json!(["todos:read"]), json!(["todos:read", "todos:write"])] {
+        let key = create_key(&app, 1, json!({ "name": "cli", "scopes": scopes })).await;
+        let token = key["token"].as_str().unwrap();
+
+        app.get(&members).bearer(token).send().await.assert_status(StatusCode::OK);
+        app.put(&format!("{members}/2"))
+            .bearer(token)
+            .json(json!({ "role": "owner" }))
+            .send()
+            .await
+            .assert_error(StatusCode::FORBIDDEN, "forbidden");
+        app.put(&format!("{members}/3"))
+            .bearer(token)
+            .json(json!({ "role": "editor" }))
+            .send()
+            .await
+            .assert_error(StatusCode::FORBIDDEN, "forbidden");
+        app.delete(&format!("{members}/2")).bearer(token).send().await.assert_error(StatusCode::FORBIDDEN, "forbidden");
+        app.delete(&format!("{members}/1")).bearer(token).send().await.assert_error(StatusCode::FORBIDDEN, "forbidden");
+        app.post("/v1/workspaces")
+            .bearer(token)
+            .json(json!({ "name": "new" }))
+            .send()
+            .await
+            .assert_error(StatusCode::FORBIDDEN, "forbidden");
+    }
+
+    // без todos:read ключ не видит и списки
+    let key = create_key(&app, 1, json!({ "name": "writer", "scopes": ["todos:write"] })).await;
+    let token = key["token"].as_str().unwrap();
+    app.get("/v1/workspaces").bearer(token).send().await.assert_error(StatusCode::FORBIDDEN, "forbidden");
+    app.get(&members).bearer(token).send().await.assert_error(StatusCode::FORBIDDEN, "forbidden");
+
+    let roles = app.get(&members).user(1).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(roles.as_array().unwrap().len(), 2);
+    assert_eq!(roles[1]["role"], "viewer");
+}
+
+#[tokio::test]
+async fn service_key_is_bound_to_workspace() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let other = app.workspace(1).await;
+    app.add_member(1, workspace_id, 2, "editor").await;
+
+    app.post("/v1/api-keys")
+        .user(2)
+        .json(json!({ "name": "ci", "workspace_id": workspace_id, "scopes": ["todos:read"] }))
+        .send()
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "forbidden");
+    app.post("/v1/api-keys")
+        .user(3)
+        .json(json!({ "name": "ci", "workspace_id": workspace_id, "scopes": ["todos:read"] }))
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+
+    let key = create_key(&app, 1, json!({ "name": "ci", "workspace_id": workspace_id, "scopes": ["todos:read"] })).await;
+    assert_eq!(key["kind"], "service");
+    let token = key["token"].as_str().unwrap();
+
+    app.get("/v1/todos").bearer(token).workspace(workspace_id).send().await.assert_status(StatusCode::OK);
+    app.get("/v1/todos").bearer(token).workspace(other).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn revoked_key_is_rejected() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+
+    let key = create_key(&app, 1, json!({ "name": "old", "scopes": ["todos:read"] })).await;
+    let token = key["token"].as_str().unwrap();
+    let id = key["id"].as_i64().unwrap();
+
+    app.delete(&format!("/v1/api-keys/{id}")).user(2).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+    app.delete(&format!("/v1/api-keys/{id}")).user(1).send().await.assert_status(StatusCode::NO_CONTENT);
+    app.delete(&format!("/v1/api-keys/{id}")).user(1).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+
+    app.get("/v1/todos").bearer(token).workspace(workspace_id).send().await.assert_error(StatusCode::UNAUTHORIZED, "unauthorized");
+}
+
+#[tokio::test]
+async fn invalid_keys_are_rejected() {
+    let app = TestApp::spawn().await;
+
+    for body in [
+        json!({ "name": "", "scopes": ["todos:read"] }),
+        json!({ "name": "no scopes", "scopes": [] }),
+        json!({ "name": "expired", "scopes": ["todos:read"], "expires_at": "2000-01-01T00:00:00Z" }),
+    ] {
+        app.post("/v1/api-keys")
+            .user(1)
+            .json(body)
+            .send()
+            .await
+            .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    }
+}
\ No newline at end of file
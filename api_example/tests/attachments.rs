@@ -0,0 +1,193 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{TestApp, ATTACHMENT_MAX_BYTES};
+use futures_util::future::join_all;
+use serde_json::json;
+
+#[tokio::test]
+async fn upload_list_download_delete() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "with files").await;
+    let attachments = format!("/v1/todos/{id}/attachments");
+
+    let png = b"\x89PNG\r\n\x1a\n0000";
+    let attachment = app
+        .post(&attachments)
+        .user(1)
+        .workspace(workspace_id)
+        .multipart("file", "../uploads/pic.png", png)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(attachment["file_name"], "pic.png");
+    assert_eq!(attachment["content_type"], "image/png");
+    assert_eq!(attachment["size_bytes"], png.len());
+    assert!(attachment.get("storage_key").is_none());
+    let attachment_id = attachment["id"].as_i64().unwrap();
+    assert_eq!(app.blob_count(), 1);
+
+    let list = app.get(&attachments).user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(list, serde_json::json!([attachment]));
+
+    let download = app
+        .get(&format!("{attachments}/{attachment_id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(&download.body[..], png);
+    assert_eq!(download.header("content-type"), Some("image/png"));
+    assert_eq!(download.header("content-disposition"), Some("attachment; filename=\"pic.png\""));
+    assert_eq!(download.header("x-content-type-options"), Some("nosniff"));
+
+    app.delete(&format!("{attachments}/{attachment_id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert_eq!(app.blob_count(), 0);
+    app.get(&format!("{attachments}/{attachment_id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn non_ascii_file_names_are_encoded() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "with files").await;
+    let attachments = format!("/v1/todos/{id}/attachments");
+
+    let attachment = app
+        .post(&attachments)
+        .user(1)
+        .workspace(workspace_id)
+        .multipart("file", "Отчёт за май.pdf", b"%PDF-1.7")
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(attachment["file_name"], "Отчёт за май.pdf");
+
+    let download = app
+        .get(&format!("{attachments}/{}", attachment["id"]))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    // заголовок целиком в ASCII, полное имя - в filename* (RFC 5987)
+    assert_eq!(
+        download.header("content-disposition"),
+        Some(
+            "attachment; filename=\"_____ __ ___.pdf\"; \
+             filename*=UTF-8''%D0%9E%D1%82%D1%87%D1%91%D1%82%20%D0%B7%D0%B0%20%D0%BC%D0%B0%D0%B9.pdf"
+        ),
+    );
+}
+
+#[tokio::test]
+async fn deleting_todo_removes_blobs() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "with files").await;
+    for name in ["a.txt", "b.txt"] {
+        app.post(&format!("/v1/todos/{id}/attachments"))
+            .user(1)
+            .workspace(workspace_id)
+            .multipart("file", name, b"text")
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+    assert_eq!(app.blob_count(), 2);
+
+    app.delete(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).send().await.assert_status(StatusCode::NO_CONTENT);
+    assert_eq!(app.blob_count(), 0);
+}
+
+#[tokio::test]
+async fn uploads_racing_todo_deletion_leave_no_blobs() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    for round in 0..10 {
+        let id = app.todo(1, workspace_id, &format!("round {round}")).await;
+        let uploads = (0..4).map(|n| {
+            app.post(&format!("/v1/todos/{id}/attachments"))
+                .user(1)
+                .workspace(workspace_id)
+                .multipart("file", &format!("{n}.txt"), b"text")
+                .send()
+        });
+        let delete = app.delete(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).send();
+        let (_, deleted) = tokio::join!(join_all(uploads), delete);
+        deleted.assert_status(StatusCode::NO_CONTENT);
+        // вложение либо успело и удалено вместе с todo, либо не нашло todo и убрало свой blob
+        assert_eq!(app.blob_count(), 0, "round {round}");
+    }
+
+    // удаление через sync тоже убирает содержимое
+    let id = app.todo(1, workspace_id, "synced").await;
+    app.post(&format!("/v1/todos/{id}/attachments"))
+        .user(1)
+        .workspace(workspace_id)
+        .multipart("file", "a.txt", b"text")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.post("/v1/sync")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "changes": [{ "id": id, "deleted": true, "updated_at": "2099-01-01T00:00:00Z" }] }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(app.blob_count(), 0);
+}
+
+#[tokio::test]
+async fn invalid_uploads_are_rejected() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "with files").await;
+    let attachments = format!("/v1/todos/{id}/attachments");
+
+    app.post(&attachments)
+        .user(1)
+        .workspace(workspace_id)
+        .multipart("file", "big.bin", &vec![1; ATTACHMENT_MAX_BYTES + 1])
+        .send()
+        .await
+        .assert_error(StatusCode::PAYLOAD_TOO_LARGE, "validateion_error");
+    app.post(&attachments)
+        .user(1)
+        .workspace(workspace_id)
+        .multipart("file", "empty.txt", b"")
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    app.post(&attachments)
+        .user(1)
+        .workspace(workspace_id)
+        .multipart("document", "a.txt", b"text")
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    app.post("/v1/todos/4242/attachments")
+        .user(1)
+        .workspace(workspace_id)
+        .multipart("file", "a.txt", b"text")
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+
+    assert_eq!(app.blob_count(), 0);
+}
\ No newline at end of file
@@ -0,0 +1,30 @@
+//! Файловое хранилище вложений.
+
+use api_example::blob::BlobStore;
+use api_example::blob::fs::FsStore;
+use futures_util::StreamExt;
+use futures_util::future::join_all;
+
+#[tokio::test]
+async fn concurrent_puts_of_one_key_do_not_interleave() {
+    let root = std::env::temp_dir().join(format!("todo_test_fs_{}", hex::encode(rand::random::<[u8; 8]>())));
+    let store = FsStore::new(&root);
+    let contents: Vec<Vec<u8>> = (0..8u8).map(|n| vec![n; 256 * 1024]).collect();
+
+    join_all(contents.iter().map(|data| store.put("1/2/key", "application/octet-stream", data.clone().into())))
+        .await
+        .into_iter()
+        .for_each(|put| put.unwrap());
+
+    // побеждает одна из записей целиком, временных файлов не остаётся
+    let mut stored = Vec::new();
+    let mut stream = store.get("1/2/key").await.unwrap();
+    while let Some(chunk) = stream.next().await {
+        stored.extend_from_slice(&chunk.unwrap());
+    }
+    assert!(contents.contains(&stored), "blob mixes several writes");
+    let files: Vec<_> = std::fs::read_dir(root.join("1/2")).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+    assert_eq!(files, ["key"]);
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
\ No newline at end of file
@@ -0,0 +1,135 @@
+//! Кэш чтений todo: попадания, сброс по записи и по NOTIFY, TTL и ёмкость.
+
+mod common;
+
+use api_example::repo::cache::TodoCache;
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+async fn read_body(app: &TestApp, workspace_id: i64, id: i64) -> String {
+    let todo = app.get(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK).json();
+    todo["body"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn repeated_reads_are_served_from_cache() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "cached").await;
+
+    read_body(&app, workspace_id, id).await;
+    let stats = app.db.cache().stats();
+    read_body(&app, workspace_id, id).await;
+    assert_eq!(app.db.cache().stats().hits, stats.hits + 1);
+    assert_eq!(app.db.cache().stats().misses, stats.misses);
+
+    // список и его версия для ETag кэшируются отдельно от todo
+    app.get("/v1/todos").user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK);
+    let stats = app.db.cache().stats();
+    let list = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(list[0]["body"], "cached");
+    assert!(app.db.cache().stats().hits > stats.hits);
+    assert_eq!(app.db.cache().stats().misses, stats.misses);
+    assert!(app.db.cache().stats().entries >= 2);
+}
+
+#[tokio::test]
+async fn writes_invalidate_their_workspace_only() {
+    let app = TestApp::spawn().await;
+    let changed = app.workspace(1).await;
+    let untouched = app.workspace(1).await;
+    let id = app.todo(1, changed, "before").await;
+    let other = app.todo(1, untouched, "other").await;
+    read_body(&app, changed, id).await;
+    read_body(&app, untouched, other).await;
+
+    app.patch(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(changed)
+        .json(json!({ "body": "after" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let stats = app.db.cache().stats();
+    assert_eq!(read_body(&app, untouched, other).await, "other");
+    assert_eq!(app.db.cache().stats().hits, stats.hits + 1);
+    assert_eq!(read_body(&app, changed, id).await, "after");
+    assert_eq!(app.db.cache().stats().misses, stats.misses + 1);
+
+    // удаление тоже сбрасывает кэш
+    app.delete(&format!("/v1/todos/{id}")).user(1).workspace(changed).send().await.assert_status(StatusCode::NO_CONTENT);
+    app.get(&format!("/v1/todos/{id}")).user(1).workspace(changed).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn changes_from_other_instances_arrive_via_notify() {
+    let app = TestApp::spawn_with_listener(TodoCache::new(1000, Duration::from_secs(30))).await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "v0").await;
+
+    // LISTEN поднимается в фоне: пока его нет, NOTIFY теряется, поэтому пишем, пока не дойдёт
+    let started = Instant::now();
+    for version in 1.. {
+        read_body(&app, workspace_id, id).await;
+        let body = format!("v{version}");
+        sqlx::query("UPDATE todo SET body = $1 WHERE id = $2").bind(&body).bind(id).execute(app.db.primary()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        if read_body(&app, workspace_id, id).await == body {
+            break;
+        }
+        assert!(started.elapsed() < Duration::from_secs(5), "cache was not invalidated by NOTIFY");
+    }
+}
+
+#[tokio::test]
+async fn entries_expire_after_ttl() {
+    let ttl = Duration::from_secs(1);
+    let app = TestApp::spawn_with_cache(TodoCache::new(1000, ttl)).await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "short-lived").await;
+
+    // запись попадает в кэш не раньше started, значит и протухнуть раньше started + ttl не может
+    let started = Instant::now();
+    read_body(&app, workspace_id, id).await;
+    let stats = app.db.cache().stats();
+    read_body(&app, workspace_id, id).await;
+    assert_eq!(app.db.cache().stats().hits, stats.hits + 1);
+
+    let stats = app.db.cache().stats();
+    loop {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        read_body(&app, workspace_id, id).await;
+        if app.db.cache().stats().misses > stats.misses {
+            break;
+        }
+        assert!(started.elapsed() < ttl * 5, "entry did not expire");
+    }
+    assert!(started.elapsed() >= ttl, "entry expired before its ttl");
+}
+
+#[tokio::test]
+async fn capacity_bounds_the_cache() {
+    let app = TestApp::spawn_with_cache(TodoCache::new(2, Duration::from_secs(30))).await;
+    let workspace_id = app.workspace(1).await;
+    let mut ids = Vec::new();
+    for body in ["one", "two", "three"] {
+        ids.push(app.todo(1, workspace_id, body).await);
+    }
+    for id in ids {
+        read_body(&app, workspace_id, id).await;
+        assert!(app.db.cache().stats().entries <= 2);
+    }
+    assert_eq!(app.db.cache().stats().entries, 2);
+
+    // нулевая ёмкость выключает кэш
+    let app = TestApp::spawn_with_cache(TodoCache::new(0, Duration::from_secs(30))).await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "uncached").await;
+    read_body(&app, workspace_id, id).await;
+    read_body(&app, workspace_id, id).await;
+    let stats = app.db.cache().stats();
+    assert_eq!((stats.hits, stats.entries), (0, 0));
+}
\ No newline at end of file
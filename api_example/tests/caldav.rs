@@ -0,0 +1,255 @@
+mod common;
+
+use api_example::ical::{self, Status};
+use axum::http::{Method, StatusCode};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use common::TestApp;
+use serde_json::json;
+
+const PROPFIND: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav" xmlns:cs="http://calendarserver.org/ns/">
+  <d:prop><d:current-user-principal/><c:calendar-home-set/><d:resourcetype/><d:displayname/><cs:getctag/><d:getetag/></d:prop>
+</d:propfind>"#;
+
+// RFC 5545, 4, без VALARM
+const INCOME_TAXES: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+PRODID:-//ABC Corporation//NONSGML My Product//EN\r
+BEGIN:VTODO\r
+DTSTAMP:19980130T134500Z\r
+SEQUENCE:2\r
+UID:uid4@example.com\r
+DUE:19980415T000000\r
+STATUS:NEEDS-ACTION\r
+SUMMARY:Submit Income Taxes\r
+END:VTODO\r
+END:VCALENDAR\r
+";
+
+fn basic(token: &str) -> String {
+    format!("Basic {}", STANDARD.encode(format!("calendar:{token}")))
+}
+
+async fn api_key(app: &TestApp, user_id: i64, workspace_id: Option<i64>) -> String {
+    app.post("/v1/api-keys")
+        .user(user_id)
+        .json(json!({ "name": "calendar", "workspace_id": workspace_id, "scopes": ["todos:read", "todos:write"] }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json()["token"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn workspace_feed_lists_todos() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    app.todo(1, workspace_id, "open").await;
+    let done = app.todo(1, workspace_id, "done").await;
+    app.patch(&format!("/v1/todos/{done}")).user(1).workspace(workspace_id).json(json!({ "done": true })).send().await;
+
+    let feed = app.get("/v1/todos.ics").user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK);
+    assert_eq!(feed.header("content-type"), Some("text/calendar; charset=utf-8"));
+    assert!(feed.text().contains("X-WR-CALNAME:workspace\r\n"));
+    let todos = ical::parse(&feed.text()).unwrap();
+    assert_eq!(todos.len(), 2);
+    assert_eq!(todos[0].summary.as_deref(), Some("open"));
+    assert_eq!(todos[0].status, Some(Status::NeedsAction));
+    assert_eq!(todos[1].uid, format!("todo-{done}@api_example"));
+    assert_eq!(todos[1].status, Some(Status::Completed));
+    assert!(todos[1].completed.is_some());
+    assert!(todos[1].last_modified.is_some());
+
+    let etag = feed.header("etag").unwrap().to_string();
+    app.get(&format!("/v1/workspaces/{workspace_id}/todos.ics"))
+        .user(1)
+        .header("if-none-match", etag)
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_MODIFIED);
+}
+
+#[tokio::test]
+async fn user_feed_spans_workspaces() {
+    let app = TestApp::spawn().await;
+    let home = app.workspace(1).await;
+    let work = app.workspace(2).await;
+    app.add_member(2, work, 1, "viewer").await;
+    app.todo(1, home, "home").await;
+    app.todo(2, work, "work").await;
+    app.todo(2, app.workspace(2).await, "private").await;
+
+    // календари подписываются по URL с паролем, поэтому ключ принимается через Basic
+    let token = api_key(&app, 1, None).await;
+    let feed = app.get("/v1/calendar.ics").header("authorization", basic(&token)).send().await.assert_status(StatusCode::OK);
+    let summaries: Vec<_> = ical::parse(&feed.text()).unwrap().into_iter().filter_map(|todo| todo.summary).collect();
+    assert_eq!(summaries, vec!["home", "work"]);
+
+    let service = api_key(&app, 1, Some(home)).await;
+    let feed = app.get("/v1/calendar.ics").bearer(&service).send().await.assert_status(StatusCode::OK);
+    assert_eq!(ical::parse(&feed.text()).unwrap().len(), 1);
+
+    let anonymous = app.get("/v1/calendar.ics").send().await.assert_error(StatusCode::UNAUTHORIZED, "unauthorized");
+    assert_eq!(anonymous.header("www-authenticate"), Some("Basic realm=\"todo\", charset=\"UTF-8\""));
+}
+
+#[tokio::test]
+async fn caldav_discovery() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+
+    let redirect = app.get("/.well-known/caldav").send().await.assert_status(StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(redirect.header("location"), Some("/dav/"));
+    let options = app.request(Method::OPTIONS, "/dav/").send().await.assert_status(StatusCode::OK);
+    assert!(options.header("dav").unwrap().contains("calendar-access"));
+    let anonymous = app.request(Method::from_bytes(b"PROPFIND").unwrap(), "/dav/").send().await;
+    assert_eq!(anonymous.status, StatusCode::UNAUTHORIZED);
+    assert!(anonymous.header("www-authenticate").is_some());
+
+    let propfind = |uri: &str, depth: &str| {
+        app.request(Method::from_bytes(b"PROPFIND").unwrap(), uri)
+            .user(1)
+            .header("depth", depth)
+            .body("application/xml", PROPFIND)
+    };
+    let principal = propfind("/dav/", "0").send().await.assert_status(StatusCode::MULTI_STATUS).text();
+    assert!(principal.contains("<d:current-user-principal><d:href>/dav/</d:href></d:current-user-principal>"));
+    assert!(principal.contains("<c:calendar-home-set><d:href>/dav/workspaces/</d:href></c:calendar-home-set>"));
+    assert!(principal.contains("<d:propstat><d:prop><d:displayname/><cs:getctag/><d:getetag/></d:prop><d:status>HTTP/1.1 404 Not Found</d:status></d:propstat>"));
+
+    let home = propfind("/dav/workspaces/", "1").send().await.assert_status(StatusCode::MULTI_STATUS).text();
+    assert!(home.contains(&format!("<d:href>/dav/workspaces/{workspace_id}/</d:href>")));
+    assert!(home.contains("<d:resourcetype><d:collection/><c:calendar/></d:resourcetype><d:displayname>workspace</d:displayname>"));
+    assert!(home.contains("<cs:getctag>"));
+}
+
+#[tokio::test]
+async fn caldav_round_trip() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let api_todo = app.todo(1, workspace_id, "from api").await;
+    let token = api_key(&app, 1, None).await;
+    let calendar = format!("/dav/workspaces/{workspace_id}/");
+    let href = format!("{calendar}uid4@example.com.ics");
+    let dav = |method: &[u8], uri: &str| app.request(Method::from_bytes(method).unwrap(), uri).header("authorization", basic(&token));
+
+    let created = dav(b"PUT", &href)
+        .header("if-none-match", "*")
+        .body("text/calendar", INCOME_TAXES)
+        .send()
+        .await
+        .assert_status(StatusCode::CREATED);
+    let etag = created.header("etag").unwrap().to_string();
+    dav(b"PUT", &href)
+        .header("if-none-match", "*")
+        .body("text/calendar", INCOME_TAXES)
+        .send()
+        .await
+        .assert_status(StatusCode::PRECONDITION_FAILED);
+
+    let list = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.json();
+    assert_eq!(list[1]["body"], "Submit Income Taxes");
+    assert_eq!(list[1]["due_at"], "1998-04-15T00:00:00Z");
+    assert_eq!(list[1]["ical_uid"], "uid4@example.com");
+
+    let resource = dav(b"GET", &href).send().await.assert_status(StatusCode::OK);
+    assert_eq!(resource.header("etag"), Some(etag.as_str()));
+    let original = ical::parse(INCOME_TAXES).unwrap().remove(0);
+    let stored = ical::parse(&resource.text()).unwrap().remove(0);
+    assert_eq!((&stored.uid, &stored.summary, stored.due, stored.status), (&original.uid, &original.summary, original.due, original.status));
+
+    let completed = INCOME_TAXES.replace("STATUS:NEEDS-ACTION", "STATUS:COMPLETED");
+    dav(b"PUT", &href)
+        .header("if-match", "\"stale\"")
+        .body("text/calendar", &completed)
+        .send()
+        .await
+        .assert_status(StatusCode::PRECONDITION_FAILED);
+    dav(b"PUT", &href)
+        .header("if-match", &etag)
+        .body("text/calendar", &completed)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert_eq!(app.get("/v1/todos").user(1).workspace(workspace_id).send().await.json()[1]["done"], true);
+
+    let multiget = format!(
+        r#"<c:calendar-multiget xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+             <d:prop><d:getetag/><c:calendar-data/></d:prop>
+             <d:href>{href}</d:href><d:href>{calendar}missing.ics</d:href>
+           </c:calendar-multiget>"#
+    );
+    let report = dav(b"REPORT", &calendar).body("application/xml", &multiget).send().await.assert_status(StatusCode::MULTI_STATUS).text();
+    assert!(report.contains("<d:href>/dav/workspaces/"));
+    assert!(report.contains("STATUS:COMPLETED"));
+    assert!(report.contains(&format!("<d:href>{calendar}missing.ics</d:href><d:status>HTTP/1.1 404 Not Found</d:status>")));
+
+    let query = r#"<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+                     <d:prop><d:getetag/></d:prop>
+                     <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VTODO"/></c:comp-filter></c:filter>
+                   </c:calendar-query>"#;
+    let report = dav(b"REPORT", &calendar).body("application/xml", query).send().await.assert_status(StatusCode::MULTI_STATUS).text();
+    assert!(report.contains(&format!("<d:href>{calendar}{api_todo}.ics</d:href>")));
+    assert!(report.contains(&format!("<d:href>{calendar}uid4@example.com.ics</d:href>")));
+    let events = query.replace("VTODO", "VEVENT");
+    let report = dav(b"REPORT", &calendar).body("application/xml", &events).send().await.assert_status(StatusCode::MULTI_STATUS).text();
+    assert!(!report.contains("<d:response>"));
+
+    dav(b"DELETE", &href).header("if-match", &etag).send().await.assert_status(StatusCode::PRECONDITION_FAILED);
+    dav(b"DELETE", &href).send().await.assert_status(StatusCode::NO_CONTENT);
+    dav(b"GET", &href).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn caldav_checks_roles_and_names() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    app.add_member(1, workspace_id, 2, "viewer").await;
+    let id = app.todo(1, workspace_id, "shared").await;
+    let calendar = format!("/dav/workspaces/{workspace_id}/");
+
+    app.get(&format!("{calendar}{id}.ics")).user(2).send().await.assert_status(StatusCode::OK);
+    app.put(&format!("{calendar}{id}.ics"))
+        .user(2)
+        .body("text/calendar", INCOME_TAXES)
+        .send()
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "forbidden");
+    app.delete(&format!("{calendar}{id}.ics")).user(2).send().await.assert_error(StatusCode::FORBIDDEN, "forbidden");
+    app.request(Method::from_bytes(b"PROPFIND").unwrap(), &calendar)
+        .user(3)
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+
+    // новый ресурс называется по UID, числовые имена заняты id
+    for name in ["other.ics", "4242.ics"] {
+        app.put(&format!("{calendar}{name}"))
+            .user(1)
+            .body("text/calendar", INCOME_TAXES)
+            .send()
+            .await
+            .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    }
+    app.put(&format!("{calendar}uid4@example.com.ics"))
+        .user(1)
+        .body("text/calendar", "BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n")
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+
+    // PUT по id меняет todo из API
+    app.put(&format!("{calendar}{id}.ics"))
+        .user(1)
+        .body("text/calendar", INCOME_TAXES)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let todo = app.get(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).send().await.json();
+    assert_eq!(todo["body"], "Submit Income Taxes");
+    assert!(todo["ical_uid"].is_null());
+}
\ No newline at end of file
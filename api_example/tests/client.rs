@@ -0,0 +1,203 @@
+mod common;
+
+use api_example::client::args::Cli;
+use api_example::client::{self, ClientError};
+use axum::http::StatusCode;
+use clap::Parser;
+use common::TestApp;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+/// Терминал пользователя 1: сервер на сокете, ключ и профиль в отдельном файле конфигурации.
+struct Terminal {
+    config: PathBuf,
+}
+
+impl Terminal {
+    async fn open(app: &TestApp, profiles: &str) -> Terminal {
+        let server = app.serve().await;
+        let key = app
+            .post("/v1/api-keys")
+            .user(1)
+            .json(json!({ "name": "cli", "scopes": ["todos:read", "todos:write"] }))
+            .send()
+            .await
+            .assert_status(StatusCode::OK)
+            .json();
+        let token = key["token"].as_str().unwrap();
+
+        let config = std::env::temp_dir().join(format!("todo_cli_{}.toml", rand::random::<u64>()));
+        let text = profiles.replace("{server}", &server).replace("{token}", token);
+        std::fs::write(&config, text).unwrap();
+        Terminal { config }
+    }
+
+    async fn run(&self, args: &[&str]) -> Result<String, ClientError> {
+        let config = self.config.to_str().unwrap();
+        let cli = Cli::try_parse_from(["todo", "--config", config].iter().chain(args)).unwrap();
+        let mut out = Vec::new();
+        client::run(cli, &mut out).await?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    async fn json(&self, args: &[&str]) -> Value {
+        let out = self.run(&[args, &["--output", "json"]].concat()).await.unwrap();
+        serde_json::from_str(&out).unwrap()
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.config).ok();
+    }
+}
+
+const PROFILE: &str = r#"
+default = "test"
+
+[profiles.test]
+server = "{server}"
+token = "{token}"
+time_zone = "Europe/Berlin"
+output = "plain"
+"#;
+
+#[tokio::test]
+async fn add_list_done_rm() {
+    let app = TestApp::spawn().await;
+    app.workspace(1).await;
+    let terminal = Terminal::open(&app, PROFILE).await;
+
+    let added = terminal.run(&["add", "pay", "invoice", "tomorrow", "5pm", "#finance", "!high"]).await.unwrap();
+    let fields: Vec<&str> = added.trim_end().split('\t').collect();
+    let id = fields[0];
+    assert_eq!(fields[1], "");
+    assert!(fields[2].ends_with(" 17:00"), "{added}");
+    assert_eq!(fields[3..], ["high", "finance", "pay invoice"]);
+
+    let raw = terminal.run(&["add", "--raw", "call", "mom", "tomorrow"]).await.unwrap();
+    let raw_id = raw.split('\t').next().unwrap().to_string();
+    assert!(raw.trim_end().ends_with("\tcall mom tomorrow"), "{raw}");
+
+    let table = terminal.run(&["list", "--output", "table"]).await.unwrap();
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 3, "{table}");
+    assert!(lines[0].starts_with("ID") && lines[0].ends_with("BODY"), "{table}");
+    assert!(lines[1].contains("finance") && lines[1].ends_with("pay invoice"), "{table}");
+
+    terminal.run(&["done", id]).await.unwrap();
+    let open = terminal.run(&["list"]).await.unwrap();
+    assert_eq!(open.lines().count(), 1);
+    assert!(open.starts_with(&format!("{raw_id}\t")));
+    let done = terminal.run(&["list", "--done"]).await.unwrap();
+    assert!(done.starts_with(&format!("{id}\tx\t")), "{done}");
+    assert_eq!(terminal.json(&["list", "--all", "--tag", "#finance"]).await.as_array().unwrap().len(), 1);
+
+    assert_eq!(terminal.run(&["rm", id, &raw_id]).await.unwrap(), format!("{id}\n{raw_id}\n"));
+    assert_eq!(terminal.json(&["list", "--all"]).await, json!([]));
+}
+
+#[tokio::test]
+async fn tag_and_edit() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "plan sprint").await.to_string();
+    let terminal = Terminal::open(&app, PROFILE).await;
+
+    let todo = terminal.json(&["tag", &id, "#Work", "planning", "work"]).await;
+    assert_eq!(todo["tags"], json!(["work", "planning"]));
+    let todo = terminal.json(&["tag", &id, "--remove", "work"]).await;
+    assert_eq!(todo["tags"], json!(["planning"]));
+
+    let todo = terminal
+        .json(&["edit", &id, "--body", "plan next sprint", "--due", "2026-11-02T09:00:00Z", "--priority", "medium"])
+        .await;
+    assert_eq!(todo["body"], "plan next sprint");
+    assert_eq!(todo["due_at"], "2026-11-02T09:00:00Z");
+    assert_eq!(todo["priority"], "medium");
+    let todo = terminal.json(&["edit", &id, "--no-due", "--no-priority"]).await;
+    assert_eq!((&todo["due_at"], &todo["priority"]), (&Value::Null, &Value::Null));
+
+    let Err(ClientError::Config(_)) = terminal.run(&["edit", &id]).await else {
+        panic!("edit without changes must fail before the request");
+    };
+    let Err(ClientError::Api { status, code, .. }) = terminal.run(&["edit", "0", "--body", "x"]).await else {
+        panic!("unknown todo must be an API error");
+    };
+    assert_eq!((status, code.as_deref()), (StatusCode::NOT_FOUND, Some("not_found")));
+}
+
+#[tokio::test]
+async fn profiles_and_workspaces() {
+    let app = TestApp::spawn().await;
+    let home = app.workspace(1).await;
+    let work = app.workspace(1).await;
+    app.todo(1, work, "write report").await;
+    let terminal = Terminal::open(
+        &app,
+        &format!(
+            r#"
+default = "home"
+
+[profiles.home]
+server = "{{server}}"
+token = "{{token}}"
+workspace = {home}
+
+[profiles.work]
+server = "{{server}}"
+token = "{{token}}"
+workspace = {work}
+output = "json"
+"#
+        ),
+    )
+    .await;
+
+    // вывод по умолчанию - таблица
+    assert_eq!(terminal.run(&["list"]).await.unwrap(), "ID  DONE  DUE  PRIORITY  TAGS  BODY\n");
+    let list = terminal.run(&["--profile", "work", "list"]).await.unwrap();
+    assert_eq!(serde_json::from_str::<Value>(&list).unwrap()[0]["body"], "write report");
+    // флаги важнее профиля
+    let list = terminal.run(&["-p", "home", "list", "-w", &work.to_string(), "-o", "plain"]).await.unwrap();
+    assert!(list.ends_with("\twrite report\n"), "{list}");
+
+    let Err(ClientError::Config(message)) = terminal.run(&["--profile", "play", "list"]).await else {
+        panic!("unknown profile must fail");
+    };
+    assert_eq!(message, "Unknown profile 'play', known profiles: home, work");
+    let Err(ClientError::Api { status, .. }) = terminal.run(&["--token", "tk_wrong", "list"]).await else {
+        panic!("wrong token must be rejected");
+    };
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    // без workspace подходит только единственный
+    let terminal = Terminal::open(&app, PROFILE).await;
+    let Err(ClientError::Config(message)) = terminal.run(&["list"]).await else {
+        panic!("several workspaces must be ambiguous");
+    };
+    assert!(message.contains(&format!("{home} (workspace)")), "{message}");
+}
+
+#[tokio::test]
+async fn export_and_completions() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    app.todo(1, workspace_id, "renew passport").await;
+    let terminal = Terminal::open(&app, PROFILE).await;
+
+    let ics = terminal.run(&["export"]).await.unwrap();
+    assert!(ics.starts_with("BEGIN:VCALENDAR") && ics.contains("SUMMARY:renew passport"), "{ics}");
+    let file = terminal.config.with_extension("json");
+    assert_eq!(terminal.run(&["export", "--format", "json", "--file", file.to_str().unwrap()]).await.unwrap(), "");
+    let exported: Value = serde_json::from_str(&std::fs::read_to_string(&file).unwrap()).unwrap();
+    std::fs::remove_file(&file).ok();
+    assert_eq!(exported[0]["body"], "renew passport");
+
+    // автодополнению не нужны ни конфигурация, ни сервер
+    let cli = Cli::try_parse_from(["todo", "--config", "/nonexistent.toml", "completions", "bash"]).unwrap();
+    let mut script = Vec::new();
+    client::run(cli, &mut script).await.unwrap();
+    let script = String::from_utf8(script).unwrap();
+    assert!(script.contains("_todo()") && script.contains("export"), "{script}");
+}
\ No newline at end of file
@@ -0,0 +1,261 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn comments_crud_and_count() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    app.add_member(1, workspace_id, 2, "editor").await;
+    let id = app.todo(1, workspace_id, "discuss").await;
+    let comments = format!("/v1/todos/{id}/comments");
+
+    let comment = app
+        .post(&comments)
+        .user(2)
+        .workspace(workspace_id)
+        .json(json!({ "body": "first" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(comment["author_id"], 2);
+    assert_eq!(comment["todo_id"], id);
+    let comment_id = comment["id"].as_i64().unwrap();
+
+    let todo = app.get(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).send().await.json();
+    assert_eq!(todo["comment_count"], 1);
+
+    let edited = app
+        .patch(&format!("{comments}/{comment_id}"))
+        .user(2)
+        .workspace(workspace_id)
+        .json(json!({ "body": "edited" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(edited["body"], "edited");
+
+    // править может только автор, удалять - ещё и owner
+    app.patch(&format!("{comments}/{comment_id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "hijacked" }))
+        .send()
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "forbidden");
+    app.delete(&format!("{comments}/{comment_id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.delete(&format!("{comments}/{comment_id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+
+    let todo = app.get(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).send().await.json();
+    assert_eq!(todo["comment_count"], 0);
+}
+
+#[tokio::test]
+async fn comments_are_paginated() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "busy").await;
+    let comments = format!("/v1/todos/{id}/comments");
+    for n in 0..5 {
+        app.post(&comments)
+            .user(1)
+            .workspace(workspace_id)
+            .json(json!({ "body": format!("comment {n}") }))
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+
+    let mut bodies = Vec::new();
+    let mut uri = format!("{comments}?limit=2");
+    loop {
+        let page = app.get(&uri).user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK).json();
+        bodies.extend(page["items"].as_array().unwrap().iter().map(|comment| comment["body"].clone()));
+        match page["next_after"].as_i64() {
+            Some(after) => uri = format!("{comments}?limit=2&after={after}"),
+            None => break,
+        }
+    }
+    assert_eq!(bodies, (0..5).map(|n| json!(format!("comment {n}"))).collect::<Vec<_>>());
+
+    app.get(&format!("{comments}?limit=0"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+}
+
+#[tokio::test]
+async fn invalid_comments_are_rejected() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "quiet").await;
+
+    for body in [" ".to_string(), "x".repeat(10_001)] {
+        app.post(&format!("/v1/todos/{id}/comments"))
+            .user(1)
+            .workspace(workspace_id)
+            .json(json!({ "body": body }))
+            .send()
+            .await
+            .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    }
+
+    app.post("/v1/todos/4242/comments")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "lost" }))
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+    app.get("/v1/todos/4242/comments").user(1).workspace(workspace_id).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+    app.patch(&format!("/v1/todos/{id}/comments/4242"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "lost" }))
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn page_limits_and_cursor_edges() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "busy").await;
+    let comments = format!("/v1/todos/{id}/comments");
+    let page = |query: String| app.get(&format!("{comments}?{query}")).user(1).workspace(workspace_id);
+
+    let empty = page(String::new()).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(empty, json!({ "items": [], "next_after": null }));
+
+    let mut ids = Vec::new();
+    for n in 0..3 {
+        let comment = app.post(&comments).user(1).workspace(workspace_id).json(json!({ "body": format!("c{n}") })).send().await.json();
+        ids.push(comment["id"].as_i64().unwrap());
+    }
+
+    // ровно limit записей - следующей страницы нет
+    let exact = page("limit=3".to_string()).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(exact["items"].as_array().unwrap().len(), 3);
+    assert_eq!(exact["next_after"], json!(null));
+
+    let tail = page(format!("after={}", ids[2])).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(tail["items"], json!([]));
+
+    page("limit=200".to_string()).send().await.assert_status(StatusCode::OK);
+    let response = page("limit=201".to_string()).send().await.assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    assert_eq!(response.json()["code"], "limit_out_of_range");
+}
+
+#[tokio::test]
+async fn comments_stay_with_their_todo() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "discuss").await;
+    let other = app.todo(1, workspace_id, "elsewhere").await;
+    let comment = app
+        .post(&format!("/v1/todos/{id}/comments"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "mine" }))
+        .send()
+        .await
+        .json();
+    let comment_id = comment["id"].as_i64().unwrap();
+
+    // комментарий не адресуется через чужой todo
+    app.patch(&format!("/v1/todos/{other}/comments/{comment_id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "moved" }))
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+    app.delete(&format!("/v1/todos/{other}/comments/{comment_id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+    let list = app.get(&format!("/v1/todos/{other}/comments")).user(1).workspace(workspace_id).send().await.json();
+    assert_eq!(list["items"], json!([]));
+
+    // с todo удаляются и его комментарии
+    app.delete(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).send().await.assert_status(StatusCode::NO_CONTENT);
+    let left: i64 = sqlx::query_scalar("SELECT count(*) FROM todo_comment WHERE todo_id = $1")
+        .bind(id)
+        .fetch_one(app.db.primary())
+        .await
+        .unwrap();
+    assert_eq!(left, 0);
+}
+
+#[tokio::test]
+async fn comment_count_survives_concurrent_writes() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "popular").await;
+    let comments = format!("/v1/todos/{id}/comments");
+
+    let created = futures_util::future::join_all((0..10).map(|n| {
+        app.post(&comments).user(1).workspace(workspace_id).json(json!({ "body": format!("c{n}") })).send()
+    }))
+    .await;
+    let ids: Vec<i64> = created.iter().map(|response| response.json()["id"].as_i64().unwrap()).collect();
+
+    futures_util::future::join_all(ids[..4].iter().map(|comment_id| {
+        app.delete(&format!("{comments}/{comment_id}")).user(1).workspace(workspace_id).send()
+    }))
+    .await;
+
+    let todo = app.get(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).send().await.json();
+    assert_eq!(todo["comment_count"], 6);
+    let list = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.json();
+    assert_eq!(list[0]["comment_count"], 6);
+}
+
+#[tokio::test]
+async fn edits_bump_updated_at() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "discuss").await;
+    let comments = format!("/v1/todos/{id}/comments");
+    let comment = app.post(&comments).user(1).workspace(workspace_id).json(json!({ "body": "draft" })).send().await.json();
+    assert_eq!(comment["created_at"], comment["updated_at"]);
+
+    let edited = app
+        .patch(&format!("{comments}/{}", comment["id"]))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "final" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(edited["created_at"], comment["created_at"]);
+    assert_ne!(edited["updated_at"], comment["updated_at"]);
+
+    app.patch(&format!("{comments}/{}", comment["id"]))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "  " }))
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+}
\ No newline at end of file
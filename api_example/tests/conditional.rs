@@ -0,0 +1,152 @@
+//! ETag и условный GET для списка todo, сжатие ответов.
+
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+use std::io::Read;
+
+#[tokio::test]
+async fn list_supports_conditional_get() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    app.todo(1, workspace_id, "first").await;
+
+    let list = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK);
+    let etag = list.header("etag").unwrap().to_string();
+    assert!(etag.starts_with("W/"), "{etag}");
+    assert_eq!(list.header("cache-control"), Some("private, no-cache"));
+
+    let not_modified = app
+        .get("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .header("if-none-match", &etag)
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_MODIFIED);
+    assert!(not_modified.body.is_empty());
+
+    app.todo(1, workspace_id, "second").await;
+    let changed = app
+        .get("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .header("if-none-match", &etag)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_ne!(changed.header("etag"), Some(etag.as_str()));
+    assert_eq!(changed.json().as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn comments_change_the_list_etag() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "commented").await;
+    let etag = || async {
+        let list = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK);
+        list.header("etag").unwrap().to_string()
+    };
+
+    // комментарий не меняет updated_at todo, но меняет comment_count в списке
+    let before = etag().await;
+    let comment = app
+        .post(&format!("/v1/todos/{id}/comments"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "note" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    let commented = etag().await;
+    assert_ne!(commented, before);
+
+    app.delete(&format!("/v1/todos/{id}/comments/{}", comment["id"]))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert_ne!(etag().await, commented);
+}
+
+#[tokio::test]
+async fn if_none_match_uses_weak_comparison() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    app.todo(1, workspace_id, "first").await;
+    let list = |if_none_match: &str| app.get("/v1/todos").user(1).workspace(workspace_id).header("if-none-match", if_none_match);
+
+    let etag = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.header("etag").unwrap().to_string();
+    let strong = etag.trim_start_matches("W/");
+    list(strong).send().await.assert_status(StatusCode::NOT_MODIFIED);
+    list(&format!("\"other\", {etag}")).send().await.assert_status(StatusCode::NOT_MODIFIED);
+    list("*").send().await.assert_status(StatusCode::NOT_MODIFIED);
+    list("W/\"other\"").send().await.assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn etag_depends_on_projection() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    app.todo(1, workspace_id, "first").await;
+    let get = |uri: &'static str| app.get(uri).user(1).workspace(workspace_id);
+
+    let full = get("/v1/todos").send().await.header("etag").unwrap().to_string();
+    let ids = get("/v1/todos?fields=id").send().await.header("etag").unwrap().to_string();
+    assert_ne!(full, ids);
+
+    // ответ с другим набором полей не считается закэшированным
+    get("/v1/todos?fields=id").header("if-none-match", &full).send().await.assert_status(StatusCode::OK);
+    get("/v1/todos?fields=id").header("if-none-match", &ids).send().await.assert_status(StatusCode::NOT_MODIFIED);
+}
+
+#[tokio::test]
+async fn large_lists_are_gzipped() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    for n in 0..30 {
+        app.todo(1, workspace_id, &format!("todo number {n} with a body long enough to compress")).await;
+    }
+    let plain = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK);
+    assert_eq!(plain.header("content-encoding"), None);
+
+    let gzipped = app
+        .get("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .header("accept-encoding", "gzip")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(gzipped.header("content-encoding"), Some("gzip"));
+    // Vary ставит и CORS, поэтому заголовков несколько
+    let vary: Vec<_> = gzipped.headers.get_all("vary").iter().map(|value| value.to_str().unwrap().to_lowercase()).collect();
+    assert!(vary.iter().any(|value| value.contains("accept-encoding")), "{vary:?}");
+    assert!(gzipped.body.len() < plain.body.len());
+    assert_eq!(gzipped.header("etag"), plain.header("etag"));
+
+    let mut json = String::new();
+    flate2::read::GzDecoder::new(&gzipped.body[..]).read_to_string(&mut json).unwrap();
+    assert_eq!(json.as_bytes(), &plain.body[..]);
+}
+
+#[tokio::test]
+async fn small_and_empty_responses_are_not_compressed() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    app.todo(1, workspace_id, "first").await;
+    let list = || app.get("/v1/todos").user(1).workspace(workspace_id).header("accept-encoding", "gzip");
+
+    let small = list().send().await.assert_status(StatusCode::OK);
+    assert_eq!(small.header("content-encoding"), None);
+
+    let etag = small.header("etag").unwrap().to_string();
+    let not_modified = list().header("if-none-match", &etag).send().await.assert_status(StatusCode::NOT_MODIFIED);
+    assert_eq!(not_modified.header("content-encoding"), None);
+    assert!(not_modified.body.is_empty());
+}
\ No newline at end of file
@@ -0,0 +1,134 @@
+mod common;
+
+use api_example::repo::event::{self, Rebuilt, Store};
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+const STORE: Store = Store::Events { snapshot_every: 100 };
+
+async fn kinds(app: &TestApp, todo_id: i64) -> Vec<String> {
+    sqlx::query_scalar("SELECT kind FROM todo_event WHERE todo_id = $1 ORDER BY seq")
+        .bind(todo_id)
+        .fetch_all(app.db.primary())
+        .await
+        .unwrap()
+}
+
+async fn body(app: &TestApp, todo_id: i64) -> Option<String> {
+    sqlx::query_scalar("SELECT body FROM todo WHERE id = $1")
+        .bind(todo_id)
+        .fetch_optional(app.db.primary())
+        .await
+        .unwrap()
+}
+
+async fn patch(app: &TestApp, workspace_id: i64, id: i64, patch: serde_json::Value) {
+    app.patch(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(patch)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn writes_append_events() {
+    let app = TestApp::spawn_with_store(STORE).await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "call mom").await;
+
+    patch(&app, workspace_id, id, json!({ "body": "call dad" })).await;
+    patch(&app, workspace_id, id, json!({ "done": true })).await;
+    patch(&app, workspace_id, id, json!({ "done": false, "due_at": "2026-05-01T09:00:00Z" })).await;
+    // ничего не меняется - событий нет
+    patch(&app, workspace_id, id, json!({ "body": "call dad" })).await;
+    app.delete(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).send().await.assert_status(StatusCode::NO_CONTENT);
+
+    assert_eq!(kinds(&app, id).await, ["created", "body_changed", "completed", "reopened", "edited", "deleted"]);
+
+    let rows = TestApp::spawn().await;
+    let workspace_id = rows.workspace(1).await;
+    let id = rows.todo(1, workspace_id, "call mom").await;
+    assert!(kinds(&rows, id).await.is_empty());
+}
+
+#[tokio::test]
+async fn rebuild_restores_projection() {
+    let app = TestApp::spawn_with_store(STORE).await;
+    let workspace_id = app.workspace(1).await;
+    let changed = app.todo(1, workspace_id, "changed").await;
+    let lost = app.todo(1, workspace_id, "lost").await;
+    let deleted = app.todo(1, workspace_id, "deleted").await;
+    patch(&app, workspace_id, changed, json!({ "body": "changed twice", "tags": ["home"] })).await;
+    patch(&app, workspace_id, lost, json!({ "done": true })).await;
+    app.delete(&format!("/v1/todos/{deleted}")).user(1).workspace(workspace_id).send().await.assert_status(StatusCode::NO_CONTENT);
+
+    let before: Vec<serde_json::Value> = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.json().as_array().unwrap().clone();
+
+    // проекция разошлась с историей
+    sqlx::query("UPDATE todo SET body = 'corrupted', tags = '{}' WHERE id = $1").bind(changed).execute(app.db.primary()).await.unwrap();
+    sqlx::query("DELETE FROM todo WHERE id = $1").bind(lost).execute(app.db.primary()).await.unwrap();
+
+    let rebuilt = event::rebuild(app.db.primary(), Some(workspace_id), 100).await.unwrap();
+    assert_eq!(rebuilt, Rebuilt { todos: 3, events: 7, inserted: 1, updated: 1, deleted: 0, snapshots: 0 });
+    assert_eq!(body(&app, changed).await.as_deref(), Some("changed twice"));
+    assert_eq!(body(&app, deleted).await, None);
+
+    let restored = app.get(&format!("/v1/todos/{lost}")).user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK).json();
+    for field in ["body", "done", "completed_at", "position", "created_at"] {
+        assert_eq!(restored[field], before[1][field], "{field}");
+    }
+
+    // проекция совпадает с историей - пересборка ничего не меняет
+    let again = event::rebuild(app.db.primary(), None, 100).await.unwrap();
+    assert_eq!((again.inserted, again.updated, again.deleted), (0, 0, 0));
+}
+
+#[tokio::test]
+async fn snapshots_bound_replay() {
+    let app = TestApp::spawn_with_store(Store::Events { snapshot_every: 3 }).await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "draft 0").await;
+    for draft in 1..=6 {
+        patch(&app, workspace_id, id, json!({ "body": format!("draft {draft}") })).await;
+    }
+
+    let seq: i64 = sqlx::query_scalar("SELECT seq FROM todo_snapshot WHERE todo_id = $1")
+        .bind(id)
+        .fetch_one(app.db.primary())
+        .await
+        .unwrap();
+    assert_eq!(seq, 6);
+
+    // события до снимка больше не нужны для пересборки
+    sqlx::query("DELETE FROM todo_event WHERE todo_id = $1 AND seq <= 6").bind(id).execute(app.db.primary()).await.unwrap();
+    sqlx::query("UPDATE todo SET body = 'corrupted' WHERE id = $1").bind(id).execute(app.db.primary()).await.unwrap();
+
+    let rebuilt = event::rebuild(app.db.primary(), Some(workspace_id), 3).await.unwrap();
+    assert_eq!((rebuilt.events, rebuilt.updated), (1, 1));
+    assert_eq!(body(&app, id).await.as_deref(), Some("draft 6"));
+}
+
+#[tokio::test]
+async fn history_of_existing_todos() {
+    let app = TestApp::spawn_with_store(STORE).await;
+    let workspace_id = app.workspace(1).await;
+    // todo из времени до включения режима
+    let insert = "INSERT INTO todo (workspace_id, body, position) VALUES ($1, $2, $3) RETURNING id";
+    let edited: i64 = sqlx::query_scalar(insert).bind(workspace_id).bind("old").bind("a").fetch_one(app.db.primary()).await.unwrap();
+    let untouched: i64 = sqlx::query_scalar(insert).bind(workspace_id).bind("older").bind("b").fetch_one(app.db.primary()).await.unwrap();
+
+    // первая запись начинает историю с текущего состояния
+    patch(&app, workspace_id, edited, json!({ "body": "new" })).await;
+    assert_eq!(kinds(&app, edited).await, ["created", "body_changed"]);
+    assert!(kinds(&app, untouched).await.is_empty());
+
+    assert_eq!(event::seed(app.db.primary()).await.unwrap(), 1);
+    assert_eq!(kinds(&app, untouched).await, ["created"]);
+    assert_eq!(event::seed(app.db.primary()).await.unwrap(), 0);
+
+    let rebuilt = event::rebuild(app.db.primary(), None, 100).await.unwrap();
+    assert_eq!((rebuilt.todos, rebuilt.inserted, rebuilt.updated, rebuilt.deleted), (2, 0, 0, 0));
+}
\ No newline at end of file
@@ -0,0 +1,121 @@
+mod common;
+
+use api_example::i18n::{Key, Locale, Message};
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+#[test]
+fn every_locale_translates_every_key() {
+    for locale in Locale::ALL {
+        let missing: Vec<Key> = Key::ALL.into_iter().filter(|key| locale.template(*key).is_none()).collect();
+        assert!(missing.is_empty(), "'{}' has no messages for {missing:?}", locale.tag());
+    }
+}
+
+#[test]
+fn translations_use_known_placeholders() {
+    for key in Key::ALL {
+        let english = Message::placeholders(Locale::En.template(key).unwrap());
+        for locale in Locale::ALL {
+            for placeholder in locale.template(key).map(Message::placeholders).unwrap_or_default() {
+                assert!(english.contains(&placeholder), "'{}' {key:?} uses unknown {{{placeholder}}}", locale.tag());
+            }
+        }
+    }
+}
+
+#[test]
+fn accept_language_negotiation() {
+    let cases = [
+        ("ru", Locale::Ru),
+        ("ru-RU,ru;q=0.9,en-US;q=0.8,en;q=0.7", Locale::Ru),
+        ("RU-ru", Locale::Ru),
+        ("en-US,en;q=0.9,ru;q=0.8", Locale::En),
+        ("en;q=0.5, ru;q=0.8", Locale::Ru),
+        ("de-DE, ru;q=0.1", Locale::Ru),
+        ("de-DE, fr", Locale::En),
+        // при равном q выигрывает первый
+        ("ru;q=0.5, en;q=0.5", Locale::Ru),
+        ("ru;q=0, en", Locale::En),
+        ("*;q=0.9, ru;q=0.3", Locale::En),
+        ("ru;q=abc", Locale::En),
+        ("", Locale::En),
+    ];
+    for (header, expected) in cases {
+        assert_eq!(Locale::negotiate(header), expected, "{header}");
+    }
+}
+
+#[test]
+fn arguments_are_substituted_once() {
+    let message = Message::new(Key::TagInvalid).arg("tag", "{max}").arg("max", 50);
+    assert_eq!(message.render(Locale::En), "Tag '{max}' must be 1 to 50 characters without spaces");
+    assert_eq!(message.render(Locale::Ru), "Тег '{max}' должен содержать от 1 до 50 символов без пробелов");
+}
+
+#[tokio::test]
+async fn errors_follow_accept_language() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "call mom").await;
+
+    let error = app
+        .patch(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .header("accept-language", "ru-RU,ru;q=0.9,en;q=0.8")
+        .json(json!({ "tags": ["two words"] }))
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    assert_eq!(error.header("content-language"), Some("ru"));
+    let error = error.json();
+    assert_eq!(error["code"], "tag_invalid");
+    assert_eq!(error["message"], "Тег 'two words' должен содержать от 1 до 50 символов без пробелов");
+
+    let error = app
+        .patch(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .header("accept-language", "de, en;q=0.5")
+        .json(json!({}))
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error")
+        .json();
+    assert_eq!(error["code"], "update_empty");
+    assert!(error["message"].as_str().unwrap().starts_with("At least one of 'body'"));
+
+    // ответы guard и аутентификации тоже переводятся
+    let error = app.get("/v1/todos/1").header("accept-language", "ru").send().await;
+    let error = error.assert_error(StatusCode::UNAUTHORIZED, "unauthorized").json();
+    assert_eq!(error["message"], "Требуется аутентификация");
+}
+
+#[tokio::test]
+async fn infrastructure_errors_hide_driver_text() {
+    let app = TestApp::without_database();
+    let error = app.get("/ready").header("accept-language", "ru").send().await;
+    let error = error.assert_error(StatusCode::INTERNAL_SERVER_ERROR, "db_error").json();
+    assert_eq!(error["code"], "database_error");
+    assert_eq!(error["message"], "Ошибка базы данных");
+
+    // каталог вложений не создать: на его месте файл
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "with attachment").await;
+    std::fs::write(&app.blob_root, b"not a directory").unwrap();
+    let error = app
+        .post(&format!("/v1/todos/{id}/attachments"))
+        .user(1)
+        .workspace(workspace_id)
+        .multipart("file", "notes.txt", b"notes")
+        .send()
+        .await
+        .assert_error(StatusCode::BAD_GATEWAY, "storage_error")
+        .json();
+    assert_eq!(error["code"], "storage_error");
+    assert_eq!(error["message"], "File storage error");
+    std::fs::remove_file(&app.blob_root).unwrap();
+}
\ No newline at end of file
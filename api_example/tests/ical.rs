@@ -0,0 +1,132 @@
+use api_example::ical::{calendar, parse, Status, VTodo};
+use chrono::{DateTime, Utc};
+
+// примеры из RFC 5545, 3.6.2
+const TAX_RETURN: &str = "BEGIN:VTODO\r
+UID:20070313T123432Z-456553@example.com\r
+DTSTAMP:20070313T123432Z\r
+DUE;VALUE=DATE:20070501\r
+SUMMARY:Submit Quebec Income Tax Return for 2006\r
+CLASS:CONFIDENTIAL\r
+CATEGORIES:FAMILY,FINANCE\r
+STATUS:NEEDS-ACTION\r
+END:VTODO\r
+";
+
+const INTERNET_DRAFT: &str = "BEGIN:VTODO\r
+UID:20070514T103211Z-123404@example.com\r
+DTSTAMP:20070514T103211Z\r
+DTSTART:20070514T110000Z\r
+DUE:20070709T130000Z\r
+COMPLETED:20070707T100000Z\r
+SUMMARY:Submit Revised Internet-Draft\r
+PRIORITY:1\r
+STATUS:NEEDS-ACTION\r
+END:VTODO\r
+";
+
+// RFC 5545, 4: VTODO с вложенным VALARM и перенесённой строкой
+const INCOME_TAXES: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+PRODID:-//ABC Corporation//NONSGML My Product//EN\r
+BEGIN:VTODO\r
+DTSTAMP:19980130T134500Z\r
+SEQUENCE:2\r
+UID:uid4@example.com\r
+ORGANIZER:mailto:unclesam@example.com\r
+ATTENDEE;PARTSTAT=ACCEPTED:mailto:jqpublic@example.com\r
+DUE:19980415T000000\r
+STATUS:NEEDS-ACTION\r
+SUMMARY:Submit Income Taxes\r
+BEGIN:VALARM\r
+ACTION:AUDIO\r
+TRIGGER:19980403T120000Z\r
+ATTACH;FMTTYPE=audio/basic:http://example.com/pub/audio-\r
+ files/ssbanner.aud\r
+REPEAT:4\r
+DURATION:PT1H\r
+END:VALARM\r
+END:VTODO\r
+END:VCALENDAR\r
+";
+
+fn at(value: &str) -> Option<DateTime<Utc>> {
+    Some(value.parse().unwrap())
+}
+
+fn single(input: &str) -> VTodo {
+    let mut todos = parse(input).unwrap();
+    assert_eq!(todos.len(), 1);
+    todos.remove(0)
+}
+
+#[test]
+fn rfc_examples_are_parsed() {
+    let tax_return = single(TAX_RETURN);
+    assert_eq!(tax_return.uid, "20070313T123432Z-456553@example.com");
+    assert_eq!(tax_return.summary.as_deref(), Some("Submit Quebec Income Tax Return for 2006"));
+    assert_eq!(tax_return.status, Some(Status::NeedsAction));
+    assert_eq!(tax_return.due, at("2007-05-01T00:00:00Z"));
+    assert_eq!(tax_return.dtstamp, at("2007-03-13T12:34:32Z"));
+    assert!(!tax_return.done());
+
+    let draft = single(INTERNET_DRAFT);
+    assert_eq!(draft.due, at("2007-07-09T13:00:00Z"));
+    assert_eq!(draft.completed, at("2007-07-07T10:00:00Z"));
+
+    // свойства VALARM не попадают в сам VTODO
+    let taxes = single(INCOME_TAXES);
+    assert_eq!(taxes.uid, "uid4@example.com");
+    assert_eq!(taxes.summary.as_deref(), Some("Submit Income Taxes"));
+    assert_eq!(taxes.due, at("1998-04-15T00:00:00Z"));
+}
+
+#[test]
+fn rfc_examples_round_trip() {
+    for example in [TAX_RETURN, INTERNET_DRAFT, INCOME_TAXES] {
+        let todos = parse(example).unwrap();
+        let serialized = calendar(Some("Examples"), &todos);
+        assert!(serialized.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(serialized.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(parse(&serialized).unwrap(), todos);
+    }
+}
+
+#[test]
+fn text_is_escaped_and_folded() {
+    let summary = format!("a;b,c\\d\nновая строка {}", "ё".repeat(100));
+    let todo = VTodo {
+        summary: Some(summary.clone()),
+        status: Some(Status::Completed),
+        completed: at("2026-03-10T12:00:00Z"),
+        ..single(TAX_RETURN)
+    };
+
+    let serialized = calendar(None, std::slice::from_ref(&todo));
+    assert!(serialized.contains(r"SUMMARY:a\;b\,c\\d\nновая"));
+    for line in serialized.split("\r\n") {
+        assert!(line.len() <= 75, "{line} is longer than 75 octets");
+    }
+    assert_eq!(parse(&serialized).unwrap(), vec![todo]);
+}
+
+#[test]
+fn local_times_use_tzid() {
+    let todo = single("BEGIN:VTODO\nUID:tz\nDUE;TZID=America/New_York:19980119T020000\nEND:VTODO\n");
+    assert_eq!(todo.due, at("1998-01-19T07:00:00Z"));
+}
+
+#[test]
+fn invalid_calendars_are_rejected() {
+    for input in [
+        "BEGIN:VTODO\nSUMMARY:no uid\nEND:VTODO\n",
+        "BEGIN:VCALENDAR\nBEGIN:VTODO\nUID:open\nEND:VTODO\n",
+        "BEGIN:VTODO\nUID:x\nEND:VEVENT\n",
+        "BEGIN:VTODO\nUID:x\nSTATUS:SOMEDAY\nEND:VTODO\n",
+        "BEGIN:VTODO\nUID:x\nDUE;TZID=Mars/Olympus:20260101T000000\nEND:VTODO\n",
+        "BEGIN:VTODO\nUID:x\nDUE:tomorrow\nEND:VTODO\n",
+        "BEGIN:VTODO\nUID\nEND:VTODO\n",
+    ] {
+        assert!(parse(input).is_err(), "{input:?} was accepted");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,125 @@
+mod common;
+
+use api_example::api::limits::Limits;
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+use std::time::Duration;
+
+#[tokio::test]
+async fn slow_request_times_out() {
+    // запрос к базе не успевает за нулевой таймаут
+    let app = TestApp::spawn_with(Limits { request_timeout: Duration::ZERO, ..Limits::default() }).await;
+
+    app.get("/v1/workspaces").user(1).send().await.assert_error(StatusCode::GATEWAY_TIMEOUT, "timeout");
+    app.get("/health").send().await.assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn requests_over_concurrency_limit_are_shed() {
+    let app = TestApp::spawn_with(Limits { max_concurrent_requests: 0, ..Limits::default() }).await;
+
+    let response = app
+        .get("/v1/workspaces")
+        .user(1)
+        .send()
+        .await
+        .assert_error(StatusCode::SERVICE_UNAVAILABLE, "overloaded");
+    assert_eq!(response.header("retry-after"), Some("1"));
+    app.get("/health").send().await.assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn requests_are_shed_while_pool_is_exhausted() {
+    let app = TestApp::spawn_with(Limits { db_queue_max: 0, ..Limits::default() }).await;
+    app.get("/v1/workspaces").user(1).send().await.assert_status(StatusCode::OK);
+
+    let mut held = Vec::new();
+    for _ in 0..app.db.primary().options().get_max_connections() {
+        held.push(app.db.primary().acquire().await.unwrap());
+    }
+    app.get("/v1/workspaces")
+        .user(1)
+        .send()
+        .await
+        .assert_error(StatusCode::SERVICE_UNAVAILABLE, "overloaded");
+
+    // соединения возвращаются в пул фоновой задачей
+    drop(held);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    app.get("/v1/workspaces").user(1).send().await.assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn body_over_limit_is_rejected() {
+    let app = TestApp::spawn_with(Limits { body_max_bytes: 64, ..Limits::default() }).await;
+    let workspace_id = app.workspace(1).await;
+
+    app.post("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "x".repeat(64) }))
+        .send()
+        .await
+        .assert_error(StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large");
+
+    // у вложений свой предел
+    let id = app.todo(1, workspace_id, "small").await;
+    app.post(&format!("/v1/todos/{id}/attachments"))
+        .user(1)
+        .workspace(workspace_id)
+        .multipart("file", "notes.txt", &[b'a'; 512])
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn chunked_body_over_limit_is_rejected() {
+    let app = TestApp::spawn_with(Limits { body_max_bytes: 64, ..Limits::default() }).await;
+    let workspace_id = app.workspace(1).await;
+
+    let response = app
+        .post("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "x".repeat(64) }))
+        .chunked()
+        .send()
+        .await
+        .assert_error(StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large");
+    assert_eq!(response.json()["code"], "body_too_large");
+
+    app.post("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "small" }))
+        .chunked()
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    // у вложений свой предел и при чтении потока
+    let id = app.todo(1, workspace_id, "small").await;
+    app.post(&format!("/v1/todos/{id}/attachments"))
+        .user(1)
+        .workspace(workspace_id)
+        .multipart("file", "notes.txt", &[b'a'; 512])
+        .chunked()
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+}
+
+#[tokio::test]
+async fn transfers_have_their_own_timeout() {
+    let app = TestApp::spawn_with(Limits { request_timeout: Duration::ZERO, ..Limits::default() }).await;
+
+    // у календаря и CalDAV таймаут передачи, у остального API - обычный
+    app.get("/v1/calendar.ics").user(1).send().await.assert_status(StatusCode::OK);
+    app.get("/v1/workspaces").user(1).send().await.assert_error(StatusCode::GATEWAY_TIMEOUT, "timeout");
+
+    let app = TestApp::spawn_with(Limits { transfer_timeout: Duration::ZERO, ..Limits::default() }).await;
+    app.get("/v1/calendar.ics").user(1).send().await.assert_error(StatusCode::GATEWAY_TIMEOUT, "timeout");
+    app.get("/v1/workspaces").user(1).send().await.assert_status(StatusCode::OK);
+}
\ No newline at end of file
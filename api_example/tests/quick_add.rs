@@ -0,0 +1,200 @@
+use api_example::dto::todo::Priority;
+use api_example::quick_add::{parse, Kind, Parsed};
+use chrono::{DateTime, TimeZone};
+use chrono_tz::Tz;
+use chrono_tz::Europe::Berlin;
+
+/// Среда, 21 октября 2026, 14:30 по Берлину.
+fn now() -> DateTime<Tz> {
+    Berlin.with_ymd_and_hms(2026, 10, 21, 14, 30, 0).unwrap()
+}
+
+fn due(text: &str) -> Option<String> {
+    parse(text, now()).due_at.map(|at| at.with_timezone(&Berlin).format("%Y-%m-%d %H:%M").to_string())
+}
+
+#[test]
+fn relative_days() {
+    let cases = [
+        ("call mom today", "2026-10-21 09:00"),
+        ("call mom tomorrow", "2026-10-22 09:00"),
+        ("call mom tmrw", "2026-10-22 09:00"),
+        ("call mom day after tomorrow", "2026-10-23 09:00"),
+        ("call mom the day after tomorrow", "2026-10-23 09:00"),
+        ("call mom in 3 days", "2026-10-24 09:00"),
+        ("call mom in a week", "2026-10-28 09:00"),
+        ("call mom in two weeks", "2026-11-04 09:00"),
+        ("call mom in 2 months", "2026-12-21 09:00"),
+        ("call mom in a year", "2027-10-21 09:00"),
+        ("call mom next week", "2026-10-26 09:00"),
+        ("call mom next month", "2026-11-01 09:00"),
+        ("call mom next year", "2027-01-01 09:00"),
+        ("call mom end of month", "2026-10-31 09:00"),
+        ("call mom end of the week", "2026-10-23 09:00"),
+        ("call mom eom", "2026-10-31 09:00"),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(due(text).as_deref(), Some(expected), "{text}");
+        assert_eq!(parse(text, now()).body, "call mom", "{text}");
+    }
+}
+
+#[test]
+fn weekdays() {
+    let cases = [
+        ("friday", "2026-10-23 09:00"),
+        ("Friday", "2026-10-23 09:00"),
+        ("on fri", "2026-10-23 09:00"),
+        ("by thurs", "2026-10-22 09:00"),
+        // сегодняшний день недели - это сегодня
+        ("wednesday", "2026-10-21 09:00"),
+        ("monday", "2026-10-26 09:00"),
+        ("this sunday", "2026-10-25 09:00"),
+        // next - день следующей недели
+        ("next friday", "2026-10-30 09:00"),
+        ("next mon", "2026-10-26 09:00"),
+        ("weekend", "2026-10-24 09:00"),
+        ("this weekend", "2026-10-24 09:00"),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(due(&format!("water plants {text}")).as_deref(), Some(expected), "{text}");
+    }
+}
+
+#[test]
+fn calendar_dates() {
+    let cases = [
+        ("dec 24", "2026-12-24 09:00"),
+        ("december 24th", "2026-12-24 09:00"),
+        ("5 nov", "2026-11-05 09:00"),
+        ("5th of november 2027", "2027-11-05 09:00"),
+        ("on jan 5, 2028", "2028-01-05 09:00"),
+        // уже прошло в этом году
+        ("jan 5", "2027-01-05 09:00"),
+        ("oct 21", "2026-10-21 09:00"),
+        ("2026-12-01", "2026-12-01 09:00"),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(due(&format!("renew passport {text}")).as_deref(), Some(expected), "{text}");
+    }
+}
+
+#[test]
+fn times_of_day() {
+    let cases = [
+        ("tomorrow 5pm", "2026-10-22 17:00"),
+        ("5pm tomorrow", "2026-10-22 17:00"),
+        ("tomorrow at 5:30 pm", "2026-10-22 17:30"),
+        ("tomorrow 5.30pm", "2026-10-22 17:30"),
+        ("tomorrow at 17:45", "2026-10-22 17:45"),
+        ("tomorrow at 8", "2026-10-22 08:00"),
+        ("tomorrow 12am", "2026-10-22 00:00"),
+        ("tomorrow 12pm", "2026-10-22 12:00"),
+        ("tomorrow 7 a.m.", "2026-10-22 07:00"),
+        ("tomorrow morning", "2026-10-22 09:00"),
+        ("friday evening", "2026-10-23 18:00"),
+        ("friday at noon", "2026-10-23 12:00"),
+        ("tomorrow in the afternoon", "2026-10-22 15:00"),
+        ("tonight", "2026-10-21 21:00"),
+        ("tonight at 10pm", "2026-10-21 22:00"),
+        ("this evening", "2026-10-21 18:00"),
+        // только время: ближайшее в будущем
+        ("5pm", "2026-10-21 17:00"),
+        ("at 17", "2026-10-21 17:00"),
+        ("9am", "2026-10-22 09:00"),
+        ("at noon", "2026-10-22 12:00"),
+        ("in 2 hours", "2026-10-21 16:30"),
+        ("in an hour", "2026-10-21 15:30"),
+        ("in 45 mins", "2026-10-21 15:15"),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(due(&format!("stand-up {text}")).as_deref(), Some(expected), "{text}");
+        assert_eq!(parse(&format!("stand-up {text}"), now()).body, "stand-up", "{text}");
+    }
+}
+
+#[test]
+fn time_zone_of_now_is_used() {
+    let utc = parse("report tomorrow 5pm", now().with_timezone(&Tz::UTC)).due_at.unwrap();
+    let berlin = parse("report tomorrow 5pm", now()).due_at.unwrap();
+    assert_eq!(utc.to_rfc3339(), "2026-10-22T17:00:00+00:00");
+    assert_eq!(berlin.to_rfc3339(), "2026-10-22T15:00:00+00:00");
+
+    // 25 октября Берлин переходит на зимнее время
+    let after_switch = parse("report monday 9am", now()).due_at.unwrap();
+    assert_eq!(after_switch.to_rfc3339(), "2026-10-26T08:00:00+00:00");
+}
+
+#[test]
+fn full_example() {
+    let parsed = parse("pay invoice tomorrow 5pm #finance !high", now());
+    assert_eq!(parsed.body, "pay invoice");
+    assert_eq!(parsed.tags, vec!["finance"]);
+    assert_eq!(parsed.priority, Some(Priority::High));
+    assert_eq!(due("pay invoice tomorrow 5pm #finance !high").as_deref(), Some("2026-10-22 17:00"));
+
+    let recognised: Vec<(Kind, &str)> = parsed.recognised.iter().map(|token| (token.kind, token.text.as_str())).collect();
+    assert_eq!(
+        recognised,
+        vec![(Kind::Due, "tomorrow"), (Kind::Due, "5pm"), (Kind::Tag, "#finance"), (Kind::Priority, "!high")],
+    );
+}
+
+#[test]
+fn tags_and_priorities() {
+    let parsed = parse("#Work plan sprint #work #home-office, #1 #", now());
+    assert_eq!(parsed.tags, vec!["work", "home-office"]);
+    assert_eq!(parsed.body, "plan sprint #1 #");
+
+    let priorities = [("!high", Priority::High), ("!1", Priority::High), ("!!!", Priority::High), ("!medium", Priority::Medium),
+        ("!med", Priority::Medium), ("!!", Priority::Medium), ("!low", Priority::Low), ("!3", Priority::Low)];
+    for (marker, priority) in priorities {
+        assert_eq!(parse(&format!("fix bug {marker}"), now()).priority, Some(priority), "{marker}");
+    }
+
+    // учитывается первый приоритет, второй остаётся в тексте
+    let parsed = parse("fix bug !low !high", now());
+    assert_eq!(parsed.priority, Some(Priority::Low));
+    assert_eq!(parsed.body, "fix bug !high");
+}
+
+#[test]
+fn only_first_date_is_used() {
+    let parsed = parse("move meeting from tomorrow to friday", now());
+    assert_eq!(parsed.body, "move meeting from to friday");
+    assert_eq!(due("move meeting from tomorrow to friday").as_deref(), Some("2026-10-22 09:00"));
+}
+
+#[test]
+fn ordinary_words_are_left_alone() {
+    let texts = [
+        "buy sun cream",
+        "read 1984",
+        "meet at home",
+        "sat exam results",
+        "watch 13pm",
+        "feb 30 is not a date",
+        "end of story",
+        "in the box",
+        "this is fine",
+        "next steps",
+        "due diligence",
+        "",
+    ];
+    for text in texts {
+        let parsed = parse(text, now());
+        assert_eq!(
+            parsed,
+            Parsed { body: text.to_string(), due_at: None, tags: vec![], priority: None, recognised: vec![] },
+            "{text}",
+        );
+    }
+}
+
+#[test]
+fn punctuation_and_spacing() {
+    let parsed = parse("  Call Bob,   Tomorrow.  ", now());
+    assert_eq!(parsed.body, "Call Bob,");
+    assert_eq!(due("Call Bob, Tomorrow.").as_deref(), Some("2026-10-22 09:00"));
+    assert_eq!(parsed.recognised[0].text, "Tomorrow.");
+}
\ No newline at end of file
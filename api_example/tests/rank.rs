@@ -0,0 +1,67 @@
+use api_example::rank::{after, between, spread, MAX_LEN};
+
+#[test]
+fn between_is_strictly_ordered() {
+    let cases = [
+        (None, None),
+        (Some("i"), None),
+        (None, Some("i")),
+        (Some("a"), Some("b")),
+        (Some("a"), Some("a1")),
+        (Some("az"), Some("b")),
+        (Some("0000000001i"), Some("0000000002i")),
+        (Some("zz"), None),
+        (None, Some("01")),
+    ];
+    for (lower, upper) in cases {
+        let rank = between(lower, upper);
+        assert!(!rank.ends_with('0'), "{rank} ends with 0");
+        assert!(lower.is_none_or(|lower| lower < rank.as_str()), "{lower:?} < {rank}");
+        assert!(upper.is_none_or(|upper| rank.as_str() < upper), "{rank} < {upper:?}");
+    }
+}
+
+#[test]
+fn repeated_inserts_grow_slowly() {
+    // худший случай: всё время вставляем сразу после одного и того же элемента
+    let first = between(None, None);
+    let mut upper = between(Some(&first), None);
+    for _ in 0..100 {
+        let rank = between(Some(&first), Some(&upper));
+        assert!(first < rank && rank < upper);
+        upper = rank;
+    }
+    assert!(upper.len() <= MAX_LEN, "{upper}");
+}
+
+#[test]
+fn appends_do_not_lengthen_ranks() {
+    let cases = [(None, "i"), (Some("i"), "j"), (Some("a9"), "aa"), (Some("azz"), "b"), (Some("zz"), "zzi")];
+    for (last, expected) in cases {
+        assert_eq!(after(last), expected, "after {last:?}");
+    }
+
+    // ранг удлиняется, только когда младшие разряды кончились (`z...`)
+    let mut last = None;
+    for _ in 0..160 {
+        let rank = after(last.as_deref());
+        assert!(last.as_deref().is_none_or(|last| last < rank.as_str()) && !rank.ends_with('0'), "{rank}");
+        last = Some(rank);
+    }
+    assert!(last.unwrap().len() <= MAX_LEN);
+}
+
+#[test]
+fn spread_is_even_and_sorted() {
+    for count in [0, 1, 2, 35, 36, 1000, 50_000] {
+        let ranks = spread(count);
+        assert_eq!(ranks.len(), count);
+        assert!(ranks.windows(2).all(|pair| pair[0] < pair[1]), "{count} ranks are not sorted");
+        assert!(ranks.iter().all(|rank| !rank.is_empty() && !rank.ends_with('0')));
+        // после пересчёта вставка по краям удлиняет ранг не больше чем на цифру
+        if let (Some(first), Some(last)) = (ranks.first(), ranks.last()) {
+            assert!(between(None, Some(first)).len() <= first.len() + 1);
+            assert!(between(Some(last), None).len() <= last.len() + 1);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,294 @@
+//! Повторяющиеся todo: правила, часовые пояса, предпросмотр вхождений и следующее вхождение.
+
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::{json, Value};
+
+async fn create(app: &TestApp, workspace_id: i64, body: Value) -> Value {
+    app.post("/v1/todos").user(1).workspace(workspace_id).json(body).send().await.assert_status(StatusCode::OK).json()
+}
+
+async fn complete(app: &TestApp, workspace_id: i64, id: &Value) -> Value {
+    app.patch(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "done": true }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json()
+}
+
+async fn occurrences(app: &TestApp, workspace_id: i64, id: &Value, limit: usize) -> Value {
+    let preview = app
+        .get(&format!("/v1/todos/{id}/occurrences?limit={limit}"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    preview["occurrences"].clone()
+}
+
+#[tokio::test]
+async fn recurring_todo_spawns_next_occurrence() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+
+    let created = app
+        .post("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({
+            "body": "water plants",
+            "due_at": "2030-03-25T08:00:00Z",
+            "recurrence": "weekly:mo,th",
+            "time_zone": "Europe/Berlin",
+        }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    let id = created["id"].as_i64().unwrap();
+    assert_eq!(created["recurrence"], "FREQ=WEEKLY;BYDAY=MO,TH");
+
+    // 31 марта 2030 Берлин переходит на летнее время, 09:00 по местному остаётся 09:00
+    let preview = app
+        .get(&format!("/v1/todos/{id}/occurrences?limit=3"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(
+        preview["occurrences"],
+        json!(["2030-03-25T09:00:00+01:00", "2030-03-28T09:00:00+01:00", "2030-04-01T09:00:00+02:00"])
+    );
+
+    let done = app
+        .patch(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "done": true }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    let next_id = done["next_todo_id"].as_i64().expect("next occurrence was not created");
+
+    let next = app.get(&format!("/v1/todos/{next_id}")).user(1).workspace(workspace_id).send().await.json();
+    assert_eq!(next["body"], "water plants");
+    assert_eq!(next["done"], false);
+    assert_eq!(next["due_at"], "2030-03-28T08:00:00Z");
+
+    // повторное выполнение не создаёт ещё одно вхождение
+    for done in [false, true] {
+        app.patch(&format!("/v1/todos/{id}"))
+            .user(1)
+            .workspace(workspace_id)
+            .json(json!({ "done": done }))
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+    let list = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.json();
+    assert_eq!(list.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn occurrences_require_recurrence() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "once").await;
+
+    app.get(&format!("/v1/todos/{id}/occurrences"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    app.get("/v1/todos/4242/occurrences")
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+
+    app.post("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "yearly", "recurrence": "FREQ=YEARLY" }))
+        .send()
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn shorthands_are_stored_as_rrule() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+
+    for (shorthand, rrule) in [
+        ("daily", "FREQ=DAILY"),
+        ("weekly", "FREQ=WEEKLY"),
+        ("weekly:mo,th", "FREQ=WEEKLY;BYDAY=MO,TH"),
+        ("monthly:15", "FREQ=MONTHLY;BYMONTHDAY=15"),
+        ("FREQ=DAILY;INTERVAL=2;COUNT=3", "FREQ=DAILY;INTERVAL=2;COUNT=3"),
+    ] {
+        let todo = create(&app, workspace_id, json!({ "body": shorthand, "due_at": "2030-01-01T08:00:00Z", "recurrence": shorthand })).await;
+        assert_eq!(todo["recurrence"], rrule, "{shorthand}");
+    }
+}
+
+#[tokio::test]
+async fn unsupported_rules_are_rejected() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+
+    for recurrence in [
+        "FREQ=HOURLY",
+        "FREQ=DAILY;BYDAY=MO",
+        "FREQ=WEEKLY;BYMONTHDAY=1",
+        "FREQ=DAILY;COUNT=2;UNTIL=20301231",
+        "FREQ=WEEKLY;BYDAY=XX",
+        "FREQ=DAILY;UNTIL=tomorrow",
+        "FREQ=DAILY;INTERVAL=0",
+        "fortnightly",
+    ] {
+        let response = app
+            .post("/v1/todos")
+            .user(1)
+            .workspace(workspace_id)
+            .json(json!({ "body": "bad", "due_at": "2030-01-01T08:00:00Z", "recurrence": recurrence }))
+            .send()
+            .await;
+        assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY, "{recurrence}: {}", response.text());
+    }
+
+    let response = app
+        .post("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "bad", "recurrence": "daily", "time_zone": "Mars/Olympus_Mons" }))
+        .send()
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY, "{}", response.text());
+}
+
+#[tokio::test]
+async fn count_runs_out() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let first = create(&app, workspace_id, json!({ "body": "twice", "due_at": "2030-01-01T08:00:00Z", "recurrence": "FREQ=DAILY;COUNT=2" })).await;
+    assert_eq!(occurrences(&app, workspace_id, &first["id"], 10).await, json!(["2030-01-01T08:00:00Z", "2030-01-02T08:00:00Z"]));
+
+    let done = complete(&app, workspace_id, &first["id"]).await;
+    let next = app.get(&format!("/v1/todos/{}", done["next_todo_id"])).user(1).workspace(workspace_id).send().await.json();
+    assert_eq!(next["due_at"], "2030-01-02T08:00:00Z");
+    assert_eq!(next["recurrence"], "FREQ=DAILY;COUNT=1");
+
+    // последнее вхождение серии следующего не порождает
+    let last = complete(&app, workspace_id, &next["id"]).await;
+    assert_eq!(last["next_todo_id"], json!(null));
+}
+
+#[tokio::test]
+async fn until_ends_the_series() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let todo = create(
+        &app,
+        workspace_id,
+        json!({ "body": "short", "due_at": "2030-01-01T08:00:00Z", "recurrence": "FREQ=DAILY;INTERVAL=2;UNTIL=20300105" }),
+    )
+    .await;
+    assert_eq!(
+        occurrences(&app, workspace_id, &todo["id"], 10).await,
+        json!(["2030-01-01T08:00:00Z", "2030-01-03T08:00:00Z", "2030-01-05T08:00:00Z"])
+    );
+}
+
+#[tokio::test]
+async fn last_day_of_month() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let todo = create(
+        &app,
+        workspace_id,
+        json!({ "body": "rent", "due_at": "2030-01-31T09:00:00Z", "recurrence": "monthly:-1", "time_zone": "UTC" }),
+    )
+    .await;
+    assert_eq!(todo["recurrence"], "FREQ=MONTHLY;BYMONTHDAY=-1");
+    assert_eq!(
+        occurrences(&app, workspace_id, &todo["id"], 3).await,
+        json!(["2030-01-31T09:00:00Z", "2030-02-28T09:00:00Z", "2030-03-31T09:00:00Z"])
+    );
+}
+
+#[tokio::test]
+async fn missing_due_at_starts_at_next_occurrence() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let before = chrono::Utc::now();
+
+    let todo = create(&app, workspace_id, json!({ "body": "weekly review", "recurrence": "weekly:mo", "time_zone": "Europe/Berlin" })).await;
+    let due_at: chrono::DateTime<chrono::Utc> = todo["due_at"].as_str().unwrap().parse().unwrap();
+    assert!(due_at >= before - chrono::Duration::seconds(1), "{due_at}");
+    assert!(due_at <= before + chrono::Duration::days(7), "{due_at}");
+    assert_eq!(due_at.with_timezone(&chrono_tz::Europe::Berlin).format("%a").to_string(), "Mon");
+
+    // серия, которая уже закончилась, создать нельзя
+    app.post("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "past", "recurrence": "FREQ=DAILY;UNTIL=20200101" }))
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+}
+
+#[tokio::test]
+async fn occurrence_limit_is_bounded() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let todo = create(&app, workspace_id, json!({ "body": "daily", "due_at": "2030-01-01T08:00:00Z", "recurrence": "daily" })).await;
+
+    assert_eq!(occurrences(&app, workspace_id, &todo["id"], 100).await.as_array().unwrap().len(), 100);
+    let default = app.get(&format!("/v1/todos/{}/occurrences", todo["id"])).user(1).workspace(workspace_id).send().await.json();
+    assert_eq!(default["occurrences"].as_array().unwrap().len(), 10);
+    for limit in [0, 101] {
+        app.get(&format!("/v1/todos/{}/occurrences?limit={limit}", todo["id"]))
+            .user(1)
+            .workspace(workspace_id)
+            .send()
+            .await
+            .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    }
+}
+
+#[tokio::test]
+async fn clearing_recurrence_stops_the_series() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let todo = create(&app, workspace_id, json!({ "body": "habit", "due_at": "2030-01-01T08:00:00Z", "recurrence": "daily" })).await;
+
+    let cleared = app
+        .patch(&format!("/v1/todos/{}", todo["id"]))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "recurrence": null }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(cleared["recurrence"], json!(null));
+
+    let done = complete(&app, workspace_id, &todo["id"]).await;
+    assert_eq!(done["next_todo_id"], json!(null));
+    let list = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.json();
+    assert_eq!(list.as_array().unwrap().len(), 1);
+}
\ No newline at end of file
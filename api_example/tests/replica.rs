@@ -0,0 +1,98 @@
+mod common;
+
+use api_example::repo::pg::Db;
+use axum::http::StatusCode;
+use common::TestApp;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn dead_replica_does_not_break_startup() {
+    let app = TestApp::spawn().await;
+    // переменную читает только этот тест бинарника
+    unsafe { std::env::set_var("DATABASE_REPLICA_URL", "postgres://postgres@127.0.0.1:1/todo_db") };
+
+    let started = Instant::now();
+    let db = Db::from_env(app.db.primary().clone()).await.expect("lazy replica pool must not connect on startup");
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(db.has_replica());
+    assert!(std::ptr::eq(db.reader(), db.primary()), "reads must stay on primary until the replica answers");
+}
+
+#[tokio::test]
+async fn reads_go_to_primary_while_replica_is_down() {
+    let app = TestApp::spawn_with_dead_replica().await;
+    let workspace_id = app.workspace(1).await;
+    app.add_member(1, workspace_id, 2, "viewer").await;
+    let id = app.todo(1, workspace_id, "still readable").await;
+
+    // пользователь 2 не писал, так что его чтения пошли бы в реплику, будь она жива
+    let list = app.get("/v1/todos").user(2).workspace(workspace_id).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(list[0]["id"], id);
+    app.get(&format!("/v1/todos/{id}")).user(2).workspace(workspace_id).send().await.assert_status(StatusCode::OK);
+
+    let report = app.get("/health/details").send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(report["status"], "degraded");
+    assert_eq!(report["checks"][1]["name"], "replica");
+    assert_eq!(report["checks"][1]["status"], "degraded");
+}
+
+/// Выборка полей идёт мимо кэша, так что ответ показывает, какой пул её прочитал.
+async fn bodies(app: &TestApp, user_id: i64, workspace_id: i64) -> Vec<String> {
+    let list = app
+        .get("/v1/todos?fields=id,body")
+        .user(user_id)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    list.as_array().unwrap().iter().map(|todo| todo["body"].as_str().unwrap().to_string()).collect()
+}
+
+#[tokio::test]
+async fn writers_read_their_writes_from_primary() {
+    let app = TestApp::spawn_with_replica(Duration::from_secs(60)).await;
+    let workspace_id = app.workspace(1).await;
+    app.add_member(1, workspace_id, 2, "viewer").await;
+    app.todo(1, workspace_id, "fresh").await;
+
+    // автор только что писал: его чтения в primary, остальные читают реплику, которая записи не видела
+    assert_eq!(bodies(&app, 1, workspace_id).await, ["fresh"]);
+    assert!(bodies(&app, 2, workspace_id).await.is_empty());
+}
+
+#[tokio::test]
+async fn sticky_window_expires() {
+    let app = TestApp::spawn_with_replica(Duration::from_millis(200)).await;
+    let workspace_id = app.workspace(1).await;
+    app.todo(1, workspace_id, "fresh").await;
+    assert_eq!(bodies(&app, 1, workspace_id).await, ["fresh"]);
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(bodies(&app, 1, workspace_id).await.is_empty(), "after the window reads go to the replica again");
+}
+
+#[tokio::test]
+async fn lagging_replica_reads_are_not_cached() {
+    let app = TestApp::spawn_with_replica(Duration::from_secs(60)).await;
+    let workspace_id = app.workspace(1).await;
+    app.add_member(1, workspace_id, 2, "viewer").await;
+    let id = app.todo(1, workspace_id, "fresh").await;
+    let todo = format!("/v1/todos/{id}");
+
+    // реплика отстаёт: пользователь 2 видит старое состояние, но в кэш оно не попадает
+    let list = app.get("/v1/todos").user(2).workspace(workspace_id).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(list, serde_json::json!([]));
+    app.get(&todo).user(2).workspace(workspace_id).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+    assert_eq!(app.db.cache().stats().entries, 0);
+
+    // иначе автор получил бы из кэша список без своей записи
+    let list = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(list[0]["id"], id);
+    app.get(&todo).user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK);
+
+    // чтения из primary кэшируются как обычно
+    let hits = app.db.cache().stats().hits;
+    app.get(&todo).user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK);
+    assert_eq!(app.db.cache().stats().hits, hits + 1);
+}
\ No newline at end of file
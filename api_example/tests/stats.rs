@@ -0,0 +1,134 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+/// Переносит todo в прошлое: время создания и, если задано, выполнения (UTC).
+async fn backdate(app: &TestApp, id: i64, created_at: &str, completed_at: Option<&str>) {
+    sqlx::query(
+        "UPDATE todo
+         SET created_at = $2::timestamptz, completed_at = $3::timestamptz, done = $3 IS NOT NULL
+         WHERE id = $1",
+    )
+        .bind(id)
+        .bind(created_at)
+        .bind(completed_at)
+        .execute(app.db.primary())
+        .await
+        .unwrap();
+}
+
+async fn seeded_workspace(app: &TestApp) -> i64 {
+    let workspace_id = app.workspace(1).await;
+    for (created_at, completed_at) in [
+        ("2026-03-02 10:00Z", Some("2026-03-03 10:00Z")),
+        ("2026-03-02 10:00Z", Some("2026-03-12 10:00Z")),
+        ("2026-03-09 20:00Z", None),
+        ("2026-03-10 10:00Z", Some("2026-03-10 12:00Z")),
+    ] {
+        let id = app.todo(1, workspace_id, "tracked").await;
+        backdate(app, id, created_at, completed_at).await;
+    }
+    workspace_id
+}
+
+#[tokio::test]
+async fn weekly_stats() {
+    let app = TestApp::spawn().await;
+    let workspace_id = seeded_workspace(&app).await;
+
+    // диапазон расширяется до целых недель
+    let stats = app
+        .get("/v1/stats?from=2026-03-04&to=2026-03-15&bucket=week")
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(stats["total"], 4);
+    assert_eq!(stats["open"], 1);
+    assert_eq!(stats["done"], 3);
+    assert_eq!(stats["completion_rate"], 0.75);
+    assert_eq!(stats["median_time_to_done_secs"], 86400.0);
+    assert_eq!(stats["from"], "2026-03-02");
+    assert_eq!(stats["to"], "2026-03-15");
+    assert_eq!(
+        stats["histogram"],
+        json!([
+            { "start": "2026-03-02", "created": 2, "completed": 1 },
+            { "start": "2026-03-09", "created": 2, "completed": 2 },
+        ])
+    );
+}
+
+#[tokio::test]
+async fn daily_stats_follow_time_zone() {
+    let app = TestApp::spawn().await;
+    let workspace_id = seeded_workspace(&app).await;
+    let created = |stats: &serde_json::Value| -> Vec<i64> {
+        stats["histogram"].as_array().unwrap().iter().map(|bucket| bucket["created"].as_i64().unwrap()).collect()
+    };
+
+    let utc = app
+        .get("/v1/stats?from=2026-03-09&to=2026-03-11&bucket=day")
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(created(&utc), vec![1, 1, 0]);
+    assert_eq!(utc["median_time_to_done_secs"], 7200.0);
+
+    // 20:00 UTC в Токио уже следующий день
+    let tokyo = app
+        .get("/v1/stats?from=2026-03-09&to=2026-03-11&bucket=day&time_zone=Asia/Tokyo")
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(tokyo["time_zone"], "Asia/Tokyo");
+    assert_eq!(created(&tokyo), vec![0, 2, 0]);
+}
+
+#[tokio::test]
+async fn reopening_clears_completed_at() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "flip").await;
+
+    let done = |done: bool| app.patch(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).json(json!({ "done": done }));
+    let completed_at = done(true).send().await.assert_status(StatusCode::OK).json()["completed_at"].clone();
+    assert!(!completed_at.is_null());
+    // повторная отметка не сдвигает время выполнения
+    assert_eq!(done(true).send().await.json()["completed_at"], completed_at);
+    assert!(done(false).send().await.json()["completed_at"].is_null());
+
+    let stats = app.get("/v1/stats").user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(stats["done"], 0);
+    assert!(stats["median_time_to_done_secs"].is_null());
+    assert_eq!(stats["bucket"], "week");
+    assert_eq!(stats["histogram"].as_array().unwrap().len(), 12);
+}
+
+#[tokio::test]
+async fn stats_are_for_owners() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    app.add_member(1, workspace_id, 2, "editor").await;
+
+    app.get("/v1/stats").user(2).workspace(workspace_id).send().await.assert_error(StatusCode::FORBIDDEN, "forbidden");
+    app.get("/v1/stats").user(3).workspace(workspace_id).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+    for query in ["from=2026-03-10&to=2026-03-01", "from=2020-01-01&to=2026-01-01&bucket=day"] {
+        app.get(&format!("/v1/stats?{query}"))
+            .user(1)
+            .workspace(workspace_id)
+            .send()
+            .await
+            .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,259 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::{json, Value};
+
+async fn pull(app: &TestApp, workspace_id: i64, since: Option<&str>) -> Value {
+    let uri = match since {
+        Some(since) => format!("/v1/sync?since={since}"),
+        None => "/v1/sync".to_string(),
+    };
+    app.get(&uri).user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK).json()
+}
+
+async fn push(app: &TestApp, workspace_id: i64, changes: Value) -> Vec<Value> {
+    let result = app
+        .post("/v1/sync")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "changes": changes }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    result["results"].as_array().unwrap().clone()
+}
+
+fn ids(todos: &Value) -> Vec<i64> {
+    let mut ids: Vec<i64> = todos.as_array().unwrap().iter().map(|todo| todo["id"].as_i64().unwrap()).collect();
+    ids.sort();
+    ids
+}
+
+#[tokio::test]
+async fn pull_returns_changes_and_tombstones_since_token() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let kept = app.todo(1, workspace_id, "kept").await;
+    let changed = app.todo(1, workspace_id, "changed").await;
+    let deleted = app.todo(1, workspace_id, "deleted").await;
+
+    let initial = pull(&app, workspace_id, None).await;
+    assert_eq!(ids(&initial["todos"]), vec![kept, changed, deleted]);
+    assert_eq!(initial["deleted"], json!([]));
+    let token = initial["token"].as_str().unwrap().to_string();
+
+    app.patch(&format!("/v1/todos/{changed}"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "done": true }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.delete(&format!("/v1/todos/{deleted}"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let delta = pull(&app, workspace_id, Some(&token)).await;
+    assert_eq!(ids(&delta["todos"]), vec![changed]);
+    assert_eq!(delta["todos"][0]["version"], 2);
+    assert_eq!(ids(&delta["deleted"]), vec![deleted]);
+
+    // без новых изменений следующий ответ пуст
+    let next = delta["token"].as_str().unwrap();
+    let empty = pull(&app, workspace_id, Some(next)).await;
+    assert_eq!(empty["todos"], json!([]));
+    assert_eq!(empty["deleted"], json!([]));
+
+    app.get("/v1/sync?since=latest")
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+}
+
+#[tokio::test]
+async fn push_creates_updates_and_deletes() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let updated = app.todo(1, workspace_id, "before").await;
+    let deleted = app.todo(1, workspace_id, "deleted").await;
+
+    let results = push(
+        &app,
+        workspace_id,
+        json!([
+            { "client_id": "local-1", "body": "offline", "updated_at": "2026-01-01T00:00:00Z" },
+            { "id": updated, "base_version": 1, "body": "after", "done": true, "updated_at": "2026-01-01T00:00:00Z" },
+            { "id": deleted, "base_version": 1, "deleted": true, "updated_at": "2026-01-01T00:00:00Z" },
+        ]),
+    )
+        .await;
+
+    assert_eq!(results[0]["outcome"], "created");
+    assert_eq!(results[0]["client_id"], "local-1");
+    assert_eq!(results[0]["todo"]["body"], "offline");
+    assert_eq!(results[1]["outcome"], "updated");
+    assert_eq!(results[1]["conflict"], false);
+    assert_eq!(results[1]["todo"]["body"], "after");
+    assert_eq!(results[1]["todo"]["done"], true);
+    assert_eq!(results[1]["todo"]["version"], 2);
+    assert_eq!(results[2]["outcome"], "deleted");
+    assert_eq!(results[2]["id"], deleted);
+
+    let created = results[0]["id"].as_i64().unwrap();
+    assert_eq!(ids(&pull(&app, workspace_id, None).await["todos"]), vec![updated, created]);
+}
+
+#[tokio::test]
+async fn stale_push_resolved_by_last_writer() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "created").await;
+    app.patch(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "server" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    // клиент правил версию 1 раньше сервера: остаётся серверное состояние
+    let results = push(
+        &app,
+        workspace_id,
+        json!([{ "id": id, "base_version": 1, "body": "old client", "updated_at": "2020-01-01T00:00:00Z" }]),
+    )
+        .await;
+    assert_eq!(results[0]["outcome"], "rejected");
+    assert_eq!(results[0]["conflict"], true);
+    assert_eq!(results[0]["todo"]["body"], "server");
+
+    // более позднее изменение клиента перезаписывает сервер
+    let results = push(
+        &app,
+        workspace_id,
+        json!([{ "id": id, "base_version": 1, "body": "new client", "updated_at": "2099-01-01T00:00:00Z" }]),
+    )
+        .await;
+    assert_eq!(results[0]["outcome"], "updated");
+    assert_eq!(results[0]["conflict"], true);
+    assert_eq!(results[0]["todo"]["body"], "new client");
+}
+
+#[tokio::test]
+async fn comments_do_not_conflict_with_offline_edits() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "offline").await;
+    let pulled = pull(&app, workspace_id, None).await;
+    let token = pulled["token"].as_str().unwrap().to_string();
+    let edited_at = chrono::Utc::now().to_rfc3339();
+
+    // пока клиент офлайн, todo комментируют, а один комментарий удаляют
+    let comments = format!("/v1/todos/{id}/comments");
+    let comment = |body: &'static str| {
+        app.post(&comments).user(1).workspace(workspace_id).json(json!({ "body": body })).send()
+    };
+    comment("first").await.assert_status(StatusCode::OK);
+    let second = comment("second").await.assert_status(StatusCode::OK).json();
+    app.delete(&format!("{comments}/{}", second["id"]))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert!(pull(&app, workspace_id, Some(&token)).await["todos"].as_array().unwrap().is_empty());
+
+    let results = push(
+        &app,
+        workspace_id,
+        json!([{ "id": id, "base_version": pulled["todos"][0]["version"], "body": "edited offline", "updated_at": edited_at }]),
+    )
+        .await;
+    assert_eq!(results[0]["outcome"], "updated");
+    assert_eq!(results[0]["conflict"], false);
+    assert_eq!(results[0]["todo"]["body"], "edited offline");
+    assert_eq!(results[0]["todo"]["comment_count"], 1);
+}
+
+#[tokio::test]
+async fn invalid_change_is_rejected_alone() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let empty = app.todo(1, workspace_id, "untouched").await;
+    let edited = app.todo(1, workspace_id, "before").await;
+
+    // изменение без полей не проходит ту же проверку, что и PATCH, но пакет применяется
+    let results = push(
+        &app,
+        workspace_id,
+        json!([
+            { "id": empty, "base_version": 1, "updated_at": "2099-01-01T00:00:00Z" },
+            { "id": edited, "base_version": 1, "body": "after", "updated_at": "2099-01-01T00:00:00Z" },
+        ]),
+    )
+        .await;
+    assert_eq!(results[0]["outcome"], "rejected");
+    assert_eq!(results[0]["conflict"], false);
+    assert_eq!(results[0]["error"]["code"], "update_empty");
+    assert!(results[0]["error"]["message"].as_str().is_some_and(|message| !message.is_empty()));
+    assert_eq!(results[0]["todo"]["version"], 1);
+    assert_eq!(results[1]["outcome"], "updated");
+    assert_eq!(results[1]["todo"]["body"], "after");
+    assert!(results[1].get("error").is_none());
+
+    let todo = app.get(&format!("/v1/todos/{empty}")).user(1).workspace(workspace_id).send().await.json();
+    assert_eq!((&todo["body"], &todo["version"]), (&json!("untouched"), &json!(1)));
+}
+
+#[tokio::test]
+async fn push_to_deleted_todo_is_gone() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "deleted").await;
+    app.delete(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).send().await.assert_status(StatusCode::NO_CONTENT);
+
+    let results = push(
+        &app,
+        workspace_id,
+        json!([
+            { "id": id, "base_version": 1, "done": true, "updated_at": "2099-01-01T00:00:00Z" },
+            { "id": id, "base_version": 1, "deleted": true, "updated_at": "2099-01-01T00:00:00Z" },
+        ]),
+    )
+        .await;
+    assert_eq!(results[0]["outcome"], "gone");
+    assert_eq!(results[0]["conflict"], true);
+    assert_eq!(results[1]["outcome"], "gone");
+    assert_eq!(results[1]["conflict"], false);
+}
+
+#[tokio::test]
+async fn push_validation_and_roles() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    app.add_member(1, workspace_id, 2, "viewer").await;
+
+    app.get("/v1/sync").user(2).workspace(workspace_id).send().await.assert_status(StatusCode::OK);
+    app.post("/v1/sync")
+        .user(2)
+        .workspace(workspace_id)
+        .json(json!({ "changes": [] }))
+        .send()
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "forbidden");
+
+    app.post("/v1/sync")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "changes": [{ "client_id": "local-1", "updated_at": "2026-01-01T00:00:00Z" }] }))
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+}
\ No newline at end of file
@@ -0,0 +1,160 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn health_and_ready() {
+    let app = TestApp::spawn().await;
+
+    let health = app.get("/health").send().await.assert_status(StatusCode::OK);
+    assert_eq!(health.text(), "Ok");
+    assert_eq!(health.header("cache-control"), Some("no-store"));
+
+    let ready = app.get("/ready").send().await.assert_status(StatusCode::OK);
+    assert_eq!(ready.text(), "ok");
+}
+
+#[tokio::test]
+async fn ready_fails_when_database_is_down() {
+    let app = TestApp::without_database();
+
+    app.get("/health").send().await.assert_status(StatusCode::OK);
+    app.get("/ready")
+        .send()
+        .await
+        .assert_error(StatusCode::INTERNAL_SERVER_ERROR, "db_error");
+}
+
+#[tokio::test]
+async fn health_details_report_dependencies() {
+    let app = TestApp::spawn().await;
+
+    let report = app.get("/health/details").send().await.assert_status(StatusCode::OK);
+    assert_eq!(report.header("cache-control"), Some("no-store"));
+    let report = report.json();
+    assert_ne!(report["status"], "unhealthy");
+    assert_eq!(report["build"]["version"], env!("CARGO_PKG_VERSION"));
+
+    let database = &report["checks"][0];
+    assert_eq!(database["name"], "database");
+    assert!(database["latency_ms"].as_f64().unwrap() >= 0.0);
+    assert_eq!(database["pool"]["max_connections"], 5);
+
+    let migrations = &report["checks"][1];
+    assert_eq!(migrations["name"], "migrations");
+    assert_eq!(migrations["status"], "healthy");
+    assert_eq!(migrations["migrations"]["applied"], migrations["migrations"]["expected"]);
+    assert_eq!(migrations["migrations"]["pending"], json!([]));
+
+    // схема отстала от сборки
+    sqlx::query("DELETE FROM _sqlx_migrations WHERE version = (SELECT max(version) FROM _sqlx_migrations)")
+        .execute(app.db.primary())
+        .await
+        .unwrap();
+    let report = app.get("/health/details").send().await.assert_status(StatusCode::SERVICE_UNAVAILABLE).json();
+    assert_eq!(report["status"], "unhealthy");
+    assert_eq!(report["checks"][1]["status"], "unhealthy");
+    assert_eq!(report["checks"][1]["migrations"]["pending"], json!([report["checks"][1]["migrations"]["expected"]]));
+}
+
+#[tokio::test]
+async fn health_details_fail_when_database_is_down() {
+    let app = TestApp::without_database();
+
+    let report = app.get("/health/details").send().await.assert_status(StatusCode::SERVICE_UNAVAILABLE).json();
+    assert_eq!(report["status"], "unhealthy");
+    assert_eq!(report["checks"][0]["status"], "unhealthy");
+    assert!(report["checks"][0]["error"].is_string());
+    assert_eq!(report["checks"][1]["status"], "unhealthy");
+    // подробности sqlx (адрес, причина) только в логе
+    for check in report["checks"].as_array().unwrap() {
+        let error = check["error"].as_str().unwrap();
+        assert!(!error.contains("127.0.0.1") && !error.to_lowercase().contains("refused"), "{error}");
+    }
+}
+
+#[tokio::test]
+async fn health_details_do_not_change_schema() {
+    let app = TestApp::spawn().await;
+    let count = || async {
+        sqlx::query_scalar::<_, i64>("SELECT count(*) FROM _sqlx_migrations").fetch_one(app.db.primary()).await.unwrap()
+    };
+    let before = count().await;
+
+    // без таблицы миграций отчёт показывает всё pending, но не создаёт её заново
+    sqlx::query("ALTER TABLE _sqlx_migrations RENAME TO _sqlx_migrations_saved").execute(app.db.primary()).await.unwrap();
+    let report = app.get("/health/details").send().await.assert_status(StatusCode::SERVICE_UNAVAILABLE).json();
+    let exists: bool = sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+        .fetch_one(app.db.primary())
+        .await
+        .unwrap();
+    sqlx::query("ALTER TABLE _sqlx_migrations_saved RENAME TO _sqlx_migrations").execute(app.db.primary()).await.unwrap();
+
+    assert!(!exists);
+    assert_eq!(report["checks"][1]["migrations"]["applied"], json!(null));
+    assert_eq!(report["checks"][1]["migrations"]["pending"].as_array().unwrap().len() as i64, before);
+    app.get("/health/details").send().await.assert_status(StatusCode::OK);
+    assert_eq!(count().await, before);
+}
+
+#[tokio::test]
+async fn metrics_report_cache_counters() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "cached").await;
+    for _ in 0..2 {
+        app.get(&format!("/v1/todos/{id}"))
+            .user(1)
+            .workspace(workspace_id)
+            .send()
+            .await
+            .assert_status(StatusCode::OK);
+    }
+
+    let metrics = app.get("/metrics").send().await.assert_status(StatusCode::OK);
+    assert!(metrics.header("content-type").unwrap().starts_with("text/plain"));
+    let text = metrics.text();
+    assert!(text.contains("todo_cache_hits_total 1\n"), "{text}");
+    assert!(text.contains("todo_cache_misses_total 1\n"), "{text}");
+    assert!(text.contains("todo_cache_capacity 1000\n"), "{text}");
+}
+
+#[tokio::test]
+async fn openapi_documents_every_route() {
+    let app = TestApp::spawn().await;
+
+    let doc = app.get("/api-docs/openapi.json").send().await.assert_status(StatusCode::OK).json();
+    let paths = doc["paths"].as_object().unwrap();
+    for path in [
+        "/v1/todos",
+        "/v1/todos/{id}",
+        "/v1/todos/{id}/occurrences",
+        "/v1/todos/{id}/comments",
+        "/v1/todos/{id}/comments/{comment_id}",
+        "/v1/todos/{id}/attachments",
+        "/v1/todos/{id}/attachments/{attachment_id}",
+        "/v1/workspaces",
+        "/v1/workspaces/{workspace_id}/members",
+        "/v1/workspaces/{workspace_id}/members/{user_id}",
+        "/v1/api-keys",
+        "/v1/api-keys/{id}",
+    ] {
+        assert!(paths.contains_key(path), "{path} is missing from OpenAPI");
+    }
+}
+
+#[tokio::test]
+async fn v1_requires_authentication() {
+    let app = TestApp::spawn().await;
+
+    app.get("/v1/workspaces").send().await.assert_error(StatusCode::UNAUTHORIZED, "unauthorized");
+    app.get("/v1/todos").workspace(1).send().await.assert_error(StatusCode::UNAUTHORIZED, "unauthorized");
+    app.get("/v1/todos")
+        .bearer("tk_not-a-real-token")
+        .workspace(1)
+        .send()
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED, "unauthorized");
+}
\ No newline at end of file
@@ -0,0 +1,248 @@
+use api_example::tls::{self, TlsSettings};
+use axum::Router;
+use axum::routing::get;
+use rcgen::{
+    BasicConstraints, CertificateParams, CertifiedIssuer, DnType, ExtendedKeyUsagePurpose, IsCa, KeyPair, KeyUsagePurpose,
+};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::{ClientConfig, RootCertStore};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::client::TlsStream;
+
+/// Временный каталог под PEM-файлы, удаляется вместе с тестом.
+struct Dir(PathBuf);
+
+impl Dir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("api_example-tls-{}-{}", std::process::id(), name));
+        std::fs::create_dir_all(&path).unwrap();
+        Dir(path)
+    }
+
+    fn write(&self, name: &str, contents: &str) -> PathBuf {
+        let path = self.0.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for Dir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+type Ca = CertifiedIssuer<'static, KeyPair>;
+
+fn ca(name: &str) -> Ca {
+    let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    params.distinguished_name.push(DnType::CommonName, name);
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::DigitalSignature];
+    CertifiedIssuer::self_signed(params, KeyPair::generate().unwrap()).unwrap()
+}
+
+/// Сертификат, подписанный `ca`: сертификат и ключ в PEM, сертификат в DER.
+struct Leaf {
+    cert_pem: String,
+    key_pem: String,
+    der: CertificateDer<'static>,
+}
+
+impl Leaf {
+    fn new(ca: &Ca, name: &str, purpose: ExtendedKeyUsagePurpose) -> Self {
+        let mut params = CertificateParams::new(vec![name.to_string()]).unwrap();
+        params.distinguished_name.push(DnType::CommonName, name);
+        params.extended_key_usages = vec![purpose];
+        let key = KeyPair::generate().unwrap();
+        let cert = params.signed_by(&key, ca).unwrap();
+        Leaf { cert_pem: cert.pem(), key_pem: key.serialize_pem(), der: cert.der().clone() }
+    }
+
+    fn server(ca: &Ca) -> Self {
+        Leaf::new(ca, "localhost", ExtendedKeyUsagePurpose::ServerAuth)
+    }
+
+    fn client(ca: &Ca) -> Self {
+        Leaf::new(ca, "client", ExtendedKeyUsagePurpose::ClientAuth)
+    }
+
+    fn key(&self) -> PrivateKeyDer<'static> {
+        use rustls::pki_types::pem::PemObject;
+        PrivateKeyDer::from_pem_slice(self.key_pem.as_bytes()).unwrap()
+    }
+}
+
+struct Server {
+    addr: SocketAddr,
+    shutdown: oneshot::Sender<()>,
+    served: JoinHandle<std::io::Result<()>>,
+}
+
+async fn start(settings: TlsSettings) -> Server {
+    let router = Router::new().route("/", get(|| async { "Ok" })).route(
+        "/slow",
+        get(|| async {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            "done"
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown, signal) = oneshot::channel();
+    let served = tokio::spawn(tls::serve(listener, router, settings, async {
+        let _ = signal.await;
+    }));
+    Server { addr, shutdown, served }
+}
+
+fn connector(ca: &Ca, client: Option<&Leaf>, alpn: &[&[u8]]) -> TlsConnector {
+    let mut roots = RootCertStore::empty();
+    roots.add(ca.der().clone()).unwrap();
+    let builder = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots);
+    let mut config = match client {
+        Some(leaf) => builder.with_client_auth_cert(vec![leaf.der.clone()], leaf.key()).unwrap(),
+        None => builder.with_no_client_auth(),
+    };
+    config.alpn_protocols = alpn.iter().map(|protocol| protocol.to_vec()).collect();
+    TlsConnector::from(Arc::new(config))
+}
+
+async fn connect(addr: SocketAddr, connector: &TlsConnector) -> std::io::Result<TlsStream<TcpStream>> {
+    let tcp = TcpStream::connect(addr).await?;
+    connector.connect(ServerName::try_from("localhost").unwrap(), tcp).await
+}
+
+/// HTTP/1.1 запрос по уже открытому соединению, ответ целиком.
+async fn get_path(stream: &mut TlsStream<TcpStream>, path: &str) -> std::io::Result<String> {
+    stream
+        .write_all(format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").as_bytes())
+        .await?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await?;
+    Ok(String::from_utf8_lossy(&response).into_owned())
+}
+
+fn peer_certificate(stream: &TlsStream<TcpStream>) -> CertificateDer<'static> {
+    stream.get_ref().1.peer_certificates().unwrap()[0].clone().into_owned()
+}
+
+#[tokio::test]
+async fn negotiates_h2_and_falls_back_to_http1() {
+    let dir = Dir::new("alpn");
+    let ca = ca("test ca");
+    let server = Leaf::server(&ca);
+    let settings = TlsSettings::new(dir.write("cert.pem", &server.cert_pem), dir.write("key.pem", &server.key_pem));
+    let server = start(settings).await;
+
+    let mut stream = connect(server.addr, &connector(&ca, None, &[b"h2", b"http/1.1"])).await.unwrap();
+    assert_eq!(stream.get_ref().1.alpn_protocol(), Some(&b"h2"[..]));
+    // на preface HTTP/2 сервер отвечает своим кадром SETTINGS
+    stream.write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\x00\x00\x00\x04\x00\x00\x00\x00\x00").await.unwrap();
+    let mut frame = [0; 9];
+    stream.read_exact(&mut frame).await.unwrap();
+    assert_eq!(frame[3], 0x4, "expected a SETTINGS frame, got {frame:?}");
+
+    let mut stream = connect(server.addr, &connector(&ca, None, &[b"http/1.1"])).await.unwrap();
+    assert_eq!(stream.get_ref().1.alpn_protocol(), Some(&b"http/1.1"[..]));
+    let response = get_path(&mut stream, "/").await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+    assert!(response.ends_with("Ok"), "{response}");
+}
+
+#[tokio::test]
+async fn changed_certificates_are_reloaded() {
+    let dir = Dir::new("reload");
+    let ca = ca("test ca");
+    let first = Leaf::server(&ca);
+    let settings = TlsSettings::new(dir.write("cert.pem", &first.cert_pem), dir.write("key.pem", &first.key_pem))
+        .with_reload_interval(Duration::from_millis(50));
+    let server = start(settings).await;
+    let connector = connector(&ca, None, &[b"http/1.1"]);
+
+    let mut open = connect(server.addr, &connector).await.unwrap();
+    assert_eq!(peer_certificate(&open), first.der);
+
+    let second = Leaf::server(&ca);
+    dir.write("key.pem", &second.key_pem);
+    dir.write("cert.pem", &second.cert_pem);
+
+    let started = Instant::now();
+    loop {
+        let stream = connect(server.addr, &connector).await.unwrap();
+        if peer_certificate(&stream) == second.der {
+            break;
+        }
+        assert!(started.elapsed() < Duration::from_secs(5), "certificate was not reloaded");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    // соединение, открытое до перезагрузки, продолжает работать
+    let response = get_path(&mut open, "/").await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+}
+
+#[tokio::test]
+async fn mtls_rejects_clients_without_trusted_certificate() {
+    let dir = Dir::new("mtls");
+    let ca = ca("test ca");
+    let server = Leaf::server(&ca);
+    let settings = TlsSettings::new(dir.write("cert.pem", &server.cert_pem), dir.write("key.pem", &server.key_pem))
+        .with_client_ca(dir.write("client-ca.pem", &ca.pem()));
+    let server = start(settings).await;
+
+    // в TLS 1.3 клиент узнаёт об отказе уже после рукопожатия, при чтении ответа
+    let request = async |connector: TlsConnector| {
+        let mut stream = connect(server.addr, &connector).await?;
+        get_path(&mut stream, "/").await
+    };
+
+    let anonymous = request(connector(&ca, None, &[b"http/1.1"])).await;
+    assert!(anonymous.is_err(), "{anonymous:?}");
+
+    let stranger = Leaf::client(&self::ca("other ca"));
+    let untrusted = request(connector(&ca, Some(&stranger), &[b"http/1.1"])).await;
+    assert!(untrusted.is_err(), "{untrusted:?}");
+
+    let client = Leaf::client(&ca);
+    let response = request(connector(&ca, Some(&client), &[b"http/1.1"])).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+}
+
+#[tokio::test]
+async fn shutdown_lets_open_requests_finish() {
+    let dir = Dir::new("shutdown");
+    let ca = ca("test ca");
+    let server = Leaf::server(&ca);
+    let settings = TlsSettings::new(dir.write("cert.pem", &server.cert_pem), dir.write("key.pem", &server.key_pem));
+    let server = start(settings).await;
+
+    let mut stream = connect(server.addr, &connector(&ca, None, &[b"http/1.1"])).await.unwrap();
+    let slow = tokio::spawn(async move { get_path(&mut stream, "/slow").await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    server.shutdown.send(()).unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!server.served.is_finished(), "serve must wait for the open request");
+    let response = slow.await.unwrap().unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"), "{response}");
+    assert!(response.ends_with("done"), "{response}");
+
+    tokio::time::timeout(Duration::from_secs(2), server.served)
+        .await
+        .expect("serve must return after open connections are closed")
+        .unwrap()
+        .unwrap();
+    assert!(TcpStream::connect(server.addr).await.is_err(), "listener must be closed");
+}
\ No newline at end of file
@@ -0,0 +1,544 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+#[tokio::test]
+async fn create_read_update_delete() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+
+    let created = app
+        .post("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "buy milk" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    let id = created["id"].as_i64().unwrap();
+    assert_eq!(created["body"], "buy milk");
+    assert_eq!(created["done"], false);
+    assert_eq!(created["workspace_id"], workspace_id);
+    assert_eq!(created["comment_count"], 0);
+    assert!(created["completed_at"].is_null());
+
+    let read = app.get(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).send().await;
+    assert_eq!(read.assert_status(StatusCode::OK).json(), created);
+
+    let updated = app
+        .patch(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "done": true }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(updated["done"], true);
+    assert_eq!(updated["body"], "buy milk");
+    assert!(!updated["completed_at"].is_null());
+
+    let list = app.get("/v1/todos").user(1).workspace(workspace_id).send().await;
+    assert_eq!(list.assert_status(StatusCode::OK).json(), json!([updated]));
+
+    app.delete(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.get(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn workspace_from_path_or_header() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "nested").await;
+
+    let todo = app
+        .get(&format!("/v1/workspaces/{workspace_id}/todos/{id}"))
+        .user(1)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(todo["id"], id);
+
+    app.get("/v1/todos")
+        .user(1)
+        .send()
+        .await
+        .assert_error(StatusCode::BAD_REQUEST, "validateion_error");
+}
+
+#[tokio::test]
+async fn missing_todo_is_not_found() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+
+    app.get("/v1/todos/4242").user(1).workspace(workspace_id).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+    app.patch("/v1/todos/4242")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "done": true }))
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+    app.delete("/v1/todos/4242").user(1).workspace(workspace_id).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn invalid_bodies_are_rejected() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "todo").await;
+
+    app.patch(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({}))
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+
+    // ошибки разбора JSON отдаёт сам axum
+    app.post("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "text": "no body field" }))
+        .send()
+        .await
+        .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    app.post("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .header("content-type", "application/json")
+        .send()
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+async fn bodies(app: &TestApp, workspace_id: i64) -> Vec<String> {
+    app.get("/v1/todos")
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|todo| todo["body"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn move_reorders_list() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let a = app.todo(1, workspace_id, "a").await;
+    let b = app.todo(1, workspace_id, "b").await;
+    let c = app.todo(1, workspace_id, "c").await;
+    assert_eq!(bodies(&app, workspace_id).await, ["a", "b", "c"]);
+
+    let moved = app
+        .post(&format!("/v1/todos/{c}/move"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "before": a }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(moved["id"], c);
+    assert_eq!(bodies(&app, workspace_id).await, ["c", "a", "b"]);
+
+    app.post(&format!("/v1/todos/{c}/move"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "after": b }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(bodies(&app, workspace_id).await, ["a", "b", "c"]);
+
+    app.post(&format!("/v1/todos/{a}/move"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "after": b }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(bodies(&app, workspace_id).await, ["b", "a", "c"]);
+}
+
+#[tokio::test]
+async fn invalid_moves_are_rejected() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let a = app.todo(1, workspace_id, "a").await;
+    let b = app.todo(1, workspace_id, "b").await;
+    let foreign = app.todo(1, app.workspace(1).await, "foreign").await;
+    let uri = format!("/v1/todos/{a}/move");
+
+    for body in [
+        json!({}),
+        json!({ "before": b, "after": b }),
+        json!({ "before": a }),
+        json!({ "after": 4242 }),
+        json!({ "after": foreign }),
+    ] {
+        app.post(&uri)
+            .user(1)
+            .workspace(workspace_id)
+            .json(body)
+            .send()
+            .await
+            .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    }
+    app.post("/v1/todos/4242/move")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "after": b }))
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn concurrent_moves_keep_positions_unique() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let first = app.todo(1, workspace_id, "first").await;
+    let mut ids = Vec::new();
+    for n in 0..8 {
+        ids.push(app.todo(1, workspace_id, &format!("todo {n}")).await);
+    }
+
+    // все одновременно встают сразу после first и претендуют на один и тот же ранг
+    let moves = ids.iter().map(|id| {
+        app.post(&format!("/v1/todos/{id}/move"))
+            .user(1)
+            .workspace(workspace_id)
+            .json(json!({ "after": first }))
+            .send()
+    });
+    for response in futures_util::future::join_all(moves).await {
+        response.assert_status(StatusCode::OK);
+    }
+
+    let list = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.json();
+    let mut positions: Vec<_> = list.as_array().unwrap().iter().map(|todo| todo["position"].as_str().unwrap().to_string()).collect();
+    assert_eq!(list[0]["id"], first);
+    positions.dedup();
+    assert_eq!(positions.len(), 9);
+}
+
+#[tokio::test]
+async fn long_ranks_are_rebalanced() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let first = app.todo(1, workspace_id, "first").await;
+    app.todo(1, workspace_id, "last").await;
+
+    // каждая вставка делит один и тот же промежуток пополам, ранг растёт
+    let mut expected = vec!["first".to_string(), "last".to_string()];
+    let mut longest = 0;
+    for n in 0..150 {
+        let body = format!("todo {n}");
+        let id = app.todo(1, workspace_id, &body).await;
+        let moved = app
+            .post(&format!("/v1/todos/{id}/move"))
+            .user(1)
+            .workspace(workspace_id)
+            .json(json!({ "after": first }))
+            .send()
+            .await
+            .assert_status(StatusCode::OK)
+            .json();
+        longest = longest.max(moved["position"].as_str().unwrap().len());
+        expected.insert(1, body);
+    }
+
+    assert!(longest <= api_example::rank::MAX_LEN, "ranks grew to {longest}");
+    assert_eq!(bodies(&app, workspace_id).await, expected);
+}
+
+#[tokio::test]
+async fn appends_keep_ranks_short() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+
+    let mut expected = Vec::new();
+    let mut longest = 0;
+    for n in 0..160 {
+        let body = format!("todo {n}");
+        let id = app.todo(1, workspace_id, &body).await;
+        let todo = app.get(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).send().await.json();
+        longest = longest.max(todo["position"].as_str().unwrap().len());
+        expected.push(body);
+    }
+    assert!(longest <= api_example::rank::MAX_LEN, "ranks grew to {longest}");
+    assert_eq!(bodies(&app, workspace_id).await, expected);
+
+    // в конце не осталось места: добавление пересчитывает ранги workspace
+    let last = "z".repeat(api_example::rank::MAX_LEN);
+    sqlx::query("UPDATE todo SET position = $1 WHERE workspace_id = $2 AND body = 'todo 159'")
+        .bind(&last)
+        .bind(workspace_id)
+        .execute(app.db.primary())
+        .await
+        .unwrap();
+    app.todo(1, workspace_id, "after rebalance").await;
+    expected.push("after rebalance".to_string());
+    assert_eq!(bodies(&app, workspace_id).await, expected);
+    let list = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.json();
+    assert!(list.as_array().unwrap().iter().all(|todo| todo["position"].as_str().unwrap().len() < api_example::rank::MAX_LEN));
+}
+
+#[tokio::test]
+async fn merge_patch_sets_and_clears_fields() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "call mom").await;
+    let patch = |body: &str| {
+        app.patch(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).body("application/merge-patch+json", body)
+    };
+
+    let todo = patch(r#"{ "due_at": "2026-05-01T09:00:00Z", "time_zone": "Europe/Berlin" }"#)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(todo["due_at"], "2026-05-01T09:00:00Z");
+    assert_eq!(todo["time_zone"], "Europe/Berlin");
+    assert_eq!(todo["body"], "call mom");
+
+    // null в merge patch сбрасывает поле
+    let todo = patch(r#"{ "due_at": null, "done": true }"#).send().await.assert_status(StatusCode::OK).json();
+    assert!(todo["due_at"].is_null());
+    assert_eq!(todo["done"], true);
+    assert_eq!(todo["version"], 3);
+
+    // без изменений версия не растёт
+    let todo = patch(r#"{ "body": "call mom" }"#).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(todo["version"], 3);
+
+    patch(r#"{ "body": null }"#).send().await.assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    patch(r#"{ "id": 4242 }"#).send().await.assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    patch(r#"{ "recurrence": "FREQ=HOURLY" }"#).send().await.assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+}
+
+#[tokio::test]
+async fn json_patch_is_applied_atomically() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "draft").await;
+    let patch = |body: &str| {
+        app.patch(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).body("application/json-patch+json", body)
+    };
+
+    let todo = patch(
+        r#"[
+            { "op": "test", "path": "/version", "value": 1 },
+            { "op": "replace", "path": "/body", "value": "final" },
+            { "op": "add", "path": "/due_at", "value": "2026-05-01T09:00:00Z" }
+        ]"#,
+    )
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(todo["body"], "final");
+    assert_eq!(todo["due_at"], "2026-05-01T09:00:00Z");
+    assert_eq!(todo["version"], 2);
+
+    // устаревшая версия: ни одна операция не применяется
+    patch(
+        r#"[
+            { "op": "replace", "path": "/done", "value": true },
+            { "op": "test", "path": "/version", "value": 1 }
+        ]"#,
+    )
+        .send()
+        .await
+        .assert_error(StatusCode::CONFLICT, "validateion_error");
+
+    let todo = patch(r#"[{ "op": "remove", "path": "/due_at" }]"#).send().await.assert_status(StatusCode::OK).json();
+    assert!(todo["due_at"].is_null());
+    assert_eq!(todo["done"], false);
+
+    patch(r#"[{ "op": "replace", "path": "/created_at", "value": "2020-01-01T00:00:00Z" }]"#)
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    patch(r#"[{ "op": "replace", "path": "/done", "value": "yes" }]"#)
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    patch(r#"[{ "op": "replace", "path": "/missing/field", "value": 1 }]"#)
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    patch("not json").send().await.assert_error(StatusCode::BAD_REQUEST, "validateion_error");
+    app.patch(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .body("text/plain", "done")
+        .send()
+        .await
+        .assert_error(StatusCode::UNSUPPORTED_MEDIA_TYPE, "validateion_error");
+}
+
+#[tokio::test]
+async fn fields_select_subset_of_todo() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "call mom").await;
+    app.todo(1, workspace_id, "buy milk").await;
+
+    let list = app.get("/v1/todos?fields=body,done").user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK);
+    assert_eq!(list.json(), json!([{ "body": "call mom", "done": false }, { "body": "buy milk", "done": false }]));
+    let full = app.get("/v1/todos").user(1).workspace(workspace_id).send().await.assert_status(StatusCode::OK);
+    assert_ne!(list.header("etag"), full.header("etag"));
+
+    // все поля дают тот же JSON, что и обычный ответ
+    let all = "id,workspace_id,body,done,comment_count,position,due_at,recurrence,time_zone,next_todo_id,completed_at,ical_uid,tags,priority,version,created_at,updated_at";
+    let projected = app.get(&format!("/v1/todos/{id}?fields={all}")).user(1).workspace(workspace_id).send().await;
+    let plain = app.get(&format!("/v1/todos/{id}")).user(1).workspace(workspace_id).send().await;
+    assert_eq!(projected.assert_status(StatusCode::OK).json(), plain.assert_status(StatusCode::OK).json());
+
+    let error = app
+        .get("/v1/todos?fields=id,title")
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_error(StatusCode::BAD_REQUEST, "validateion_error")
+        .json();
+    let message = error["message"].as_str().unwrap();
+    assert!(message.contains("'title'") && message.contains("body, done"), "{message}");
+    app.get(&format!("/v1/todos/{id}?include=tags"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_error(StatusCode::BAD_REQUEST, "validateion_error");
+}
+
+#[tokio::test]
+async fn include_embeds_comments_and_attachments() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+    let id = app.todo(1, workspace_id, "call mom").await;
+    let empty = app.todo(1, workspace_id, "buy milk").await;
+    app.post(&format!("/v1/todos/{id}/comments"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "body": "after lunch" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.post(&format!("/v1/todos/{id}/attachments"))
+        .user(1)
+        .workspace(workspace_id)
+        .multipart("file", "list.txt", b"milk")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    let list = app
+        .get("/v1/todos?fields=id&include=comments,attachments")
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(list.header("etag"), None);
+    let list = list.json();
+    assert_eq!(list[0]["id"], id);
+    assert_eq!(list[0]["comments"][0]["body"], "after lunch");
+    assert_eq!(list[0]["attachments"][0]["file_name"], "list.txt");
+    assert_eq!(list[1], json!({ "id": empty, "comments": [], "attachments": [] }));
+
+    let todo = app
+        .get(&format!("/v1/todos/{id}?include=comments"))
+        .user(1)
+        .workspace(workspace_id)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(todo["body"], "call mom");
+    assert_eq!(todo["comments"].as_array().unwrap().len(), 1);
+    assert!(todo.get("attachments").is_none());
+}
+
+#[tokio::test]
+async fn quick_add_stores_recognised_fields() {
+    let app = TestApp::spawn().await;
+    let workspace_id = app.workspace(1).await;
+
+    let added = app
+        .post("/v1/todos/quick")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "text": "pay invoice tomorrow 5pm #finance !high", "time_zone": "Europe/Berlin" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(added["todo"]["body"], "pay invoice");
+    assert_eq!(added["todo"]["tags"], json!(["finance"]));
+    assert_eq!(added["todo"]["priority"], "high");
+    assert_eq!(added["todo"]["time_zone"], "Europe/Berlin");
+    assert!(added["todo"]["due_at"].is_string());
+    assert_eq!(
+        added["recognised"],
+        json!([
+            { "kind": "due", "text": "tomorrow" },
+            { "kind": "due", "text": "5pm" },
+            { "kind": "tag", "text": "#finance" },
+            { "kind": "priority", "text": "!high" },
+        ]),
+    );
+
+    // теги и приоритет правятся обычным PATCH
+    let id = added["todo"]["id"].as_i64().unwrap();
+    let todo = app
+        .patch(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .body("application/merge-patch+json", r#"{ "tags": ["finance", "q4"], "priority": null }"#)
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(todo["tags"], json!(["finance", "q4"]));
+    assert!(todo["priority"].is_null());
+
+    app.patch(&format!("/v1/todos/{id}"))
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "tags": ["two words"] }))
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    app.post("/v1/todos/quick")
+        .user(1)
+        .workspace(workspace_id)
+        .json(json!({ "text": "tomorrow #finance" }))
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+}
\ No newline at end of file
@@ -0,0 +1,55 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use opentelemetry::trace::{SpanId, TraceId, TracerProvider};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+use tracing_subscriber::prelude::*;
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const PARENT_ID: &str = "00f067aa0ba902b7";
+
+/// Спаны запросов к приложению, собранные тем же слоем, что и в `logger::init`, но в память.
+async fn request_spans(traceparent: Option<&str>) -> Vec<SpanData> {
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder().with_simple_exporter(exporter.clone()).build();
+    let subscriber = tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+    // тест однопоточный, подписчик действует на весь запрос
+    let _default = tracing::subscriber::set_default(subscriber);
+
+    let app = TestApp::without_database();
+    let mut request = app.get("/health");
+    if let Some(traceparent) = traceparent {
+        request = request.header("traceparent", traceparent);
+    }
+    request.send().await.assert_status(StatusCode::OK);
+
+    provider.force_flush().unwrap();
+    exporter.get_finished_spans().unwrap().into_iter().filter(|span| span.name == "request").collect()
+}
+
+#[tokio::test]
+async fn traceparent_becomes_parent_of_request_span() {
+    let spans = request_spans(Some(&format!("00-{TRACE_ID}-{PARENT_ID}-01"))).await;
+    assert_eq!(spans.len(), 1);
+    let span = &spans[0];
+    assert_eq!(span.span_context.trace_id(), TraceId::from_hex(TRACE_ID).unwrap());
+    assert_eq!(span.parent_span_id, SpanId::from_hex(PARENT_ID).unwrap());
+    assert_ne!(span.span_context.span_id(), SpanId::from_hex(PARENT_ID).unwrap());
+    assert!(span.span_context.is_sampled());
+}
+
+#[tokio::test]
+async fn request_without_traceparent_starts_a_trace() {
+    let spans = request_spans(None).await;
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0].parent_span_id, SpanId::INVALID);
+    assert!(spans[0].span_context.is_valid());
+
+    // битый заголовок не ломает запрос и не становится родителем
+    let spans = request_spans(Some("00-not-a-trace-01")).await;
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0].parent_span_id, SpanId::INVALID);
+}
\ No newline at end of file
@@ -0,0 +1,262 @@
+mod common;
+
+use api_example::repo::workspace;
+use axum::http::{Method, StatusCode};
+use common::{TestApp, TestResponse};
+use serde_json::{json, Value};
+
+const OWNER: i64 = 1;
+const EDITOR: i64 = 2;
+const VIEWER: i64 = 3;
+const OUTSIDER: i64 = 4;
+
+/// Workspace с владельцем, редактором и читателем, в нём todo с комментарием и вложением.
+async fn shared_workspace(app: &TestApp) -> (i64, i64) {
+    let workspace_id = app.workspace(OWNER).await;
+    app.add_member(OWNER, workspace_id, EDITOR, "editor").await;
+    app.add_member(OWNER, workspace_id, VIEWER, "viewer").await;
+
+    let id = app.todo(OWNER, workspace_id, "shared").await;
+    app.post(&format!("/v1/todos/{id}/comments"))
+        .user(OWNER)
+        .workspace(workspace_id)
+        .json(json!({ "body": "comment" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+    app.post(&format!("/v1/todos/{id}/attachments"))
+        .user(OWNER)
+        .workspace(workspace_id)
+        .multipart("file", "notes.txt", b"notes")
+        .send()
+        .await
+        .assert_status(StatusCode::OK);
+
+    (workspace_id, id)
+}
+
+#[tokio::test]
+async fn create_and_list_workspaces() {
+    let app = TestApp::spawn().await;
+
+    let workspace = app
+        .post("/v1/workspaces")
+        .user(OWNER)
+        .json(json!({ "name": "home" }))
+        .send()
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(workspace["name"], "home");
+    assert_eq!(workspace["role"], "owner");
+    app.workspace(OUTSIDER).await;
+
+    let list = app.get("/v1/workspaces").user(OWNER).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(list, json!([workspace]));
+
+    app.post("/v1/workspaces")
+        .user(OWNER)
+        .json(json!({ "name": "  " }))
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+}
+
+#[tokio::test]
+async fn members_are_managed_by_owners() {
+    let app = TestApp::spawn().await;
+    let (workspace_id, _) = shared_workspace(&app).await;
+    let members = format!("/v1/workspaces/{workspace_id}/members");
+
+    let list = app.get(&members).user(VIEWER).send().await.assert_status(StatusCode::OK).json();
+    let roles: Vec<_> = list.as_array().unwrap().iter().map(|member| (member["user_id"].as_i64().unwrap(), member["role"].clone())).collect();
+    assert_eq!(roles, vec![(OWNER, json!("owner")), (EDITOR, json!("editor")), (VIEWER, json!("viewer"))]);
+
+    app.put(&format!("{members}/{OUTSIDER}"))
+        .user(EDITOR)
+        .json(json!({ "role": "viewer" }))
+        .send()
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "forbidden");
+    app.delete(&format!("{members}/{VIEWER}")).user(EDITOR).send().await.assert_error(StatusCode::FORBIDDEN, "forbidden");
+
+    // участник может выйти сам
+    app.delete(&format!("{members}/{VIEWER}")).user(VIEWER).send().await.assert_status(StatusCode::NO_CONTENT);
+    app.get(&members).user(VIEWER).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+    app.delete(&format!("{members}/{VIEWER}")).user(OWNER).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn last_owner_can_not_leave() {
+    let app = TestApp::spawn().await;
+    let (workspace_id, _) = shared_workspace(&app).await;
+    let owner = format!("/v1/workspaces/{workspace_id}/members/{OWNER}");
+
+    app.put(&owner)
+        .user(OWNER)
+        .json(json!({ "role": "editor" }))
+        .send()
+        .await
+        .assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+    app.delete(&owner).user(OWNER).send().await.assert_error(StatusCode::UNPROCESSABLE_ENTITY, "validateion_error");
+
+    app.add_member(OWNER, workspace_id, EDITOR, "owner").await;
+    app.delete(&owner).user(OWNER).send().await.assert_status(StatusCode::NO_CONTENT);
+}
+
+type Write = (Method, String, Option<Value>);
+
+/// Маршруты todo под `base`: `/v1` с заголовком workspace или `/v1/workspaces/{id}` без него.
+fn todo_routes(base: &str, id: i64) -> (Vec<String>, Vec<Write>) {
+    let reads = vec![
+        format!("{base}/todos"),
+        format!("{base}/todos/{id}"),
+        format!("{base}/todos/{id}/comments"),
+        format!("{base}/todos/{id}/attachments"),
+        format!("{base}/todos/{id}/attachments/1"),
+    ];
+    let writes = vec![
+        (Method::POST, format!("{base}/todos"), Some(json!({ "body": "new" }))),
+        (Method::PATCH, format!("{base}/todos/{id}"), Some(json!({ "body": "changed" }))),
+        (Method::POST, format!("{base}/todos/{id}/comments"), Some(json!({ "body": "more" }))),
+        (Method::PATCH, format!("{base}/todos/{id}/comments/1"), Some(json!({ "body": "edited" }))),
+        (Method::DELETE, format!("{base}/todos/{id}/comments/1"), None),
+        (Method::DELETE, format!("{base}/todos/{id}/attachments/1"), None),
+        (Method::DELETE, format!("{base}/todos/{id}"), None),
+    ];
+    (reads, writes)
+}
+
+async fn send(app: &TestApp, user: i64, header: Option<i64>, method: &Method, uri: &str, body: Option<&Value>) -> TestResponse {
+    let mut request = app.request(method.clone(), uri).user(user);
+    if let Some(workspace_id) = header {
+        request = request.workspace(workspace_id);
+    }
+    if let Some(body) = body {
+        request = request.json(body.clone());
+    }
+    request.send().await
+}
+
+/// Читатель только читает, чужой workspace не виден вовсе; `header` - как передан workspace.
+async fn assert_matrix(app: &TestApp, base: &str, header: Option<i64>, id: i64) {
+    let (reads, writes) = todo_routes(base, id);
+    for (user, read_status, write_status) in [
+        (VIEWER, StatusCode::OK, StatusCode::FORBIDDEN),
+        (OUTSIDER, StatusCode::NOT_FOUND, StatusCode::NOT_FOUND),
+    ] {
+        for uri in &reads {
+            let response = send(app, user, header, &Method::GET, uri, None).await;
+            assert_eq!(response.status, read_status, "GET {uri} as user {user}");
+        }
+        for (method, uri, body) in &writes {
+            let response = send(app, user, header, method, uri, body.as_ref()).await;
+            assert_eq!(response.status, write_status, "{method} {uri} as user {user}: {}", response.text());
+        }
+        let mut upload = app.post(&format!("{base}/todos/{id}/attachments")).user(user);
+        if let Some(workspace_id) = header {
+            upload = upload.workspace(workspace_id);
+        }
+        let upload = upload.multipart("file", "x.txt", b"x").send().await;
+        assert_eq!(upload.status, write_status, "upload to {base} as user {user}");
+    }
+
+    // редактор пишет наравне с владельцем, но чужие комментарии не трогает
+    for (method, uri, body) in &writes {
+        let response = send(app, EDITOR, header, method, uri, body.as_ref()).await;
+        if uri.contains("/comments/") {
+            assert_eq!(response.status, StatusCode::FORBIDDEN, "{method} {uri} as editor");
+        } else {
+            assert!(response.status.is_success(), "{method} {uri} as editor: {} {}", response.status, response.text());
+        }
+    }
+}
+
+/// Каждый маршрут todo для каждой роли, workspace в заголовке.
+#[tokio::test]
+async fn tenant_isolation_matrix() {
+    let app = TestApp::spawn().await;
+    let (workspace_id, id) = shared_workspace(&app).await;
+
+    let members = format!("/v1/workspaces/{workspace_id}/members");
+    app.get(&members).user(VIEWER).send().await.assert_status(StatusCode::OK);
+    app.get(&members).user(OUTSIDER).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+
+    // todo из workspace не виден через другой workspace, даже свой
+    let other = app.workspace(OUTSIDER).await;
+    app.get(&format!("/v1/todos/{id}")).user(OUTSIDER).workspace(other).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+    app.get(&format!("/v1/workspaces/{other}/todos/{id}")).user(OUTSIDER).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+
+    assert_matrix(&app, "/v1", Some(workspace_id), id).await;
+}
+
+/// Те же правила для `/v1/workspaces/{id}/todos...`, где workspace берётся из пути.
+#[tokio::test]
+async fn nested_routes_follow_the_same_matrix() {
+    let app = TestApp::spawn().await;
+    let (workspace_id, id) = shared_workspace(&app).await;
+    assert_matrix(&app, &format!("/v1/workspaces/{workspace_id}"), None, id).await;
+
+    // путь важнее заголовка: свой workspace в заголовке не открывает чужой в пути
+    let own = app.workspace(OUTSIDER).await;
+    app.get(&format!("/v1/workspaces/{workspace_id}/todos"))
+        .user(OUTSIDER)
+        .workspace(own)
+        .send()
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "not_found");
+}
+
+/// Смена роли действует со следующего запроса, в том числе на прочитанное раньше.
+#[tokio::test]
+async fn role_changes_apply_immediately() {
+    let app = TestApp::spawn().await;
+    let (workspace_id, id) = shared_workspace(&app).await;
+    let todo = format!("/v1/workspaces/{workspace_id}/todos/{id}");
+    let member = |user: i64| format!("/v1/workspaces/{workspace_id}/members/{user}");
+    let patch = |user: i64, body: &'static str| app.patch(&todo).user(user).json(json!({ "body": body }));
+
+    app.get(&todo).user(VIEWER).send().await.assert_status(StatusCode::OK);
+    patch(VIEWER, "by viewer").send().await.assert_error(StatusCode::FORBIDDEN, "forbidden");
+
+    app.put(&member(VIEWER)).user(OWNER).json(json!({ "role": "editor" })).send().await.assert_status(StatusCode::OK);
+    patch(VIEWER, "by promoted viewer").send().await.assert_status(StatusCode::OK);
+
+    app.put(&member(EDITOR)).user(OWNER).json(json!({ "role": "viewer" })).send().await.assert_status(StatusCode::OK);
+    patch(EDITOR, "by demoted editor").send().await.assert_error(StatusCode::FORBIDDEN, "forbidden");
+    app.get(&todo).user(EDITOR).send().await.assert_status(StatusCode::OK);
+
+    // повышенный до владельца управляет участниками
+    app.put(&member(EDITOR)).user(OWNER).json(json!({ "role": "owner" })).send().await.assert_status(StatusCode::OK);
+    app.put(&member(OUTSIDER)).user(EDITOR).json(json!({ "role": "viewer" })).send().await.assert_status(StatusCode::OK);
+    app.get(&todo).user(OUTSIDER).send().await.assert_status(StatusCode::OK);
+
+    // исключённый теряет и чтение, даже если todo уже лежит в кэше
+    app.delete(&member(OUTSIDER)).user(EDITOR).send().await.assert_status(StatusCode::NO_CONTENT);
+    app.get(&todo).user(OUTSIDER).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+    app.get(&format!("/v1/workspaces/{workspace_id}/todos")).user(OUTSIDER).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+}
+
+#[tokio::test]
+async fn todos_from_before_workspaces_are_adopted_by_an_owner() {
+    let app = TestApp::spawn_upgraded(20250105, &["legacy one", "legacy two"]).await;
+    let workspace_id: i64 = sqlx::query_scalar("SELECT id FROM workspace").fetch_one(app.db.primary()).await.unwrap();
+    let todos = format!("/v1/workspaces/{workspace_id}/todos");
+
+    // после миграции у общего workspace нет участников, и его todo не видны никому
+    assert_eq!(workspace::orphaned(app.db.primary()).await.unwrap(), 1);
+    app.get(&todos).user(OWNER).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+
+    assert_eq!(workspace::adopt(app.db.primary(), OWNER).await.unwrap(), 1);
+    assert_eq!(workspace::orphaned(app.db.primary()).await.unwrap(), 0);
+    let list = app.get(&todos).user(OWNER).send().await.assert_status(StatusCode::OK).json();
+    let mut bodies: Vec<&str> = list.as_array().unwrap().iter().map(|todo| todo["body"].as_str().unwrap()).collect();
+    bodies.sort_unstable();
+    assert_eq!(bodies, ["legacy one", "legacy two"]);
+    let workspaces = app.get("/v1/workspaces").user(OWNER).send().await.assert_status(StatusCode::OK).json();
+    assert_eq!(workspaces[0]["role"], "owner");
+
+    // повторный запуск не трогает workspace, у которых уже есть участники
+    assert_eq!(workspace::adopt(app.db.primary(), OUTSIDER).await.unwrap(), 0);
+    app.get(&todos).user(OUTSIDER).send().await.assert_error(StatusCode::NOT_FOUND, "not_found");
+}
\ No newline at end of file